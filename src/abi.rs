@@ -0,0 +1,108 @@
+use anyhow::{bail, Result};
+
+use crate::error::DebuggerError;
+use crate::session::DebugSession;
+use crate::types::{ArrayCount, Type, TypeId};
+
+const EIGHTBYTE_SIZE: usize = 8;
+const MAX_REGISTERS_SIZE: usize = 2 * EIGHTBYTE_SIZE;
+
+/// class of 8 bytes part of a value passed in registers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EightbyteClass {
+    Integer, // rax, rdx
+    Sse,     // xmm0, xmm1
+}
+
+/// where function puts returned value, as described by System V x86_64 ABI
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReturnClass {
+    Void,
+    Registers(Vec<EightbyteClass>),
+    Memory, // caller passes buffer in rdi, callee returns it in rax
+}
+
+pub fn classify_return_type<R: gimli::Reader>(session: &DebugSession<R>, type_id: TypeId) -> Result<ReturnClass> {
+    if let Type::Void = session.get_type_storage().get(type_id)? {
+        return Ok(ReturnClass::Void);
+    }
+
+    let size = session.get_type_size(type_id)?;
+    if size > MAX_REGISTERS_SIZE {
+        return Ok(ReturnClass::Memory);
+    }
+
+    let mut classes = vec![None; size.div_ceil(EIGHTBYTE_SIZE)];
+    if !classify(session, type_id, 0, &mut classes)? {
+        return Ok(ReturnClass::Memory);
+    }
+
+    // padding only eightbyte doesn't matter, so take any register for it
+    Ok(ReturnClass::Registers(
+        classes.into_iter().map(|class| class.unwrap_or(EightbyteClass::Sse)).collect(),
+    ))
+}
+
+/// merges classes of scalar fields into their eightbytes, integer wins over sse.
+/// Returns false if the value must be passed in memory
+fn classify<R: gimli::Reader>(session: &DebugSession<R>, type_id: TypeId, offset: usize, classes: &mut [Option<EightbyteClass>]) -> Result<bool> {
+    let class = match session.get_type_storage().get(type_id)? {
+        Type::Base { encoding, size, .. } => match encoding {
+            // long double lives in x87 registers
+            gimli::DW_ATE_float if size > 8 => bail!(DebuggerError::UnsupportedReturnType("long double")),
+            gimli::DW_ATE_float | gimli::DW_ATE_complex_float => EightbyteClass::Sse,
+            _ => EightbyteClass::Integer,
+        },
+        Type::Pointer(_) | Type::String(_) | Type::Func(_) | Type::Enum { .. } => EightbyteClass::Integer,
+        Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) | Type::Typedef(_, subtype_id) => {
+            return classify(session, subtype_id, offset, classes)
+        }
+        Type::Struct { fields, .. } => {
+            for field in fields.iter() {
+                if !classify(session, field.type_id, offset + field.offset as usize, classes)? {
+                    return Ok(false);
+                }
+            }
+            return Ok(true);
+        }
+        Type::Union { fields, .. } => {
+            for field in fields.iter() {
+                if !classify(session, field.type_id, offset, classes)? {
+                    return Ok(false);
+                }
+            }
+            return Ok(true);
+        }
+        Type::Array {
+            subtype_id,
+            count: ArrayCount::Static(count),
+            bit_stride: None,
+        } => {
+            let subtype_size = session.get_type_size(subtype_id)?;
+            for i in 0..count {
+                if !classify(session, subtype_id, offset + i * subtype_size, classes)? {
+                    return Ok(false);
+                }
+            }
+            return Ok(true);
+        }
+        _ => return Ok(false),
+    };
+
+    // unaligned (packed) scalars go to memory
+    let size = session.get_type_size(type_id)?;
+    if size == 0 {
+        return Ok(true);
+    }
+    if !offset.is_multiple_of(size.min(EIGHTBYTE_SIZE)) {
+        return Ok(false);
+    }
+    for slot in &mut classes[offset / EIGHTBYTE_SIZE..=(offset + size - 1) / EIGHTBYTE_SIZE] {
+        *slot = match (*slot, class) {
+            (Some(EightbyteClass::Integer), _) | (_, EightbyteClass::Integer) => Some(EightbyteClass::Integer),
+            _ => Some(EightbyteClass::Sse),
+        };
+    }
+
+    Ok(true)
+}
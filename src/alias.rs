@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::error::DebuggerError;
+use crate::history;
+
+/// user defined command aliases, persisted as `alias name = command` lines in aliases section of history dotfile
+#[derive(Default)]
+pub struct Aliases {
+    aliases: BTreeMap<String, String>,
+    path: Option<PathBuf>,
+}
+
+impl Aliases {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let mut aliases = Self::default();
+
+        if let Some(path) = path.as_ref() {
+            let (_, lines) = history::read(path);
+            for line in lines {
+                if let Some((name, command)) = line.strip_prefix("alias ").and_then(|alias| alias.split_once(" = ")) {
+                    aliases.aliases.insert(String::from(name), String::from(command));
+                }
+            }
+        }
+
+        aliases.path = path;
+
+        aliases
+    }
+
+    /// replaces first token of the line with aliased command
+    pub fn expand(&self, line: &str) -> String {
+        let (name, rest) = match line.split_once(char::is_whitespace) {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (line, None),
+        };
+
+        match (self.aliases.get(name), rest) {
+            (Some(command), Some(rest)) => format!("{} {}", command, rest),
+            (Some(command), None) => command.clone(),
+            (None, _) => String::from(line),
+        }
+    }
+
+    pub fn add(&mut self, name: &str, command: &str) -> Result<()> {
+        self.aliases.insert(String::from(name), String::from(command));
+
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        if self.aliases.remove(name).is_none() {
+            return Err(DebuggerError::AliasNotFound.into());
+        }
+
+        self.save()
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&String, &String)> {
+        self.aliases.iter()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+
+        let lines = self.aliases.iter().map(|(name, command)| format!("alias {} = {}", name, command));
+
+        history::save_aliases(path, lines)
+    }
+}
@@ -1,20 +1,44 @@
 use std::cell::Cell;
 
+use crate::consts::INT3;
+
 #[derive(Debug, Clone)]
 pub struct Breakpoint {
+    pub id: usize,
     pub addr: u64,
-    pub original_bytecode: i64,
+    pub original_bytecode: Cell<i64>, // refreshed if the program rewrites the code while breakpoint is lifted
     pub loc: String,
     pub enabled: Cell<bool>,
+    pub lifted: Cell<bool>,        // int3 is taken out while the program is stopped at it or steps over it, breakpoint is not disabled
+    pub commands: Vec<String>,     // run when the breakpoint is hit
+    pub condition: Option<String>, // program stops at the breakpoint only when the condition is true
+    pub temporary: bool,           // removed after the first hit
+    pub log: Option<String>,       // logpoint prints the variable and the program goes on
 }
 
 impl Breakpoint {
-    pub fn new<S: Into<String>>(addr: u64, original_bytecode: i64, loc: S) -> Self {
+    pub fn new<S: Into<String>>(id: usize, addr: u64, original_bytecode: i64, loc: S) -> Self {
         Self {
+            id,
             addr,
-            original_bytecode,
+            original_bytecode: Cell::new(original_bytecode),
             loc: loc.into(),
             enabled: Cell::new(false),
+            lifted: Cell::new(false),
+            commands: Vec::new(),
+            condition: None,
+            temporary: false,
+            log: None,
         }
     }
+
+    /// breakpoint is referred by its location or by its id
+    pub fn is_named(&self, name: &str) -> bool {
+        self.loc == name || name.parse::<usize>().is_ok_and(|id| id == self.id)
+    }
+
+    /// program's own int3, or a breakpoint set by someone else
+    pub fn is_over_int3(&self) -> bool {
+        self.original_bytecode.get() & 0xff == INT3
+    }
 }
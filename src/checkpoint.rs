@@ -0,0 +1,28 @@
+use nix::sys::signal::Signal;
+
+/// copy of writable memory mapping
+#[derive(Debug)]
+pub struct MemoryRegion {
+    pub address: u64,
+    pub data: Vec<u8>,
+}
+
+/// registers and writable memory of the child at some point
+#[derive(Debug)]
+pub struct Checkpoint {
+    pub regs: libc::user_regs_struct,
+    pub regions: Vec<MemoryRegion>,
+    pub pending_signal: Option<Signal>,
+    pub breakpoint_hits: usize, // breakpoint hits recorded before the checkpoint
+}
+
+impl Checkpoint {
+    pub fn new(regs: libc::user_regs_struct, regions: Vec<MemoryRegion>, pending_signal: Option<Signal>, breakpoint_hits: usize) -> Self {
+        Self {
+            regs,
+            regions,
+            pending_signal,
+            breakpoint_hits,
+        }
+    }
+}
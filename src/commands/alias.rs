@@ -0,0 +1,30 @@
+use anyhow::Result;
+
+use crate::alias::Aliases;
+
+pub fn add(aliases: &mut Aliases, name: &str, command: &str) -> Result<()> {
+    aliases.add(name, command)?;
+    println!("alias set");
+
+    Ok(())
+}
+
+pub fn remove(aliases: &mut Aliases, name: &str) -> Result<()> {
+    aliases.remove(name)?;
+    println!("alias removed");
+
+    Ok(())
+}
+
+pub fn list(aliases: &Aliases) {
+    let aliases_iter = aliases.iter();
+
+    if aliases_iter.len() == 0 {
+        println!("no aliases");
+        return;
+    }
+
+    for (name, command) in aliases_iter {
+        println!("{} = {}", name, command);
+    }
+}
@@ -1,15 +1,47 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
 
+use crate::error::DebuggerError;
+use crate::fsm::{CommandParser, Rule};
 use crate::session::DebugSession;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use pest::Parser;
 
-pub fn add<'a, R, S>(session: &mut DebugSession<R>, loc: S) -> Result<()>
+/// program stops at the breakpoint only when condition is true
+pub fn add<'a, R, S>(session: &mut DebugSession<R>, loc: S, condition: Option<&str>) -> Result<()>
 where
     R: gimli::Reader,
     S: Into<Cow<'a, str>>,
 {
-    session.add_breakpoint(loc)?;
+    let breakpoint = session.add_breakpoint(loc)?;
     println!("breakpoint set");
+    if breakpoint.is_over_int3() {
+        println!(
+            "warning: code at {:#x} is already int3, breakpoint may already exist or the code is self-modifying",
+            breakpoint.addr
+        );
+    }
+    if let Some(condition) = condition {
+        let loc = breakpoint.loc.clone();
+        session.set_breakpoint_condition(&loc, Some(condition.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// breakpoint printing the variable without stopping
+pub fn log<R: gimli::Reader>(session: &mut DebugSession<R>, loc: &str, expr: &str) -> Result<()> {
+    session.add_logpoint(loc, expr)?;
+    println!("logpoint set");
+
+    Ok(())
+}
+
+/// breakpoint removed after the first hit
+pub fn tadd<R: gimli::Reader>(session: &mut DebugSession<R>, loc: &str) -> Result<()> {
+    session.add_temporary_breakpoint(loc)?;
+    println!("temporary breakpoint set");
 
     Ok(())
 }
@@ -22,15 +54,21 @@ pub fn remove<R: gimli::Reader>(session: &mut DebugSession<R>, loc: &str) -> Res
 }
 
 pub fn list<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
-    let breakpoints_iter = session.list_breakpoints();
+    let mut breakpoints = session.list_breakpoints();
+    breakpoints.sort_by_key(|breakpoint| breakpoint.id);
 
-    if breakpoints_iter.len() == 0 {
+    if breakpoints.is_empty() {
         println!("no breakpoints");
         return Ok(());
     }
 
-    for breakpoint in breakpoints_iter {
-        println!("{}", breakpoint.loc);
+    for breakpoint in breakpoints {
+        match (&breakpoint.condition, &breakpoint.log) {
+            (Some(condition), _) => println!("{}: {} if {}", breakpoint.id, breakpoint.loc, condition),
+            (None, Some(expr)) => println!("{}: {} (log {})", breakpoint.id, breakpoint.loc, expr),
+            (None, None) if breakpoint.temporary => println!("{}: {} (temporary)", breakpoint.id, breakpoint.loc),
+            (None, None) => println!("{}: {}", breakpoint.id, breakpoint.loc),
+        }
     }
 
     Ok(())
@@ -50,6 +88,122 @@ pub fn disable<R: gimli::Reader>(session: &DebugSession<R>, loc: &str) -> Result
     Ok(())
 }
 
+pub fn start_commands<R: gimli::Reader>(session: &DebugSession<R>, loc: &str) -> Result<()> {
+    if session.get_breakpoint(loc).is_none() {
+        bail!(DebuggerError::BreakpointNotFound);
+    }
+    println!("type commands one per line, end with a line saying just \"end\"");
+
+    Ok(())
+}
+
+pub fn set_commands<R: gimli::Reader>(session: &mut DebugSession<R>, loc: &str, commands: Vec<String>) -> Result<()> {
+    session.set_breakpoint_commands(loc, commands)?;
+    println!("commands set");
+
+    Ok(())
+}
+
 pub fn clear<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
     session.clear_breakpoints()
 }
+
+/// breakpoints are written as commands setting them, the way the user would type them
+pub fn save<R: gimli::Reader>(session: &DebugSession<R>, path: &str) -> Result<()> {
+    let mut breakpoints = session.list_breakpoints();
+    breakpoints.sort_by_key(|breakpoint| breakpoint.id);
+
+    let mut content = String::new();
+    for breakpoint in breakpoints.iter() {
+        let loc = &breakpoint.loc;
+        match (&breakpoint.log, &breakpoint.condition) {
+            (Some(expr), _) => content.push_str(&format!("logpoint {} {}\n", loc, expr)),
+            (None, _) if breakpoint.temporary => content.push_str(&format!("tbreak {}\n", loc)),
+            (None, Some(condition)) => content.push_str(&format!("break {} if {}\n", loc, condition)),
+            (None, None) => content.push_str(&format!("break {}\n", loc)),
+        }
+        // breakpoint we are stopped at is lifted until continue, it's not disabled
+        if !breakpoint.enabled.get() && !breakpoint.lifted.get() {
+            content.push_str(&format!("disable {}\n", loc));
+        }
+        if !breakpoint.commands.is_empty() {
+            content.push_str(&format!("commands {}\n", loc));
+            for command in breakpoint.commands.iter() {
+                content.push_str(&format!("{}\n", command));
+            }
+            content.push_str("end\n");
+        }
+    }
+    fs::write(path, content).map_err(|source| DebuggerError::FileAccess {
+        path: path.to_string(),
+        source,
+    })?;
+    println!("{} breakpoints saved", breakpoints.len());
+
+    Ok(())
+}
+
+/// sets breakpoints saved by `save`, locations which are gone from the program are skipped along with their settings
+pub fn source<R: gimli::Reader>(session: &mut DebugSession<R>, path: &str) -> Result<()> {
+    let content = fs::read_to_string(path).map_err(|source| DebuggerError::FileAccess {
+        path: path.to_string(),
+        source,
+    })?;
+    // saved location -> location of the set breakpoint, skipped locations are missing
+    let mut locs: HashMap<&str, String> = HashMap::new();
+    let mut lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+    while let Some(line) = lines.next() {
+        let pair = CommandParser::parse(Rule::command, line)
+            .map_err(|_| DebuggerError::InvalidCommand)?
+            .next()
+            .unwrap()
+            .into_inner()
+            .next()
+            .unwrap();
+        let rule = pair.as_rule();
+        let mut inner_pairs = pair.into_inner();
+        let loc = inner_pairs.next().unwrap().as_str();
+
+        let result = match rule {
+            Rule::add_breakpoint => session.add_breakpoint(loc),
+            Rule::add_temporary_breakpoint => session.add_temporary_breakpoint(loc),
+            Rule::add_logpoint => session.add_logpoint(loc, inner_pairs.next().unwrap().as_str()),
+            Rule::disable_breakpoint => {
+                if let Some(loc) = locs.get(loc) {
+                    session.disable_breakpoint(loc)?;
+                }
+                continue;
+            }
+            Rule::breakpoint_commands => {
+                let commands = lines.by_ref().take_while(|&line| line != "end").map(String::from).collect();
+                if let Some(loc) = locs.get(loc) {
+                    session.set_breakpoint_commands(loc, commands)?;
+                }
+                continue;
+            }
+            _ => bail!(DebuggerError::InvalidCommand),
+        };
+
+        match result {
+            Ok(breakpoint) => {
+                locs.insert(loc, breakpoint.loc.clone());
+            }
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<DebuggerError>(),
+                    Some(DebuggerError::LocNotFound | DebuggerError::BreakpointAlreadyExist)
+                ) =>
+            {
+                println!("warning: breakpoint {} skipped: {}", loc, e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if let Some(condition) = inner_pairs.next() {
+            session.set_breakpoint_condition(&locs[loc], Some(condition.as_str().to_string()))?;
+        }
+    }
+    println!("{} breakpoints set", locs.len());
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use anyhow::Result;
+
+use crate::error::DebuggerError;
+use crate::session::DebugSession;
+
+pub fn save<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
+    let id = session.save_checkpoint()?;
+    println!("checkpoint {} saved", id);
+
+    Ok(())
+}
+
+pub fn restore<R: gimli::Reader>(session: &DebugSession<R>, id: &str) -> Result<()> {
+    session.restore_checkpoint(id.parse().map_err(|_| DebuggerError::CheckpointNotFound)?)?;
+    println!("checkpoint {} restored", id);
+
+    Ok(())
+}
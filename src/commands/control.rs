@@ -1,30 +1,159 @@
-use crate::{error::DebuggerError, session::DebugSession};
-use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{
+    condition::{Operand, Scalar},
+    error::DebuggerError,
+    loc_finder::LocFinder,
+    printer::Printer,
+    session::{DebugSession, ExitStatus, FollowForkMode, SessionState},
+    var::Var,
+};
+use anyhow::{anyhow, bail, Result};
 
 pub fn run<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
     session.run()?;
-    session.wait()
+    session.wait()?;
+    report_stop(session);
+    Ok(())
+}
+
+/// breakpoints are ignored till the end, ctrl-c stops the program where it is
+pub fn run_to_exit<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
+    session.run_to_exit()?;
+
+    match session.get_exit_status() {
+        Some(ExitStatus::Code(code)) => println!("program exited with code {}", code),
+        Some(ExitStatus::Signal(signal)) => println!("program terminated by signal {}", signal.as_str()),
+        None if session.get_state() == SessionState::Running => println!("program interrupted"),
+        None => (),
+    }
+
+    Ok(())
 }
 
 pub fn stop<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
     session.stop()
 }
 
+pub fn detach<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
+    session.detach()?;
+    println!("program detached");
+    Ok(())
+}
+
+/// spawns the program again, it waits for `run` like after the debugger start
+pub fn restart<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
+    session.restart()?;
+    println!("program restarted");
+    Ok(())
+}
+
+pub fn kill<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
+    session.restart()?;
+    println!("program killed");
+    Ok(())
+}
+
 pub fn cont<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
     session.cont()?;
-    session.wait()
+    session.wait()?;
+    report_stop(session);
+    Ok(())
 }
 
-pub fn step<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
-    session.step()
+/// goes back to the previous breakpoint hit by running the program again
+pub fn reverse_continue<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
+    session.reverse_continue()?;
+    report_stop(session);
+    Ok(())
+}
+
+/// steps over `count` lines, reaching a breakpoint, watchpoint or a signal stops earlier
+pub fn step<R: gimli::Reader>(session: &mut DebugSession<R>, count: Option<&str>) -> Result<()> {
+    let count = match count {
+        Some(count) => count.parse::<usize>().ok().filter(|&count| count > 0).ok_or(DebuggerError::InvalidValue)?,
+        None => 1,
+    };
+
+    for i in 0..count {
+        session.step()?;
+        if session.get_state() == SessionState::Exited || i + 1 == count {
+            break;
+        }
+        // signal is delivered on the next step, so it must be reported now
+        if session.get_watchpoint_hit().is_some() || session.get_current_breakpoint()?.is_some() || session.get_pending_signal().is_some() {
+            break;
+        }
+    }
+    report_stop(session);
+
+    Ok(())
 }
 
 pub fn step_in<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
-    session.step_in()
+    session.step_in()?;
+    report_stop(session);
+    Ok(())
 }
 
 pub fn step_out<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
-    session.step_out()
+    session.step_out()?;
+    report_stop(session);
+    Ok(())
+}
+
+pub fn set_follow_fork_mode<R: gimli::Reader>(session: &DebugSession<R>, mode: &str) -> Result<()> {
+    session.set_follow_fork_mode(FollowForkMode::try_from(mode)?);
+    Ok(())
+}
+
+pub fn set_disable_randomization<R: gimli::Reader>(session: &DebugSession<R>, value: &str) -> Result<()> {
+    session.set_disable_randomization(value == "on");
+    Ok(())
+}
+
+/// function treated as the entry of the program, for stepping out of it and picking default unit
+pub fn set_main_function<R: gimli::Reader>(session: &mut DebugSession<R>, func_name: &str) -> Result<()> {
+    session.set_main_func(func_name)
+}
+
+pub fn advance<R: gimli::Reader>(session: &mut DebugSession<R>, loc: &str) -> Result<()> {
+    session.advance(loc)?;
+    report_stop(session);
+    Ok(())
+}
+
+pub fn until<R: gimli::Reader>(session: &mut DebugSession<R>, loc: &str) -> Result<()> {
+    session.until(loc)?;
+    report_stop(session);
+    Ok(())
+}
+
+pub fn finish<R: gimli::Reader>(session: &mut DebugSession<R>) -> Result<()> {
+    let func = session.get_current_func()?.ok_or(anyhow!(DebuggerError::InvalidLocation))?;
+    if let Some(value) = session.finish()? {
+        Printer::new(session).print(&Var::new(format!("{}()", func), value))?;
+    }
+    report_stop(session);
+    Ok(())
+}
+
+/// only integer and pointer arguments are passed, value of `void` function is not printed
+pub fn call<R: gimli::Reader>(session: &mut DebugSession<R>, expr: &str, func: &str, args: &[Operand]) -> Result<()> {
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+        values.push(match arg.get_value(session)? {
+            Scalar::Int(value) => value as u64,
+            Scalar::Float(_) => bail!(DebuggerError::UnsupportedArgument),
+        });
+    }
+
+    if let Some(value) = session.call_func(func, &values)? {
+        Printer::new(session).print(&Var::new(expr, value))?;
+    }
+
+    Ok(())
 }
 
 pub fn location<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
@@ -32,3 +161,123 @@ pub fn location<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
     println!("{}", loc);
     Ok(())
 }
+
+/// variables are read in the selected frame, 0 is the innermost frame
+pub fn frame<R: gimli::Reader>(session: &DebugSession<R>, frame: &str) -> Result<()> {
+    let frame = frame.parse::<usize>().map_err(|_| DebuggerError::InvalidValue)?;
+    frame_by_number(session, frame)
+}
+
+/// selects caller of the selected frame, count frames up
+pub fn up<R: gimli::Reader>(session: &DebugSession<R>, count: Option<&str>) -> Result<()> {
+    let frame = session.get_selected_frame() + parse_frame_count(count)?;
+    frame_by_number(session, frame)
+}
+
+/// selects callee of the selected frame, count frames down
+pub fn down<R: gimli::Reader>(session: &DebugSession<R>, count: Option<&str>) -> Result<()> {
+    let frame = session
+        .get_selected_frame()
+        .checked_sub(parse_frame_count(count)?)
+        .ok_or(DebuggerError::InnermostFrame)?;
+    frame_by_number(session, frame)
+}
+
+fn parse_frame_count(count: Option<&str>) -> Result<usize> {
+    match count {
+        Some(count) => Ok(count.parse::<usize>().map_err(|_| DebuggerError::InvalidValue)?),
+        None => Ok(1),
+    }
+}
+
+fn frame_by_number<R: gimli::Reader>(session: &DebugSession<R>, frame: usize) -> Result<()> {
+    let pc = session.select_frame(frame)?;
+    print_frame(session.get_loc_finder(), frame, pc);
+
+    Ok(())
+}
+
+/// frames up to main, innermost first
+pub fn backtrace<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
+    for (frame, pc) in session.backtrace()?.into_iter().enumerate() {
+        print_frame(session.get_loc_finder(), frame, pc);
+    }
+
+    Ok(())
+}
+
+fn print_frame<R: gimli::Reader>(loc_finder: &LocFinder<R>, frame: usize, pc: u64) {
+    let func = loc_finder.find_func_by_address(pc);
+    let line = loc_finder.find_enclosing_line(pc);
+    println!("#{} {} at {}", frame, func.as_deref().unwrap_or("??"), line.as_deref().unwrap_or("??"));
+}
+
+pub fn set_stop_context<R: gimli::Reader>(session: &DebugSession<R>, lines: &str) -> Result<()> {
+    session.set_stop_context(lines.parse::<usize>().map_err(|_| DebuggerError::InvalidValue)?);
+    Ok(())
+}
+
+pub fn add_substitute_path<R: gimli::Reader>(session: &DebugSession<R>, from: &str, to: &str) -> Result<()> {
+    session.add_substitute_path(PathBuf::from(from), PathBuf::from(to));
+    Ok(())
+}
+
+/// reports pending signal and prints source lines around the current line if enabled
+pub fn report_stop<R: gimli::Reader>(session: &DebugSession<R>) {
+    report_signal(session);
+    if let Err(e) = report_watchpoint(session) {
+        log::warn!("can't print watchpoint: {:#}", e);
+    }
+
+    let lines = session.get_stop_context();
+    if lines == 0 || session.get_state() != SessionState::Running {
+        return;
+    }
+
+    // missing sources shouldn't break stepping
+    if let Err(e) = print_stop_context(session, lines) {
+        log::warn!("can't print source: {:#}", e);
+    }
+}
+
+fn print_stop_context<R: gimli::Reader>(session: &DebugSession<R>, lines: usize) -> Result<()> {
+    let loc = match session.get_current_line()? {
+        Some(loc) => loc,
+        None => return Ok(()),
+    };
+    println!("{}", loc);
+
+    let (filepath, line) = LocFinder::<R>::parse_fileline(&loc).ok_or(anyhow!("parse location"))?;
+    let source_path = match session.get_source_path(filepath) {
+        Some(source_path) => source_path,
+        None => bail!("source path of {} not found", filepath),
+    };
+    let source = fs::read_to_string(&source_path).map_err(|e| anyhow!("read {}: {}", source_path.display(), e))?;
+
+    // current line is in the middle of the context
+    let first_line = (line as usize).saturating_sub((lines - 1) / 2).max(1);
+    for (index, text) in source.lines().enumerate().skip(first_line - 1).take(lines) {
+        println!("{}\t{}", index + 1, text);
+    }
+
+    Ok(())
+}
+
+fn report_watchpoint<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
+    let Some(hit) = session.get_watchpoint_hit() else {
+        return Ok(());
+    };
+
+    println!("watchpoint {}: {}", hit.id, hit.expr);
+    let printer = Printer::new(session);
+    println!("old {}", printer.format(&Var::new(hit.expr.as_str(), hit.old_value))?);
+    println!("new {}", printer.format(&Var::new(hit.expr.as_str(), hit.new_value))?);
+
+    Ok(())
+}
+
+pub fn report_signal<R: gimli::Reader>(session: &DebugSession<R>) {
+    if let Some(signal) = session.get_pending_signal() {
+        println!("program received signal {}", signal.as_str());
+    }
+}
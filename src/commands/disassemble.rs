@@ -0,0 +1,47 @@
+use anyhow::Result;
+
+use crate::disasm;
+use crate::error::DebuggerError;
+use crate::session::DebugSession;
+
+/// disassembles function of the selected frame or the given function, pc of the frame is marked
+pub fn disassemble<R: gimli::Reader>(session: &DebugSession<R>, func: Option<&str>) -> Result<()> {
+    let loc_finder = session.get_loc_finder();
+    let pc = session.get_frame_context()?.pc();
+    let address = match func {
+        Some(func) => loc_finder.find_loc(func)?.ok_or_else(|| DebuggerError::FuncNotFound(func.to_string()))?,
+        None => pc,
+    };
+    let start = loc_finder.find_func_start(address).ok_or(DebuggerError::InvalidLocation)?;
+    let end = loc_finder.find_func_end(address).ok_or(DebuggerError::InvalidLocation)?;
+
+    let mut code = session.read_bytes(start, (end - start + 1) as usize)?.to_vec();
+    // memory has int3 at breakpoints
+    for breakpoint in session.list_breakpoints() {
+        if (start..=end).contains(&breakpoint.addr) {
+            code[(breakpoint.addr - start) as usize] = breakpoint.original_bytecode.get() as u8;
+        }
+    }
+
+    for instruction in disasm::disassemble(&code, start) {
+        let marker = if instruction.address == pc { "=>" } else { "  " };
+        let symbol = instruction
+            .target
+            .and_then(|target| Some((loc_finder.find_func_by_address(target)?, target - loc_finder.find_func_start(target)?)))
+            .map(|(func, offset)| match offset {
+                0 => format!(" <{}>", func),
+                offset => format!(" <{}+{}>", func, offset),
+            })
+            .unwrap_or_default();
+        println!(
+            "{} {:#x} <+{}>: {}{}",
+            marker,
+            instruction.address,
+            instruction.address - start,
+            instruction,
+            symbol
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+
+use crate::display::{Display, DisplayStatus};
+use crate::error::DebuggerError;
+use crate::path::Path;
+use crate::printer::Printer;
+use crate::session::DebugSession;
+
+pub fn add<R: gimli::Reader>(session: &mut DebugSession<R>, expr: &str) -> usize {
+    session.add_display(expr)
+}
+
+pub fn remove<R: gimli::Reader>(session: &mut DebugSession<R>, id: &str) -> Result<()> {
+    let id = id.parse::<usize>().map_err(|_| DebuggerError::DisplayNotFound)?;
+    session.remove_display(id)?;
+    println!("display removed");
+
+    Ok(())
+}
+
+pub fn list<R: gimli::Reader>(session: &DebugSession<R>) {
+    let displays = session.get_displays();
+
+    if displays.is_empty() {
+        println!("no displays");
+        return;
+    }
+
+    for display in displays {
+        println!("{}: {} ({})", display.id, display.expr, display.status.get());
+    }
+}
+
+/// evaluation errors are printed in place of the value, so one broken display doesn't hide others
+pub fn show<R: gimli::Reader>(session: &DebugSession<R>, display: &Display, path: &Path) {
+    match session.get_var(path).and_then(|var| Printer::new(session).format(&var)) {
+        Ok(text) => {
+            display.status.set(DisplayStatus::Ok);
+            println!("{}: {}", display.id, text);
+        }
+        Err(e) => {
+            display.status.set(DisplayStatus::Failed);
+            println!("{}: {} = <error: {}>", display.id, display.expr, e);
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use anyhow::{bail, Result};
+
+use crate::error::DebuggerError;
+use crate::examine::{ExamineFormat, UNITS_PER_LINE};
+use crate::location::ValueLoc;
+use crate::path::Path;
+use crate::session::DebugSession;
+
+/// memory is read at the address or at the variable location, `&var` and casted addresses are taken as is
+pub fn examine<R: gimli::Reader>(session: &DebugSession<R>, address: Option<u64>, path: Option<&Path>, format: ExamineFormat) -> Result<()> {
+    let address = match (address, path) {
+        (Some(address), _) => address,
+        (None, Some(path)) => match session.get_var_loc(path)?.location {
+            ValueLoc::Address(address) | ValueLoc::Value(address) => address,
+            ValueLoc::Register { .. } => bail!(DebuggerError::NoAddress),
+        },
+        (None, None) => bail!(DebuggerError::InvalidPath),
+    };
+
+    let len = format.count.checked_mul(format.size).ok_or(DebuggerError::InvalidFormat)?;
+    let buf = session.read_bytes(address, len)?;
+    for (i, line) in buf.chunks(UNITS_PER_LINE * format.size).enumerate() {
+        let units = line.chunks(format.size).map(|unit| format.format_unit(unit)).collect::<Vec<_>>();
+        println!("{:#x}: {}", address + (i * UNITS_PER_LINE * format.size) as u64, units.join(" "));
+    }
+
+    Ok(())
+}
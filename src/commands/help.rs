@@ -2,21 +2,71 @@ pub fn help() {
     println!(
         "Commands:
 
-breakpoint | break | b - set a breakpoint
-remove | rm - remove a breakpoint
-list | l - list breakpoints
+breakpoint | break | b - set a breakpoint, optionally with condition (b 10 if i == 500)
+tbreak | tb - set a breakpoint removed after the first hit
+logpoint | logp - print a variable every time the location is hit without stopping
+remove | rm - remove a breakpoint by location or id
+list | l - list breakpoints with ids
 disable - disable breakpoint
 enable - enable breakpoint
 clear - remove all breakpoints
+save-breakpoints - save breakpoint locations to a file
+source-breakpoints - set breakpoints saved to a file
+commands - attach commands to a breakpoint, typed one per line until end
 run | r - run the program
+run-to-exit | rune - run the program past all breakpoints until it exits and print exit status
 stop - stop the execution
+kill - kill the program, it can be run again
+restart | rerun - launch the program again, also after it exited
+detach - let the program run on its own
+checkpoint - save program memory and registers
+restart-checkpoint - restore saved checkpoint (I/O is not undone)
 continue | cont | c - continue execution of the program
-step - run the program until next line
+reverse-continue | rc - run the program again and stop at the previous breakpoint hit
+step | next | n - run the program until next line, calls are stepped over (n 3 - step 3 lines)
 step-in - run into function
 step-out - run out of current function
-print | p - print variable
+advance - run until location or until current function returns
+until | u - run until line of current function (leaves loops), other lines run out of current function
+finish - run out of current function and print returned value
+call - call function with integer or pointer arguments and print returned value (call add(x, 2))
+watch - run until condition becomes true (watch count > 10)
+watch VAR - stop when the program writes another value to the variable
+unwatch - remove watchpoint by id
+print | p - print variable (print/r - print raw bytes, print/x - hex, print/d - decimal, print *(struct node *)ptr - cast)
+examine | x - print raw memory at address or variable (x/16xb 0x1234 - count, format x|d|u|c, size b|h|w|g)
+disassemble | disas - print instructions of current function or function by name
+ptype - print type of variable with struct members and enum values (ptype/o - with offsets, sizes and holes)
+whatis - print type name of variable without expanding it
+display - print variable every time the program stops, without variable prints all displays
+undisplay - remove display by id
+info display - list displays and whether they were printed successfully last time
 set - modify variable
+set print - change printing (string-length N, string-escape on|off)
+set radix | set input-radix | set output-radix - base of integers printed and parsed without format (8, 10 or 16)
+set summary-format - print struct as a summary of its members (set summary-format MyVec \"{{len}} items @ {{data}}\")
+set stop-context | set listsize - number of source lines printed on stop, 0 disables it
+set substitute-path - replace prefix of source paths (set substitute-path /build /home/src)
+set follow-fork-mode - debug parent or child after fork
+set main-function - function treated as the program entry instead of main
+set disable-randomization - run the program without address space randomization (on|off, default on)
 location | loc - print current location
+frame - select frame (frame 1 is the caller) to print and set its variables
+up | down - select caller or callee of the selected frame (up 2 moves two frames)
+backtrace | bt - print call stack up to main
+info line-table - print line table rows of a function
+info scope - list variables visible at a location and their storage
+info args - print arguments of the selected frame
+info locals - print local variables of the selected frame
+info globals - print global variables
+info registers - print general purpose registers of the selected frame
+info all-registers - print general purpose, x87, mmx, sse and avx registers
+info proc mappings - list memory regions with permissions, offsets and file paths
+add-inferior - start another program in the session
+info inferiors - list inferiors
+inferior - select inferior by id
+alias - define command alias (alias bt = backtrace) or list aliases
+unalias - remove command alias
 quit | q - quit the program
 "
     );
@@ -0,0 +1,30 @@
+use anyhow::Result;
+
+use crate::error::DebuggerError;
+use crate::inferiors::Inferiors;
+use crate::session::SessionState;
+
+pub fn add<R: gimli::Reader>(inferiors: &mut Inferiors<R>, prog: &str, args: &[&str]) -> Result<()> {
+    let id = inferiors.add(prog, args)?;
+    println!("inferior {} added", id);
+
+    Ok(())
+}
+
+pub fn select<R: gimli::Reader>(inferiors: &mut Inferiors<R>, id: &str) -> Result<()> {
+    inferiors.select(id.parse().map_err(|_| DebuggerError::InferiorNotFound)?)?;
+    println!("switched to inferior {}", id);
+
+    Ok(())
+}
+
+pub fn list<R: gimli::Reader>(inferiors: &Inferiors<R>) {
+    for (id, session, is_current) in inferiors.iter() {
+        let state = match session.get_state() {
+            SessionState::Started => "not started",
+            SessionState::Running => "running",
+            SessionState::Exited => "exited",
+        };
+        println!("{} {} {} {}", if is_current { "*" } else { " " }, id, session.get_prog().display(), state);
+    }
+}
@@ -0,0 +1,164 @@
+use std::rc::Rc;
+
+use anyhow::Result;
+
+use crate::error::DebuggerError;
+use crate::loc_finder::{LineRowKind, VarKind};
+use crate::printer::Printer;
+use crate::session::{DebugSession, SessionState};
+use crate::xstate::{self, ST_REGISTERS_COUNT, XMM_REGISTERS_COUNT};
+
+pub fn line_table<R: gimli::Reader>(session: &DebugSession<R>, func: Option<&str>) -> Result<()> {
+    let func = match func {
+        Some(func) => Rc::from(func),
+        None if session.get_state() == SessionState::Running => session.get_current_func()?.ok_or(DebuggerError::InvalidLocation)?,
+        None => return Err(DebuggerError::InvalidLocation.into()),
+    };
+    let rows = session.get_loc_finder().get_line_table(&func).ok_or(DebuggerError::LocNotFound)?;
+
+    for row in rows {
+        let note = match row.kind {
+            LineRowKind::Line => "",
+            LineRowKind::Prologue => " (prologue)",
+            LineRowKind::Epilogue => " (epilogue)",
+        };
+        println!("{:#x} {}{}", row.address, row.fileline, note);
+    }
+
+    Ok(())
+}
+
+pub fn scope<R: gimli::Reader>(session: &DebugSession<R>, loc: Option<&str>) -> Result<()> {
+    let address = match loc {
+        Some(loc) => session.resolve_loc(loc)?.1,
+        None if session.get_state() == SessionState::Running => session.get_ip()?,
+        None => return Err(DebuggerError::InvalidLocation.into()),
+    };
+
+    let scope = session.get_scope(address)?;
+    if scope.is_empty() {
+        println!("no variables");
+        return Ok(());
+    }
+
+    for (name, location) in scope {
+        println!("{}: {}", name, location);
+    }
+
+    Ok(())
+}
+
+/// variables of the selected frame, arguments and locals don't include globals
+pub fn vars<R: gimli::Reader>(session: &DebugSession<R>, kind: VarKind) -> Result<()> {
+    let vars = session.get_vars_of_kind(kind)?;
+    if vars.is_empty() {
+        match kind {
+            VarKind::Arg => println!("no arguments"),
+            VarKind::Local => println!("no locals"),
+            VarKind::Global => println!("no globals"),
+        }
+        return Ok(());
+    }
+
+    let printer = Printer::new(session);
+    for var in vars.iter() {
+        printer.print(var)?;
+    }
+
+    Ok(())
+}
+
+/// memory regions of the program, including stack, heap and anonymous mappings
+pub fn proc_mappings<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
+    println!("{:<18} {:<18} {:<10} {:<10} {:<5} pathname", "start", "end", "size", "offset", "perms");
+    for mapping in session.get_memory_mappings()? {
+        println!(
+            "{:<18} {:<18} {:<10} {:<10} {:<5} {}",
+            format!("{:#x}", mapping.start),
+            format!("{:#x}", mapping.end),
+            format!("{:#x}", mapping.size()),
+            format!("{:#x}", mapping.offset),
+            mapping.perms,
+            mapping.pathname.as_deref().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+/// general purpose registers of the selected frame, callers have only callee saved registers restored
+pub fn registers<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
+    let ctx = session.get_frame_context()?;
+    for (name, value) in general_registers(&ctx.regs) {
+        println!("{} {:#x}", name, value);
+    }
+
+    Ok(())
+}
+
+/// general purpose, x87, mmx, sse and avx registers
+pub fn all_registers<R: gimli::Reader>(session: &DebugSession<R>) -> Result<()> {
+    let regs = session.get_regs()?;
+    let segment_registers = [
+        ("cs", regs.cs),
+        ("ss", regs.ss),
+        ("ds", regs.ds),
+        ("es", regs.es),
+        ("fs", regs.fs),
+        ("gs", regs.gs),
+        ("fs_base", regs.fs_base),
+        ("gs_base", regs.gs_base),
+    ];
+    for (name, value) in general_registers(&regs).into_iter().chain(segment_registers) {
+        println!("{} {:#x}", name, value);
+    }
+
+    let xstate = session.get_xstate()?;
+    for i in 0..ST_REGISTERS_COUNT {
+        let st = xstate.st(i);
+        println!("st{} {} (raw {})", i, xstate::f80_to_f64(st), xstate::format_vector(st));
+    }
+    println!("fctrl {:#x}", xstate.fcw());
+    println!("fstat {:#x}", xstate.fsw());
+    println!("ftag {:#x}", xstate.ftw());
+    println!("fop {:#x}", xstate.fop());
+    println!("fioff {:#x}", xstate.fip());
+    println!("fooff {:#x}", xstate.fdp());
+    println!("mxcsr {:#x}", xstate.mxcsr());
+    for i in 0..ST_REGISTERS_COUNT {
+        println!("mm{} {:#x}", i, xstate.mm(i));
+    }
+    for i in 0..XMM_REGISTERS_COUNT {
+        println!("xmm{} {}", i, xstate::format_vector(xstate.xmm(i)));
+    }
+    for i in 0..XMM_REGISTERS_COUNT {
+        if let Some(ymm) = xstate.ymm(i) {
+            println!("ymm{} {}", i, xstate::format_vector(&ymm));
+        }
+    }
+
+    Ok(())
+}
+
+fn general_registers(regs: &libc::user_regs_struct) -> [(&'static str, u64); 18] {
+    [
+        ("rax", regs.rax),
+        ("rbx", regs.rbx),
+        ("rcx", regs.rcx),
+        ("rdx", regs.rdx),
+        ("rsi", regs.rsi),
+        ("rdi", regs.rdi),
+        ("rbp", regs.rbp),
+        ("rsp", regs.rsp),
+        ("r8", regs.r8),
+        ("r9", regs.r9),
+        ("r10", regs.r10),
+        ("r11", regs.r11),
+        ("r12", regs.r12),
+        ("r13", regs.r13),
+        ("r14", regs.r14),
+        ("r15", regs.r15),
+        ("rip", regs.rip),
+        ("eflags", regs.eflags),
+    ]
+}
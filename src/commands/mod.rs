@@ -1,4 +1,12 @@
+pub mod alias;
 pub mod breakpoints;
+pub mod checkpoint;
 pub mod control;
+pub mod disassemble;
+pub mod display;
+pub mod examine;
 pub mod help;
+pub mod inferior;
+pub mod info;
 pub mod var;
+pub mod watch;
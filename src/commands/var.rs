@@ -1,11 +1,14 @@
-use anyhow::{bail, Result};
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
 use bytes::{BufMut, BytesMut};
 
 use crate::error::DebuggerError;
-use crate::path::Path;
-use crate::printer::Printer;
+use crate::path::{Path, PrefixOperator};
+use crate::printer::{parse_summary_format, Format, Printer, Radix};
 use crate::session::DebugSession;
-use crate::types::Type;
+use crate::types::{Type, TypeId};
 use crate::utils::string_parser::parse_string_literal;
 
 pub fn print_var<R: gimli::Reader>(session: &DebugSession<R>, path: Option<&Path>) -> Result<()> {
@@ -26,36 +29,127 @@ pub fn print_var<R: gimli::Reader>(session: &DebugSession<R>, path: Option<&Path
     Ok(())
 }
 
+pub fn print_formatted_var<R: gimli::Reader>(session: &DebugSession<R>, path: Option<&Path>, format: Format) -> Result<()> {
+    let printer = Printer::new(session).with_format(format);
+
+    match (format, path) {
+        (Format::Raw, Some(path)) => {
+            let loc = session.get_var_loc(path)?;
+            let buf = session.read_loc(&loc)?;
+            printer.print_raw(&DebugSession::<R>::get_var_name(path)?, &loc, &buf)?;
+        }
+        (Format::Raw, None) => bail!(DebuggerError::InvalidPath),
+        (_, Some(path)) => printer.print(&session.get_var(path)?)?,
+        (_, None) => {
+            for var in session.get_vars()?.iter() {
+                printer.print(var)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn print_type<R: gimli::Reader>(session: &DebugSession<R>, path: &Path, offsets: bool) -> Result<()> {
+    let loc = session.get_var_loc(path)?;
+    println!("{}", Printer::new(session).format_type_definition(loc.type_id, offsets)?);
+
+    Ok(())
+}
+
+pub fn print_type_name<R: gimli::Reader>(session: &DebugSession<R>, path: &Path) -> Result<()> {
+    let loc = session.get_var_loc(path)?;
+    println!("{}", Printer::new(session).format_type_name(loc.type_id)?);
+
+    Ok(())
+}
+
+pub fn set_print_setting<R: gimli::Reader>(session: &DebugSession<R>, setting: &str, value: &str) -> Result<()> {
+    let mut settings = session.get_print_settings();
+
+    match setting {
+        "string-length" => {
+            // 0 means unlimited
+            settings.string_length = match value.parse::<usize>().map_err(|_| DebuggerError::InvalidValue)? {
+                0 => None,
+                length => Some(length),
+            };
+        }
+        "string-escape" => {
+            settings.escape_strings = match value {
+                "on" => true,
+                "off" => false,
+                _ => bail!(DebuggerError::InvalidValue),
+            };
+        }
+        _ => bail!(DebuggerError::InvalidCommand),
+    }
+
+    session.set_print_settings(settings);
+
+    Ok(())
+}
+
+/// `radix` sets both input and output radix
+pub fn set_radix<R: gimli::Reader>(session: &DebugSession<R>, setting: &str, value: &str) -> Result<()> {
+    let radix = Radix::try_from(value)?;
+
+    if setting != "input-radix" {
+        let mut settings = session.get_print_settings();
+        settings.radix = radix;
+        session.set_print_settings(settings);
+    }
+    if setting != "output-radix" {
+        session.set_input_radix(radix);
+    }
+
+    Ok(())
+}
+
+/// struct is printed as the summary with `{field}` placeholders replaced by member values
+pub fn set_summary_format<R: gimli::Reader>(session: &DebugSession<R>, type_name: &str, format: &str) -> Result<()> {
+    let format = parse_string_literal(format).map_err(|_| DebuggerError::InvalidValue)?;
+    session.set_summary_format(type_name, parse_summary_format(&format)?);
+
+    Ok(())
+}
+
 pub fn set_var<R: gimli::Reader>(session: &DebugSession<R>, path: &Path, value: &str) -> Result<()> {
     let loc = session.get_var_loc(path)?;
+    let radix = session.get_input_radix();
 
     let mut buf = BytesMut::new();
     match session.get_type_storage().unwind_type(loc.type_id)? {
         Type::Base { encoding, size, .. } => match encoding {
             gimli::DW_ATE_boolean => {
-                let value = value.parse::<bool>().map_err(|_| DebuggerError::InvalidValue)?;
+                // _Bool is an integer in c, so 0 and 1 are accepted too
+                let value = match value {
+                    "true" | "1" => true,
+                    "false" | "0" => false,
+                    _ => bail!(DebuggerError::InvalidValue),
+                };
                 buf.put_i8(value as i8);
             }
-            gimli::DW_ATE_signed => match size {
-                1 => buf.put_i8(value.parse::<i8>().map_err(|_| DebuggerError::InvalidValue)?),
-                2 => buf.put_i16_ne(value.parse::<i16>().map_err(|_| DebuggerError::InvalidValue)?),
-                4 => buf.put_i32_ne(value.parse::<i32>().map_err(|_| DebuggerError::InvalidValue)?),
-                8 => buf.put_i64_ne(value.parse::<i64>().map_err(|_| DebuggerError::InvalidValue)?),
+            gimli::DW_ATE_signed | gimli::DW_ATE_signed_char => match size {
+                1 => buf.put_i8(parse_int::<i8>(value, radix)?),
+                2 => buf.put_i16_ne(parse_int::<i16>(value, radix)?),
+                4 => buf.put_i32_ne(parse_int::<i32>(value, radix)?),
+                8 => buf.put_i64_ne(parse_int::<i64>(value, radix)?),
                 _ => bail!("unsupported byte size"),
             },
-            gimli::DW_ATE_unsigned => match size {
-                1 => buf.put_u8(value.parse::<u8>().map_err(|_| DebuggerError::InvalidValue)?),
-                2 => buf.put_u16_ne(value.parse::<u16>().map_err(|_| DebuggerError::InvalidValue)?),
-                4 => buf.put_u32_ne(value.parse::<u32>().map_err(|_| DebuggerError::InvalidValue)?),
-                8 => buf.put_u64_ne(value.parse::<u64>().map_err(|_| DebuggerError::InvalidValue)?),
+            gimli::DW_ATE_unsigned | gimli::DW_ATE_unsigned_char | gimli::DW_ATE_UTF => match size {
+                1 => buf.put_u8(parse_int::<u8>(value, radix)?),
+                2 => buf.put_u16_ne(parse_int::<u16>(value, radix)?),
+                4 => buf.put_u32_ne(parse_int::<u32>(value, radix)?),
+                8 => buf.put_u64_ne(parse_int::<u64>(value, radix)?),
                 _ => bail!("unsupported byte size"),
             },
             gimli::DW_ATE_float => match size {
-                4 => buf.put_f32_ne(value.parse::<f32>().map_err(|_| DebuggerError::InvalidValue)?),
-                8 => buf.put_f64_ne(value.parse::<f64>().map_err(|_| DebuggerError::InvalidValue)?),
+                4 => buf.put_f32_ne(parse_float(value)?),
+                8 => buf.put_f64_ne(parse_float(value)?),
                 _ => bail!("unsupported byte size"),
             },
-            _ => bail!("unsupported encoding"),
+            _ => bail!(DebuggerError::UnsupportedEncoding(encoding)),
         },
         Type::Pointer(_) => {
             let ptr = if value == "null" {
@@ -65,17 +159,21 @@ pub fn set_var<R: gimli::Reader>(session: &DebugSession<R>, path: &Path, value:
             };
             buf.put_u64_ne(ptr);
         }
-        Type::String(_) => {
+        Type::String(subtype_id) => {
+            // only c-strings can be allocated
+            if session.get_type_size(subtype_id)? != 1 {
+                bail!(DebuggerError::InvalidValue);
+            }
             let new_str = parse_string_literal(value).map_err(|_| DebuggerError::InvalidValue)?;
             let new_str_addr = session.alloc_c_string(&new_str)?;
             buf.put_u64_ne(new_str_addr);
         }
         Type::Enum { encoding, size, variants, .. } => {
-            let enum_value = variants
-                .iter()
-                .find(|&variant| variant.name.as_ref() == value)
-                .map(|variant| variant.value)
-                .ok_or(DebuggerError::InvalidValue)?;
+            // sparse enums can hold values without a variant, so integers are accepted too
+            let enum_value = match variants.iter().find(|&variant| variant.name.as_ref() == value) {
+                Some(variant) => variant.value,
+                None => parse_enum_value(value, radix, encoding, size)?,
+            };
 
             match encoding {
                 gimli::DW_ATE_signed => match size {
@@ -96,7 +194,12 @@ pub fn set_var<R: gimli::Reader>(session: &DebugSession<R>, path: &Path, value:
             };
         }
         Type::Func(_) => {
-            let address = session.get_loc_finder().find_loc(value)?.ok_or(DebuggerError::InvalidValue)?;
+            // function is given by name (optionally with &) or by location
+            let value = value.strip_prefix('&').unwrap_or(value);
+            let address = match session.get_loc_finder().find_func_address(value) {
+                Some(address) => address,
+                None => session.get_loc_finder().find_loc(value)?.ok_or(DebuggerError::InvalidValue)?,
+            };
             buf.put_u64_ne(address);
         }
         _ => bail!(DebuggerError::InvalidPath),
@@ -106,3 +209,116 @@ pub fn set_var<R: gimli::Reader>(session: &DebugSession<R>, path: &Path, value:
 
     Ok(())
 }
+
+/// assigns the value of another variable, falling back to literal names (enum variants, functions, inf/nan)
+pub fn set_var_from_path<R: gimli::Reader>(session: &DebugSession<R>, path: &Path, value_path: &Path) -> Result<()> {
+    let value_loc = match session.get_var_loc(value_path) {
+        Ok(value_loc) => value_loc,
+        Err(e) if matches!(e.downcast_ref::<DebuggerError>(), Some(DebuggerError::VarNotFound(_))) => {
+            // &func is the address of the function, same as the bare name
+            if is_plain_name(value_path) || (is_name_ref(value_path) && is_func_pointer(session, path)?) {
+                return set_var(session, path, value_path.name);
+            }
+            return Err(e);
+        }
+        Err(e) => return Err(e),
+    };
+    let loc = session.get_var_loc(path)?;
+
+    if !is_assignable(session, loc.type_id, value_loc.type_id)? {
+        bail!(DebuggerError::InvalidValue);
+    }
+
+    let buf = session.read_loc(&value_loc)?;
+    session.write_location(loc.location, buf)?;
+
+    Ok(())
+}
+
+/// integer in the input radix, 0x prefix always means hex
+fn parse_int<T: TryFrom<i128>>(value: &str, radix: Radix) -> Result<T> {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, value),
+    };
+    let (digits, base) = match digits.strip_prefix("0x") {
+        Some(digits) => (digits, 16),
+        None => (digits, radix.base()),
+    };
+    if digits.starts_with(['-', '+']) {
+        bail!(DebuggerError::InvalidValue);
+    }
+
+    let value = i128::from_str_radix(digits, base).map_err(|_| DebuggerError::InvalidValue)? * sign;
+
+    T::try_from(value).map_err(|_| anyhow!(DebuggerError::InvalidValue))
+}
+
+/// integer that fits the enum width, unsigned values past i64::MAX are returned as two's complement
+fn parse_enum_value(value: &str, radix: Radix, encoding: gimli::DwAte, size: u16) -> Result<i64> {
+    if !(1..=8).contains(&size) {
+        bail!("invalid enum byte size");
+    }
+
+    let value = parse_int::<i128>(value, radix)?;
+    let bits = size as u32 * 8;
+    let range = match encoding {
+        gimli::DW_ATE_signed => -(1i128 << (bits - 1))..(1i128 << (bits - 1)),
+        gimli::DW_ATE_unsigned => 0..(1i128 << bits),
+        _ => bail!("invalid enum encoding"),
+    };
+    if !range.contains(&value) {
+        bail!(DebuggerError::InvalidValue);
+    }
+
+    Ok(value as i64)
+}
+
+fn is_plain_name(path: &Path) -> bool {
+    path.prefix_operators.is_empty() && path.scope.is_none() && path.postfix_operators.is_empty()
+}
+
+fn is_name_ref(path: &Path) -> bool {
+    matches!(path.prefix_operators.as_slice(), [PrefixOperator::Ref]) && path.scope.is_none() && path.postfix_operators.is_empty()
+}
+
+fn is_func_pointer<R: gimli::Reader>(session: &DebugSession<R>, path: &Path) -> Result<bool> {
+    let loc = session.get_var_loc(path)?;
+    Ok(matches!(session.get_type_storage().unwind_type(loc.type_id)?, Type::Func(_)))
+}
+
+fn is_assignable<R: gimli::Reader>(session: &DebugSession<R>, type_id: TypeId, value_type_id: TypeId) -> Result<bool> {
+    let type_storage = session.get_type_storage();
+
+    let compatible = match (type_storage.unwind_type(type_id)?, type_storage.unwind_type(value_type_id)?) {
+        (Type::Base { encoding, .. }, Type::Base { encoding: value_encoding, .. }) => encoding == value_encoding,
+        (Type::Pointer(_) | Type::String(_), Type::Pointer(_) | Type::String(_)) | (Type::Func(_), Type::Func(_)) => true,
+        (Type::Enum { name, .. }, Type::Enum { name: value_name, .. })
+        | (Type::Struct { name, .. }, Type::Struct { name: value_name, .. })
+        | (Type::Union { name, .. }, Type::Union { name: value_name, .. }) => name == value_name,
+        (
+            Type::Array { subtype_id, .. },
+            Type::Array {
+                subtype_id: value_subtype_id, ..
+            },
+        ) => is_assignable(session, subtype_id, value_subtype_id)?,
+        _ => false,
+    };
+
+    Ok(compatible && session.get_type_size(type_id)? == session.get_type_size(value_type_id)?)
+}
+
+/// parses decimal, scientific, inf/nan and integer (including hex) literals
+fn parse_float<F: FromStr>(value: &str) -> Result<F> {
+    let (sign, unsigned_value) = match value.strip_prefix('-') {
+        Some(unsigned_value) => ("-", unsigned_value),
+        None => ("", value),
+    };
+
+    let value = match unsigned_value.strip_prefix("0x") {
+        Some(hex) => Cow::from(format!("{}{}", sign, u64::from_str_radix(hex, 16).map_err(|_| DebuggerError::InvalidValue)?)),
+        None => Cow::from(value),
+    };
+
+    Ok(value.parse::<F>().map_err(|_| DebuggerError::InvalidValue)?)
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+
+use crate::commands::control;
+use crate::condition::Condition;
+use crate::error::DebuggerError;
+use crate::path::Path;
+use crate::printer::Printer;
+use crate::session::DebugSession;
+
+pub fn watch<R: gimli::Reader>(session: &DebugSession<R>, text: &str, condition: &Condition) -> Result<()> {
+    let mut was_true = condition.evaluate(session)?;
+
+    let triggered = session.step_until(|session| {
+        // condition is out of scope in code without debug info, like libc
+        if session.get_current_line()?.is_none() {
+            return Ok(false);
+        }
+
+        let is_true = match condition.evaluate(session) {
+            Ok(is_true) => is_true,
+            Err(_) => return Ok(false), // variables of other functions aren't visible
+        };
+        let flipped = is_true && !was_true;
+        was_true = is_true;
+
+        Ok(flipped)
+    })?;
+
+    if triggered {
+        println!("watchpoint {} triggered", text);
+        let printer = Printer::new(session);
+        for var in condition.get_vars(session)? {
+            printer.print(&var)?;
+        }
+    }
+
+    control::report_stop(session);
+
+    Ok(())
+}
+
+/// hardware watchpoint, the program stops once it writes another value to the variable
+pub fn add<R: gimli::Reader>(session: &mut DebugSession<R>, expr: &str, path: &Path) -> Result<()> {
+    let watchpoint = session.add_watchpoint(path, expr)?;
+    println!("watchpoint {} set", watchpoint.id);
+
+    Ok(())
+}
+
+pub fn remove<R: gimli::Reader>(session: &mut DebugSession<R>, id: &str) -> Result<()> {
+    session.remove_watchpoint(id.parse::<usize>().map_err(|_| DebuggerError::InvalidValue)?)?;
+    println!("watchpoint removed");
+
+    Ok(())
+}
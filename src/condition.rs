@@ -0,0 +1,186 @@
+use std::cmp::Ordering;
+
+use anyhow::{anyhow, bail, Result};
+use bytes::Buf;
+use pest::Parser;
+
+use crate::error::DebuggerError;
+use crate::fsm::{self, CommandParser, Rule};
+use crate::path::Path;
+use crate::session::DebugSession;
+use crate::types::Type;
+use crate::var::{Value, Var};
+
+#[derive(Debug, Clone, Copy)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl TryFrom<&str> for CmpOp {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "==" => Ok(CmpOp::Eq),
+            "!=" => Ok(CmpOp::Ne),
+            "<" => Ok(CmpOp::Lt),
+            "<=" => Ok(CmpOp::Le),
+            ">" => Ok(CmpOp::Gt),
+            ">=" => Ok(CmpOp::Ge),
+            _ => Err(anyhow!("invalid comparison operator")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar {
+    Int(i128),
+    Float(f64),
+}
+
+impl Scalar {
+    pub fn parse(value: &str) -> Result<Self> {
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value),
+        };
+
+        let scalar = if let Some(hex) = digits.strip_prefix("0x") {
+            let value = i128::from_str_radix(hex, 16).map_err(|_| DebuggerError::InvalidValue)?;
+            Scalar::Int(if negative { -value } else { value })
+        } else if let Ok(value) = value.parse::<i128>() {
+            Scalar::Int(value)
+        } else {
+            Scalar::Float(value.parse::<f64>().map_err(|_| DebuggerError::InvalidValue)?)
+        };
+
+        Ok(scalar)
+    }
+
+    fn is_true(self) -> bool {
+        match self {
+            Scalar::Int(value) => value != 0,
+            Scalar::Float(value) => value != 0.0,
+        }
+    }
+
+    fn compare(self, other: Self) -> Option<Ordering> {
+        match (self, other) {
+            (Scalar::Int(a), Scalar::Int(b)) => Some(a.cmp(&b)),
+            (a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Scalar::Int(value) => value as f64,
+            Scalar::Float(value) => value,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Operand<'a> {
+    Path(Path<'a>),
+    Scalar(Scalar),
+}
+
+impl Operand<'_> {
+    pub fn get_value<R: gimli::Reader>(&self, session: &DebugSession<R>) -> Result<Scalar> {
+        match self {
+            Operand::Path(path) => Condition::get_scalar(session, session.get_var(path)?.value),
+            Operand::Scalar(scalar) => Ok(*scalar),
+        }
+    }
+}
+
+/// `lhs` alone is true when it's not zero
+#[derive(Debug)]
+pub struct Condition<'a> {
+    pub lhs: Operand<'a>,
+    pub rhs: Option<(CmpOp, Operand<'a>)>,
+}
+
+impl<'a> Condition<'a> {
+    /// parses condition saved as text, like the one of a breakpoint
+    pub fn parse(text: &'a str) -> Result<Self> {
+        let mut pairs = CommandParser::parse(Rule::condition, text).map_err(|_| DebuggerError::InvalidCommand)?;
+        fsm::parse_condition(pairs.next().unwrap())
+    }
+
+    pub fn evaluate<R: gimli::Reader>(&self, session: &DebugSession<R>) -> Result<bool> {
+        let lhs = self.lhs.get_value(session)?;
+
+        let (op, rhs) = match &self.rhs {
+            Some((op, rhs)) => (op, rhs.get_value(session)?),
+            None => return Ok(lhs.is_true()),
+        };
+
+        // nan is not equal to anything
+        let ordering = match lhs.compare(rhs) {
+            Some(ordering) => ordering,
+            None => return Ok(matches!(op, CmpOp::Ne)),
+        };
+
+        Ok(match op {
+            CmpOp::Eq => ordering == Ordering::Equal,
+            CmpOp::Ne => ordering != Ordering::Equal,
+            CmpOp::Lt => ordering == Ordering::Less,
+            CmpOp::Le => ordering != Ordering::Greater,
+            CmpOp::Gt => ordering == Ordering::Greater,
+            CmpOp::Ge => ordering != Ordering::Less,
+        })
+    }
+
+    /// variables the condition depends on
+    pub fn get_vars<R: gimli::Reader>(&self, session: &DebugSession<R>) -> Result<Vec<Var>> {
+        let mut vars = Vec::new();
+        for operand in [Some(&self.lhs), self.rhs.as_ref().map(|(_, rhs)| rhs)].into_iter().flatten() {
+            if let Operand::Path(path) = operand {
+                vars.push(session.get_var(path)?);
+            }
+        }
+
+        Ok(vars)
+    }
+
+    fn get_scalar<R: gimli::Reader>(session: &DebugSession<R>, mut value: Value) -> Result<Scalar> {
+        let scalar = match session.get_type_storage().get(value.type_id)? {
+            Type::Base { encoding, size, .. } | Type::Enum { encoding, size, .. } => match encoding {
+                gimli::DW_ATE_float => match size {
+                    4 => Scalar::Float(value.buf.get_f32_ne() as f64),
+                    8 => Scalar::Float(value.buf.get_f64_ne()),
+                    _ => bail!("unsupported byte size"),
+                },
+                gimli::DW_ATE_signed | gimli::DW_ATE_signed_char => match size {
+                    1 => Scalar::Int(value.buf.get_i8() as i128),
+                    2 => Scalar::Int(value.buf.get_i16_ne() as i128),
+                    4 => Scalar::Int(value.buf.get_i32_ne() as i128),
+                    8 => Scalar::Int(value.buf.get_i64_ne() as i128),
+                    _ => bail!("unsupported byte size"),
+                },
+                gimli::DW_ATE_boolean | gimli::DW_ATE_unsigned | gimli::DW_ATE_unsigned_char | gimli::DW_ATE_UTF => match size {
+                    1 => Scalar::Int(value.buf.get_u8() as i128),
+                    2 => Scalar::Int(value.buf.get_u16_ne() as i128),
+                    4 => Scalar::Int(value.buf.get_u32_ne() as i128),
+                    8 => Scalar::Int(value.buf.get_u64_ne() as i128),
+                    _ => bail!("unsupported byte size"),
+                },
+                _ => bail!(DebuggerError::UnsupportedEncoding(encoding)),
+            },
+            Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) | Type::Typedef(_, subtype_id) => {
+                return Self::get_scalar(session, Value::new(subtype_id, value.buf));
+            }
+            Type::Pointer(_) | Type::String(_) | Type::Func(_) => Scalar::Int(value.buf.get_u64_ne() as i128),
+            // only scalars can be compared
+            _ => bail!(DebuggerError::InvalidValue),
+        };
+
+        Ok(scalar)
+    }
+}
@@ -4,14 +4,52 @@ pub const WORD_SIZE: usize = mem::size_of::<usize>();
 
 const _: () = assert!(WORD_SIZE == 8);
 
+pub const PAGE_SIZE: u64 = 0x1000;
+
+pub const HISTORY_FILE_NAME: &str = ".dbg_history";
+pub const HISTORY_SIZE: usize = 1000;
+pub const ALIASES_SECTION: &str = "#aliases";
+
 pub const MAIN_FUNC_NAME: &str = "main";
 
-pub const FUNC_PROLOGUE_MAGIC_BYTES: [u8; 8] = [
-    0xf3, 0x0f, 0x1e, 0xfa, // endbr64
-    0x55, // push %rbp
-    0x48, 0x89, 0xe5, // mov %rsp,%rbp
+pub const INT3: i64 = 0xcc;
+
+pub const ENDBR64_BYTES: [u8; 4] = [0xf3, 0x0f, 0x1e, 0xfa];
+
+/// frame pointer setup at the function start, code is compiled with or without cet (endbr64)
+pub struct FuncPrologue {
+    pub bytes: &'static [u8],
+    /// offset of `push %rbp`, return address is on the top of the stack before it
+    pub push_offset: u64,
+}
+
+pub const FUNC_PROLOGUES: [FuncPrologue; 4] = [
+    FuncPrologue {
+        bytes: &[
+            0xf3, 0x0f, 0x1e, 0xfa, // endbr64
+            0x55, // push %rbp
+            0x48, 0x89, 0xe5, // mov %rsp,%rbp
+        ],
+        push_offset: 4,
+    },
+    FuncPrologue {
+        bytes: &[
+            0x55, // push %rbp
+            0x48, 0x89, 0xe5, // mov %rsp,%rbp
+        ],
+        push_offset: 0,
+    },
+    // other encoding of the same mov
+    FuncPrologue {
+        bytes: &[0xf3, 0x0f, 0x1e, 0xfa, 0x55, 0x48, 0x8b, 0xec],
+        push_offset: 4,
+    },
+    FuncPrologue {
+        bytes: &[0x55, 0x48, 0x8b, 0xec],
+        push_offset: 0,
+    },
 ];
-pub const FUNC_PROLOGUE_SIZE: usize = FUNC_PROLOGUE_MAGIC_BYTES.len();
+pub const MAX_FUNC_PROLOGUE_SIZE: usize = 8;
 
 /*
 * ```
@@ -27,3 +65,28 @@ pub const FUNC_PROLOGUE_SIZE: usize = FUNC_PROLOGUE_MAGIC_BYTES.len();
 * ```
 */
 pub const FUNC_EPILOGUE_SIZE: usize = 2;
+
+/*
+* `__restore_rt` signal trampoline
+*
+* ```
+* 0: 48 c7 c0 0f 00 00 00   mov $0xf,%rax
+* 7: 0f 05                  syscall
+* ```
+*/
+pub const SIGRETURN_TRAMPOLINE_BYTES: [u8; 9] = [0x48, 0xc7, 0xc0, 0x0f, 0x00, 0x00, 0x00, 0x0f, 0x05];
+
+/// registers preserved across function calls by System V ABI (rbx, rbp, r12-r15)
+pub const CALLEE_SAVED_REGISTERS: [gimli::Register; 6] = [
+    gimli::X86_64::RBX,
+    gimli::X86_64::RBP,
+    gimli::X86_64::R12,
+    gimli::X86_64::R13,
+    gimli::X86_64::R14,
+    gimli::X86_64::R15,
+];
+
+/// general purpose registers variable locations can refer to
+pub const SUPPORTED_REGISTERS: [&str; 16] = [
+    "rax", "rdx", "rcx", "rbx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12", "r13", "r14", "r15",
+];
@@ -1,15 +1,36 @@
+#[derive(Clone)]
 pub struct Context {
-    pub ip: u64,
-    pub sp: u64,
-    pub bp: u64,
+    pub regs: libc::user_regs_struct,
+    /// ip was unwound from the stack and points to the instruction after the call
+    pub is_return_address: bool,
 }
 
 impl Context {
     pub fn new(regs: libc::user_regs_struct) -> Self {
         Self {
-            ip: regs.rip,
-            sp: regs.rsp,
-            bp: regs.rbp,
+            regs,
+            is_return_address: false,
+        }
+    }
+
+    pub fn ip(&self) -> u64 {
+        self.regs.rip
+    }
+
+    pub fn sp(&self) -> u64 {
+        self.regs.rsp
+    }
+
+    pub fn bp(&self) -> u64 {
+        self.regs.rbp
+    }
+
+    /// address of the instruction the frame is executing, used for ip based lookups
+    pub fn pc(&self) -> u64 {
+        if self.is_return_address {
+            self.ip() - 1
+        } else {
+            self.ip()
         }
     }
 }
@@ -1,23 +1,18 @@
 use std::borrow::Cow;
-use std::ffi::OsStr;
 use std::fs;
-use std::io::Read;
-use std::os::unix::process::CommandExt;
 use std::path::Path;
-use std::process;
 
-use crate::consts::WORD_SIZE;
-use crate::dwarf_parser::DwarfParser;
+use crate::consts::{PAGE_SIZE, WORD_SIZE};
+use crate::image::Image;
+use crate::launcher::Launcher;
 use crate::session::DebugSession;
 use crate::unwinder::{UnwindFrame, Unwinder};
 use gimli::Section;
 use memmap2::Mmap;
-use object::{Object, ObjectSection};
+use object::{Object, ObjectSection, ObjectSegment};
 use typed_arena::Arena;
 
-use anyhow::{anyhow, Result};
-use nix::sys::{ptrace, wait};
-use nix::unistd::Pid;
+use anyhow::Result;
 
 pub struct Debugger {
     arena_data: Arena<Vec<u8>>,
@@ -32,11 +27,14 @@ impl Debugger {
         }
     }
 
-    pub fn start<I, S>(&self, prog: &Path, args: I) -> Result<DebugSession<gimli::EndianSlice<'_, gimli::RunTimeEndian>>>
-    where
-        I: IntoIterator<Item = S>,
-        S: AsRef<OsStr>,
-    {
+    pub fn start(&self, launcher: Launcher) -> Result<DebugSession<gimli::EndianSlice<'_, gimli::RunTimeEndian>>> {
+        let image = self.load(launcher.get_prog())?;
+
+        DebugSession::start(launcher, image)
+    }
+
+    /// reads debug info of the program
+    pub fn load(&self, prog: &Path) -> Result<Image<gimli::EndianSlice<'_, gimli::RunTimeEndian>>> {
         let file = fs::File::open(prog)?;
         let map = self.arena_mmap.alloc(unsafe { Mmap::map(&file)? });
         let object = object::File::parse(&**map)?;
@@ -61,28 +59,17 @@ impl Debugger {
         let dwarf = gimli::Dwarf::load(load_section)?;
         let unwinder = Self::get_unwinder(&object, load_section)?;
 
-        let mut command = process::Command::new(prog);
-
-        unsafe {
-            command.pre_exec(|| {
-                ptrace::traceme()?;
-                Ok(())
-            });
-        }
-
-        let child = command.args(args).spawn()?;
-
-        let base_address = match object.kind() {
-            object::ObjectKind::Dynamic => Self::get_base_address(child.id())?,
-            _ => 0,
-        };
-        log::trace!("base address {:#x}", base_address);
-
-        let (loc_finder, type_storage) = DwarfParser::parse(&dwarf, base_address)?;
-
-        wait::waitpid(Pid::from_raw(child.id() as libc::pid_t), None)?;
-
-        Ok(DebugSession::new(child, dwarf, loc_finder, type_storage, unwinder, base_address))
+        // addresses in debug info are relative to the first segment
+        let link_address = object.segments().map(|segment| segment.address()).min().unwrap_or(0) & !(PAGE_SIZE - 1);
+
+        Ok(Image::new(
+            prog.to_path_buf(),
+            dwarf,
+            unwinder,
+            object.kind() == object::ObjectKind::Dynamic,
+            link_address,
+            object.entry(),
+        ))
     }
 
     fn get_unwinder<R, F>(object: &object::File, load_section: F) -> Result<Unwinder<R>>
@@ -116,14 +103,4 @@ impl Debugger {
 
         Ok(Unwinder::new(unwind_frame, bases))
     }
-
-    fn get_base_address(child_pid: u32) -> Result<u64> {
-        let mut buf = vec![0; 16];
-        let mut procmaps = fs::File::open(format!("/proc/{}/maps", child_pid))?;
-        _ = procmaps.read(&mut buf)?;
-        let (base_address, _) = std::str::from_utf8(&buf)?.split_once('-').ok_or(anyhow!("invalid proc maps"))?;
-        let base_address = u64::from_str_radix(base_address, 16)?;
-
-        Ok(base_address)
-    }
 }
@@ -0,0 +1,166 @@
+use std::fmt;
+
+use iced_x86::{Decoder, DecoderOptions, Formatter, IntelFormatter, MemorySizeOptions, OpKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub address: u64,
+    pub len: usize,
+    pub text: String,
+    /// destination of relative jumps and calls
+    pub target: Option<u64>,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// decodes instructions until the end of the code, bytes which aren't an instruction (or are cut by the end) are (bad)
+/// and skipped one by one
+pub fn disassemble(code: &[u8], address: u64) -> Vec<Instruction> {
+    let mut decoder = Decoder::with_ip(64, code, address, DecoderOptions::NONE);
+    let mut formatter = new_formatter();
+    let mut instruction = iced_x86::Instruction::default();
+    let mut instructions = Vec::new();
+
+    while decoder.can_decode() {
+        let offset = decoder.position();
+        decoder.decode_out(&mut instruction);
+        if instruction.is_invalid() {
+            instructions.push(Instruction {
+                address: address + offset as u64,
+                len: 1,
+                text: "(bad)".to_string(),
+                target: None,
+            });
+            decoder.set_position(offset + 1).expect("position is inside the code");
+            decoder.set_ip(address + offset as u64 + 1);
+            continue;
+        }
+
+        let mut text = String::new();
+        formatter.format(&instruction, &mut text);
+        let target = match instruction.op0_kind() {
+            OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64 if instruction.op_count() > 0 => Some(instruction.near_branch_target()),
+            _ => None,
+        };
+        instructions.push(Instruction {
+            address: instruction.ip(),
+            len: instruction.len(),
+            text,
+            target,
+        });
+    }
+
+    instructions
+}
+
+/// intel syntax with lowercase 0x hex numbers, like objdump -M intel
+fn new_formatter() -> IntelFormatter {
+    let mut formatter = IntelFormatter::new();
+    let options = formatter.options_mut();
+    options.set_hex_prefix("0x");
+    options.set_hex_suffix("");
+    options.set_uppercase_hex(false);
+    options.set_small_hex_numbers_in_decimal(false);
+    options.set_branch_leading_zeros(false);
+    options.set_show_branch_size(false);
+    options.set_space_after_operand_separator(true);
+    options.set_always_show_scale(true);
+    options.set_memory_size_options(MemorySizeOptions::Always);
+    formatter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_text(code: &[u8]) -> String {
+        let instructions = disassemble(code, 0x1000);
+        assert_eq!(instructions.len(), 1, "{:?}", instructions);
+        instructions[0].to_string()
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode_text(&[0xf3, 0x0f, 0x1e, 0xfa]), "endbr64");
+        assert_eq!(decode_text(&[0x55]), "push rbp");
+        assert_eq!(decode_text(&[0x41, 0x57]), "push r15");
+        assert_eq!(decode_text(&[0x48, 0x89, 0xe5]), "mov rbp, rsp");
+        assert_eq!(decode_text(&[0x48, 0x83, 0xec, 0x10]), "sub rsp, 0x10");
+        assert_eq!(decode_text(&[0x48, 0x83, 0xe4, 0xf0]), "and rsp, 0xfffffffffffffff0");
+        assert_eq!(decode_text(&[0xc7, 0x45, 0xfc, 0x7b, 0x00, 0x00, 0x00]), "mov dword ptr [rbp-0x4], 0x7b");
+        assert_eq!(decode_text(&[0x8b, 0x45, 0xfc]), "mov eax, dword ptr [rbp-0x4]");
+        assert_eq!(decode_text(&[0x88, 0x45, 0xff]), "mov byte ptr [rbp-0x1], al");
+        assert_eq!(decode_text(&[0x40, 0x88, 0xc6]), "mov sil, al");
+        assert_eq!(decode_text(&[0x88, 0xe0]), "mov al, ah");
+        assert_eq!(decode_text(&[0x66, 0x89, 0x45, 0xfe]), "mov word ptr [rbp-0x2], ax");
+        assert_eq!(decode_text(&[0x48, 0x8d, 0x05, 0x00, 0x01, 0x00, 0x00]), "lea rax, [0x1107]");
+        assert_eq!(decode_text(&[0x8b, 0x04, 0x85, 0x10, 0x00, 0x00, 0x00]), "mov eax, dword ptr [rax*4+0x10]");
+        assert_eq!(decode_text(&[0x8b, 0x44, 0x8d, 0xe0]), "mov eax, dword ptr [rbp+rcx*4-0x20]");
+        assert_eq!(decode_text(&[0x8b, 0x04, 0x24]), "mov eax, dword ptr [rsp]");
+        assert_eq!(
+            decode_text(&[0x64, 0x48, 0x8b, 0x04, 0x25, 0x28, 0x00, 0x00, 0x00]),
+            "mov rax, qword ptr fs:[0x28]"
+        );
+        assert_eq!(decode_text(&[0x48, 0x98]), "cdqe");
+        assert_eq!(decode_text(&[0x48, 0x63, 0xd0]), "movsxd rdx, eax");
+        assert_eq!(decode_text(&[0x0f, 0xb6, 0x45, 0xff]), "movzx eax, byte ptr [rbp-0x1]");
+        assert_eq!(decode_text(&[0x0f, 0xbf, 0xc0]), "movsx eax, ax");
+        assert_eq!(decode_text(&[0x0f, 0x94, 0xc0]), "sete al");
+        assert_eq!(decode_text(&[0xc1, 0xe0, 0x02]), "shl eax, 0x2");
+        assert_eq!(decode_text(&[0xf7, 0xd8]), "neg eax");
+        assert_eq!(decode_text(&[0x85, 0xc0]), "test eax, eax");
+        assert_eq!(decode_text(&[0xb8, 0x00, 0x00, 0x00, 0x00]), "mov eax, 0x0");
+        assert_eq!(decode_text(&[0xf3, 0x48, 0xab]), "rep stosq qword ptr [rdi]");
+        assert_eq!(decode_text(&[0xff, 0xd0]), "call rax");
+        assert_eq!(decode_text(&[0xc9]), "leave");
+        assert_eq!(decode_text(&[0xc3]), "ret");
+        assert_eq!(decode_text(&[0x0f, 0x1f, 0x44, 0x00, 0x00]), "nop dword ptr [rax+rax*1]");
+        assert_eq!(decode_text(&[0xf3, 0x0f, 0x10, 0x05, 0x00, 0x00, 0x00, 0x00]), "movss xmm0, dword ptr [0x1008]");
+        assert_eq!(decode_text(&[0xf3, 0x0f, 0x11, 0x45, 0xf8]), "movss dword ptr [rbp-0x8], xmm0");
+        assert_eq!(decode_text(&[0xf3, 0x0f, 0x5a, 0xc0]), "cvtss2sd xmm0, xmm0");
+        assert_eq!(decode_text(&[0x66, 0x48, 0x0f, 0x7e, 0xc0]), "movq rax, xmm0");
+        assert_eq!(decode_text(&[0x66, 0x0f, 0xef, 0xc0]), "pxor xmm0, xmm0");
+        assert_eq!(decode_text(&[0xf2, 0x0f, 0x2c, 0xc0]), "cvttsd2si eax, xmm0");
+        assert_eq!(decode_text(&[0xf0, 0x83, 0x00, 0x01]), "lock add dword ptr [rax], 0x1");
+        // x87 and vex encoded instructions
+        assert_eq!(decode_text(&[0xdb, 0x6d, 0x10]), "fld st, tbyte ptr [rbp+0x10]");
+        assert_eq!(decode_text(&[0xde, 0xc1]), "faddp");
+        assert_eq!(decode_text(&[0xdb, 0x7d, 0xf0]), "fstp tbyte ptr [rbp-0x10], st");
+        assert_eq!(decode_text(&[0xc5, 0xf9, 0xef, 0xc0]), "vpxor xmm0, xmm0, xmm0");
+    }
+
+    #[test]
+    fn test_decode_branches() {
+        let call = &disassemble(&[0xe8, 0xfb, 0xff, 0xff, 0xff], 0x1000)[0];
+        assert_eq!(call.to_string(), "call 0x1000");
+        assert_eq!(call.target, Some(0x1000));
+        assert_eq!(decode_text(&[0x74, 0x05]), "je 0x1007");
+        assert_eq!(decode_text(&[0x0f, 0x8e, 0x10, 0x00, 0x00, 0x00]), "jle 0x1016");
+        assert_eq!(decode_text(&[0xeb, 0xfe]), "jmp 0x1000");
+        assert_eq!(disassemble(&[0xff, 0xd0], 0x1000)[0].target, None);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        // incomplete call at the end
+        let instructions = disassemble(&[0x55, 0x06, 0xc3, 0xe8, 0x00], 0x1000);
+        let text = instructions
+            .iter()
+            .map(|instruction| (instruction.address, instruction.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            text,
+            vec![
+                (0x1000, "push rbp".to_string()),
+                (0x1001, "(bad)".to_string()),
+                (0x1002, "ret".to_string()),
+                (0x1003, "(bad)".to_string()),
+                (0x1004, "(bad)".to_string()),
+            ]
+        );
+    }
+}
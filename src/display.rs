@@ -0,0 +1,37 @@
+use std::cell::Cell;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayStatus {
+    Pending,
+    Ok,
+    Failed,
+}
+
+impl fmt::Display for DisplayStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayStatus::Pending => write!(f, "not evaluated"),
+            DisplayStatus::Ok => write!(f, "ok"),
+            DisplayStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// expression printed every time the program stops, kept as text because it's evaluated in different scopes
+#[derive(Debug)]
+pub struct Display {
+    pub id: usize,
+    pub expr: String,
+    pub status: Cell<DisplayStatus>,
+}
+
+impl Display {
+    pub fn new<S: Into<String>>(id: usize, expr: S) -> Self {
+        Self {
+            id,
+            expr: expr.into(),
+            status: Cell::new(DisplayStatus::Pending),
+        }
+    }
+}
@@ -3,9 +3,13 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::rc::Rc;
 
-use anyhow::{anyhow, bail, Context, Result};
+use anyhow::{anyhow, bail, Result};
+use gimli::ReaderOffset;
 
-use crate::loc_finder::{EntryRef, LocFinder, VarRef};
+use crate::consts::WORD_SIZE;
+use crate::error::{DebuggerError, DwarfParseError, EntryLocation};
+use crate::interrupt;
+use crate::loc_finder::{EntryRef, LocFinder, PcRanges, VarRef, VarScope};
 use crate::types::{ArrayCount, EnumVariant, Field, Type, TypeId, TypeStorage, UnionField, VOID_TYPE_ID};
 
 pub struct DwarfParser;
@@ -16,19 +20,62 @@ impl DwarfParser {
         let mut type_storage = TypeStorage::new();
 
         let mut units = dwarf.units();
+        let mut units_count = 0;
+        while units.next()?.is_some() {
+            units_count += 1;
+        }
+
+        let mut units = dwarf.units();
+        let mut parsed_units_count = 0;
+        let mut parsed_units = Vec::new();
 
         while let Some(header) = units.next()? {
-            let unit = dwarf.unit(header)?;
-            let unit_ref = unit.unit_ref(dwarf);
+            if interrupt::is_interrupted() {
+                bail!(DebuggerError::Cancelled);
+            }
+
+            let unit_offset = Self::get_section_offset(header.offset());
 
             // todo worker pool
-            Self::process_unit(&mut loc_finder, &mut type_storage, &unit_ref)?;
-            Self::find_lines(&mut loc_finder, &unit_ref)?;
+            // one broken unit shouldn't prevent debugging the rest of the program,
+            // what was collected before the error stays usable
+            match Self::parse_unit(&mut loc_finder, &mut type_storage, dwarf, header) {
+                Ok(unit) => parsed_units.push((unit_offset, unit)),
+                Err(e) => log::warn!("skipping unit at {:#x}: {:#}", unit_offset, e),
+            }
+
+            parsed_units_count += 1;
+            log::info!("parsed {}/{} units", parsed_units_count, units_count);
+        }
+
+        // line rows are matched against functions, so ranges of all functions are loaded at once before lines
+        loc_finder.build_func_ranges();
+        for (unit_offset, unit) in parsed_units {
+            if interrupt::is_interrupted() {
+                bail!(DebuggerError::Cancelled);
+            }
+
+            if let Err(e) = Self::find_lines(&mut loc_finder, &unit.unit_ref(dwarf)) {
+                log::warn!("skipping lines of unit at {:#x}: {:#}", unit_offset, e);
+            }
         }
 
         Ok((loc_finder, type_storage))
     }
 
+    fn parse_unit<R: gimli::Reader>(
+        loc_finder: &mut LocFinder<R>,
+        type_storage: &mut TypeStorage<R>,
+        dwarf: &gimli::Dwarf<R>,
+        header: gimli::UnitHeader<R>,
+    ) -> Result<gimli::Unit<R>> {
+        let unit = dwarf.unit(header)?;
+
+        Self::process_unit(loc_finder, type_storage, &unit.unit_ref(dwarf))?;
+
+        Ok(unit)
+    }
+
     fn process_unit<R: gimli::Reader>(loc_finder: &mut LocFinder<R>, type_storage: &mut TypeStorage<R>, unit_ref: &gimli::UnitRef<R>) -> Result<()> {
         // todo iterate all entries
         let mut tree = unit_ref.entries_tree(None)?;
@@ -37,15 +84,22 @@ impl DwarfParser {
         if root_entry.tag() == gimli::DW_TAG_compile_unit {
             Self::process_compile_unit(loc_finder, unit_ref, root_entry)?;
         }
+        let unit_name = Self::get_optional_name(unit_ref, root_entry)?.unwrap_or_else(|| Rc::from(""));
 
         let mut visited_types = HashMap::new();
+        let mut declarations = HashMap::new();
         let mut children = root.children();
         while let Some(child) = children.next()? {
             let entry = child.entry();
 
             match entry.tag() {
                 gimli::DW_TAG_subprogram => Self::process_subprogram(loc_finder, type_storage, unit_ref, entry, &mut visited_types)?,
-                gimli::DW_TAG_variable => Self::process_var(loc_finder, type_storage, unit_ref, entry, None, &mut visited_types)?,
+                gimli::DW_TAG_variable => {
+                    Self::process_global_var(loc_finder, type_storage, unit_ref, entry, "", &unit_name, &mut declarations, &mut visited_types)?
+                }
+                gimli::DW_TAG_namespace => {
+                    Self::process_namespace(loc_finder, type_storage, unit_ref, entry, "", &unit_name, &mut declarations, &mut visited_types)?
+                }
                 _ => (),
             }
         }
@@ -60,13 +114,21 @@ impl DwarfParser {
     ) -> Result<()> {
         let name = Self::get_name(unit_ref, entry)?;
 
-        let low_pc_attr = entry.attr_value(gimli::DW_AT_low_pc)?.ok_or(anyhow!("get low_pc attr"))?;
-        let low_pc = unit_ref.attr_address(low_pc_attr)?.ok_or(anyhow!("get low_pc value"))?;
+        let low_pc_attr = entry
+            .attr_value(gimli::DW_AT_low_pc)?
+            .ok_or_else(|| Self::missing_attr(unit_ref, entry, gimli::DW_AT_low_pc))?;
+        let low_pc = unit_ref
+            .attr_address(low_pc_attr)?
+            .ok_or_else(|| Self::unexpected_form(unit_ref, entry, gimli::DW_AT_low_pc))?;
 
-        let high_pc_attr = entry.attr_value(gimli::DW_AT_high_pc)?.ok_or(anyhow!("get high_pc attr"))?;
+        let high_pc_attr = entry
+            .attr_value(gimli::DW_AT_high_pc)?
+            .ok_or_else(|| Self::missing_attr(unit_ref, entry, gimli::DW_AT_high_pc))?;
         let high_pc = match high_pc_attr {
             gimli::AttributeValue::Udata(size) => low_pc + size,
-            high_pc => unit_ref.attr_address(high_pc)?.ok_or(anyhow!("get high_pc value"))?,
+            high_pc => unit_ref
+                .attr_address(high_pc)?
+                .ok_or_else(|| Self::unexpected_form(unit_ref, entry, gimli::DW_AT_high_pc))?,
         };
 
         // high_pc is the address of the first location past the last instruction associated with the entity,
@@ -85,17 +147,20 @@ impl DwarfParser {
     ) -> Result<()> {
         let name = Self::get_name(unit_ref, entry)?;
 
-        let unit_offset = unit_ref.header.offset().as_debug_info_offset().ok_or(anyhow!("can't get debug_info offest"))?;
-        let entry_offset = entry.offset();
-        let entry_ref = EntryRef::new(unit_offset, entry_offset);
+        let entry_ref = Self::get_entry_ref(unit_ref, entry)?;
 
         loc_finder.add_func_entry_ref(name.clone(), entry_ref);
 
+        let return_type_id = Self::process_entry_type(type_storage, unit_ref, entry, visited_types)?;
+        loc_finder.add_func_return_type(name.clone(), return_type_id);
+
         let low_pc_attr = match entry.attr_value(gimli::DW_AT_low_pc)? {
             Some(value) => value,
             None => return Ok(()),
         };
-        let low_pc = unit_ref.attr_address(low_pc_attr)?.ok_or(anyhow!("get low_pc value"))?;
+        let low_pc = unit_ref
+            .attr_address(low_pc_attr)?
+            .ok_or_else(|| Self::unexpected_form(unit_ref, entry, gimli::DW_AT_low_pc))?;
 
         loc_finder.add_location(name.clone(), low_pc);
 
@@ -105,7 +170,9 @@ impl DwarfParser {
         };
         let high_pc = match high_pc_attr {
             gimli::AttributeValue::Udata(size) => low_pc + size,
-            high_pc => unit_ref.attr_address(high_pc)?.ok_or(anyhow!("get high pc value"))?,
+            high_pc => unit_ref
+                .attr_address(high_pc)?
+                .ok_or_else(|| Self::unexpected_form(unit_ref, entry, gimli::DW_AT_high_pc))?,
         };
 
         // high_pc is the address of the first location past the last instruction associated with the entity,
@@ -114,13 +181,37 @@ impl DwarfParser {
 
         // process function parameters and variables
         let mut tree = unit_ref.entries_tree(Some(entry.offset()))?;
-        let root = tree.root()?;
-        let mut children = root.children();
+        Self::process_func_vars(loc_finder, type_storage, unit_ref, tree.root()?, &name, None, visited_types)?;
+
+        Ok(())
+    }
+
+    /// variables of lexical blocks are visible only in the block, nested blocks are processed recursively
+    fn process_func_vars<R: gimli::Reader>(
+        loc_finder: &mut LocFinder<R>,
+        type_storage: &mut TypeStorage<R>,
+        unit_ref: &gimli::UnitRef<R>,
+        node: gimli::EntriesTreeNode<R>,
+        func_name: &Rc<str>,
+        pc_ranges: Option<PcRanges>,
+        visited_types: &mut HashMap<gimli::UnitOffset<R::Offset>, TypeId>,
+    ) -> Result<()> {
+        let mut children = node.children();
         while let Some(child) = children.next()? {
             let child_entry = child.entry();
             match child_entry.tag() {
-                gimli::DW_TAG_formal_parameter | gimli::DW_TAG_variable => {
-                    Self::process_var(loc_finder, type_storage, unit_ref, child_entry, Some(name.clone()), visited_types)?
+                gimli::DW_TAG_formal_parameter | gimli::DW_TAG_variable => Self::process_var(
+                    loc_finder,
+                    type_storage,
+                    unit_ref,
+                    child_entry,
+                    VarScope::Func(func_name.clone()),
+                    pc_ranges.clone(),
+                    visited_types,
+                )?,
+                gimli::DW_TAG_lexical_block => {
+                    let block_ranges = Self::get_block_ranges(unit_ref, child_entry)?.or_else(|| pc_ranges.clone());
+                    Self::process_func_vars(loc_finder, type_storage, unit_ref, child, func_name, block_ranges, visited_types)?
                 }
                 _ => (),
             }
@@ -129,12 +220,28 @@ impl DwarfParser {
         Ok(())
     }
 
+    /// block split by DW_AT_ranges keeps every range, block without addresses is not narrowed
+    fn get_block_ranges<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<Option<PcRanges>> {
+        let mut block_ranges = Vec::new();
+        let mut ranges = unit_ref.die_ranges(entry)?;
+        while let Some(range) = ranges.next()? {
+            if range.begin >= range.end {
+                continue;
+            }
+            // ranges are exclusive, so we do -1
+            block_ranges.push((range.begin, range.end - 1));
+        }
+
+        Ok((!block_ranges.is_empty()).then(|| block_ranges.into()))
+    }
+
     fn process_var<R: gimli::Reader>(
         loc_finder: &mut LocFinder<R>,
         type_storage: &mut TypeStorage<R>,
         unit_ref: &gimli::UnitRef<R>,
         entry: &gimli::DebuggingInformationEntry<R>,
-        func_name: Option<Rc<str>>,
+        scope: VarScope,
+        pc_ranges: Option<PcRanges>,
         visited_types: &mut HashMap<gimli::UnitOffset<R::Offset>, TypeId>,
     ) -> Result<()> {
         let name = match Self::get_optional_name(unit_ref, entry)? {
@@ -142,14 +249,105 @@ impl DwarfParser {
             None => return Ok(()),
         };
 
-        let unit_offset = unit_ref.header.offset().as_debug_info_offset().ok_or(anyhow!("can't get debug_info offest"))?;
-        let entry_offset = entry.offset();
-        let entry_ref = EntryRef::new(unit_offset, entry_offset);
+        let entry_ref = Self::get_entry_ref(unit_ref, entry)?;
 
         let type_id = Self::process_entry_type(type_storage, unit_ref, entry, visited_types)?;
-        let var_ref = VarRef::new(entry_ref, type_id);
+        let var_ref = VarRef::new(entry_ref, type_id, entry.tag() == gimli::DW_TAG_formal_parameter, pc_ranges);
+
+        loc_finder.add_var(name, var_ref, scope);
+
+        Ok(())
+    }
+
+    /// declarations are remembered by their offset, definitions refer to them with DW_AT_specification
+    #[allow(clippy::too_many_arguments)]
+    fn process_global_var<R: gimli::Reader>(
+        loc_finder: &mut LocFinder<R>,
+        type_storage: &mut TypeStorage<R>,
+        unit_ref: &gimli::UnitRef<R>,
+        entry: &gimli::DebuggingInformationEntry<R>,
+        namespace: &str,
+        unit_name: &Rc<str>,
+        declarations: &mut HashMap<gimli::UnitOffset<R::Offset>, (Rc<str>, TypeId)>,
+        visited_types: &mut HashMap<gimli::UnitOffset<R::Offset>, TypeId>,
+    ) -> Result<()> {
+        let (name, type_id) = match entry.attr_value(gimli::DW_AT_specification)? {
+            Some(gimli::AttributeValue::UnitRef(offset)) => match declarations.get(&offset) {
+                Some(declaration) => declaration.clone(),
+                // static members of classes are not supported
+                None => return Ok(()),
+            },
+            Some(_) => bail!(Self::unexpected_form(unit_ref, entry, gimli::DW_AT_specification)),
+            None => {
+                let name = match Self::get_optional_name(unit_ref, entry)? {
+                    Some(name) => Rc::from(format!("{}{}", namespace, name)),
+                    None => return Ok(()),
+                };
+                let type_id = Self::process_entry_type(type_storage, unit_ref, entry, visited_types)?;
+
+                (name, type_id)
+            }
+        };
+
+        if Self::is_declaration(unit_ref, entry)? {
+            declarations.insert(entry.offset(), (name, type_id));
+            return Ok(());
+        }
+
+        let entry_ref = Self::get_entry_ref(unit_ref, entry)?;
+        let var_ref = VarRef::new(entry_ref, type_id, false, None);
+
+        loc_finder.add_var(name, var_ref, VarScope::Unit(unit_name.clone()));
+
+        Ok(())
+    }
+
+    /// variables of namespaces are qualified with namespace name, anonymous namespaces are transparent
+    #[allow(clippy::too_many_arguments)]
+    fn process_namespace<R: gimli::Reader>(
+        loc_finder: &mut LocFinder<R>,
+        type_storage: &mut TypeStorage<R>,
+        unit_ref: &gimli::UnitRef<R>,
+        entry: &gimli::DebuggingInformationEntry<R>,
+        namespace: &str,
+        unit_name: &Rc<str>,
+        declarations: &mut HashMap<gimli::UnitOffset<R::Offset>, (Rc<str>, TypeId)>,
+        visited_types: &mut HashMap<gimli::UnitOffset<R::Offset>, TypeId>,
+    ) -> Result<()> {
+        let namespace = match Self::get_optional_name(unit_ref, entry)? {
+            Some(name) => format!("{}{}::", namespace, name),
+            None => String::from(namespace),
+        };
 
-        loc_finder.add_var(name, var_ref, func_name);
+        let mut tree = unit_ref.entries_tree(Some(entry.offset()))?;
+        let root = tree.root()?;
+        let mut children = root.children();
+        while let Some(child) = children.next()? {
+            let child_entry = child.entry();
+            match child_entry.tag() {
+                gimli::DW_TAG_variable => Self::process_global_var(
+                    loc_finder,
+                    type_storage,
+                    unit_ref,
+                    child_entry,
+                    &namespace,
+                    unit_name,
+                    declarations,
+                    visited_types,
+                )?,
+                gimli::DW_TAG_namespace => Self::process_namespace(
+                    loc_finder,
+                    type_storage,
+                    unit_ref,
+                    child_entry,
+                    &namespace,
+                    unit_name,
+                    declarations,
+                    visited_types,
+                )?,
+                _ => (),
+            }
+        }
 
         Ok(())
     }
@@ -165,9 +363,8 @@ impl DwarfParser {
                 gimli::AttributeValue::UnitRef(offset) => {
                     let subtype_entry = unit_ref.entry(offset)?;
                     Self::process_type(type_storage, unit_ref, &subtype_entry, visited_types)
-                        .with_context(|| format!("failed to process type at {:?}", subtype_entry.offset().to_unit_section_offset(unit_ref.unit)))
                 }
-                _ => bail!("unknown type"),
+                _ => bail!(Self::unexpected_form(unit_ref, entry, gimli::DW_AT_type)),
             },
             None => Ok(VOID_TYPE_ID),
         }
@@ -191,12 +388,8 @@ impl DwarfParser {
         let typ = match entry.tag() {
             gimli::DW_TAG_base_type => {
                 let name = Self::get_name(unit_ref, entry)?;
-                let encoding_attr = entry.attr_value(gimli::DW_AT_encoding)?.ok_or(anyhow!("get encoding value"))?;
-                let encoding = match encoding_attr {
-                    gimli::AttributeValue::Encoding(encoding) => encoding,
-                    _ => bail!("unexpected encoding attr value"),
-                };
-                let size = Self::get_byte_size(entry)?;
+                let encoding = Self::get_encoding(unit_ref, entry)?;
+                let size = Self::get_byte_size(unit_ref, entry)?;
 
                 Type::Base { name, encoding, size }
             }
@@ -218,51 +411,44 @@ impl DwarfParser {
             gimli::DW_TAG_pointer_type => {
                 let subtype_id = Self::process_entry_type(type_storage, unit_ref, entry, visited_types)?;
 
-                match type_storage.unwind_type(subtype_id)? {
-                    Type::Base { encoding, .. } => {
-                        // check for c-string
-                        if encoding == gimli::DW_ATE_signed_char {
-                            Type::String(subtype_id)
-                        } else {
-                            Type::Pointer(subtype_id)
-                        }
+                if Self::is_char_type(type_storage, subtype_id)? {
+                    Type::String(subtype_id)
+                } else {
+                    match type_storage.unwind_type(subtype_id)? {
+                        Type::FuncDef { .. } => Type::Func(subtype_id),
+                        _ => Type::Pointer(subtype_id),
                     }
-                    Type::FuncDef { .. } => Type::Func(subtype_id),
-                    _ => Type::Pointer(subtype_id),
                 }
             }
             gimli::DW_TAG_array_type => {
                 let subtype_id = Self::process_entry_type(type_storage, unit_ref, entry, visited_types)?;
-                let dimensions = Self::map_subtree(unit_ref, entry, gimli::DW_TAG_subrange_type, |child_entry| {
-                    Self::get_array_count(unit_ref, child_entry)
+                let mut dimensions = Self::map_subtree(unit_ref, entry, gimli::DW_TAG_subrange_type, |child_entry| {
+                    Ok((Self::get_array_count(unit_ref, child_entry)?, Self::get_array_bit_stride(child_entry)?))
                 })?;
+                // stride of the array is the distance between its elements, so it belongs to the innermost dimension
+                if let Some((_, bit_stride @ None)) = dimensions.last_mut() {
+                    *bit_stride = Self::get_array_bit_stride(entry)?;
+                }
 
-                let first_dimension = dimensions[0].clone();
+                let (first_dimension, first_bit_stride) = dimensions[0].clone();
                 // create type for every nested dimension
-                let subtype_id = dimensions
-                    .into_iter()
-                    .skip(1)
-                    .rev()
-                    .fold(subtype_id, |subtype_id, count| type_storage.add(Type::Array { subtype_id, count }));
+                let subtype_id = dimensions.into_iter().skip(1).rev().fold(subtype_id, |subtype_id, (count, bit_stride)| {
+                    type_storage.add(Type::Array { subtype_id, count, bit_stride })
+                });
 
                 Type::Array {
                     subtype_id,
                     count: first_dimension,
+                    bit_stride: first_bit_stride,
                 }
             }
             gimli::DW_TAG_structure_type => {
                 let name = Self::get_optional_name(unit_ref, entry)?;
-                let size = if Self::is_declaration(entry)? { 0 } else { Self::get_byte_size(entry)? };
 
                 let fields = Self::map_subtree(unit_ref, entry, gimli::DW_TAG_member, |child_entry| {
                     let member_name = Self::get_name(unit_ref, child_entry)?;
 
-                    // todo location
-                    let member_location = child_entry
-                        .attr_value(gimli::DW_AT_data_member_location)?
-                        .ok_or(anyhow!("get data member location attr value"))?
-                        .u16_value()
-                        .ok_or(anyhow!("convert data member location to u16"))?;
+                    let member_location = Self::get_member_location(unit_ref, child_entry)?;
 
                     let member_type_id = Self::process_entry_type(type_storage, unit_ref, child_entry, visited_types)?;
 
@@ -273,6 +459,23 @@ impl DwarfParser {
                     })
                 })?;
 
+                let size = if Self::is_declaration(unit_ref, entry)? {
+                    0
+                } else {
+                    match Self::get_optional_byte_size(unit_ref, entry)? {
+                        Some(size) => size,
+                        // size up to the end of the last field, trailing padding is unknown
+                        None => fields.iter().try_fold(0, |size, field| -> Result<u16> {
+                            let field_size = Self::get_static_type_size(unit_ref, entry, type_storage, field.type_id)?;
+                            let field_end = field
+                                .offset
+                                .checked_add(field_size)
+                                .ok_or_else(|| Self::missing_attr(unit_ref, entry, gimli::DW_AT_byte_size))?;
+                            Ok(size.max(field_end))
+                        })?,
+                    }
+                };
+
                 Type::Struct {
                     name,
                     size,
@@ -286,16 +489,13 @@ impl DwarfParser {
                         let subtype_id = Self::process_entry_type(type_storage, unit_ref, entry, visited_types)?;
                         match type_storage.get(subtype_id)? {
                             Type::Base { encoding, size, .. } => (encoding, size),
-                            _ => bail!("invalid enum subtype"),
+                            _ => bail!(Self::unexpected_form(unit_ref, entry, gimli::DW_AT_type)),
                         }
                     }
                     None => {
-                        let encoding_attr = entry.attr_value(gimli::DW_AT_encoding)?.ok_or(anyhow!("get encoding value"))?;
-                        let encoding = match encoding_attr {
-                            gimli::AttributeValue::Encoding(encoding) => encoding,
-                            _ => bail!("unexpected encoding attr value"),
-                        };
-                        let size = Self::get_byte_size(entry)?;
+                        let encoding = Self::get_encoding(unit_ref, entry)?;
+                        // c enums are int by default
+                        let size = Self::get_optional_byte_size(unit_ref, entry)?.unwrap_or(4);
 
                         (encoding, size)
                     }
@@ -305,13 +505,17 @@ impl DwarfParser {
                     let variant_name = Self::get_name(unit_ref, child_entry)?;
                     let variant_value = child_entry
                         .attr_value(gimli::DW_AT_const_value)?
-                        .ok_or(anyhow!("get const value attr"))?
-                        .sdata_value()
-                        .ok_or(anyhow!("get variant value"))?;
+                        .ok_or_else(|| Self::missing_attr(unit_ref, child_entry, gimli::DW_AT_const_value))?;
+                    // fixed size forms have no signedness, so they are extended by the enum encoding
+                    let variant_value = match encoding {
+                        gimli::DW_ATE_unsigned => variant_value.udata_value().map(|value| value as i64).or_else(|| variant_value.sdata_value()),
+                        _ => variant_value.sdata_value().or_else(|| variant_value.udata_value().map(|value| value as i64)),
+                    }
+                    .ok_or_else(|| Self::unexpected_form(unit_ref, child_entry, gimli::DW_AT_const_value))?;
 
                     Ok(EnumVariant {
                         name: variant_name,
-                        value: variant_value,
+                        value: Self::extend_enum_value(variant_value, encoding, size),
                     })
                 })?;
 
@@ -324,7 +528,6 @@ impl DwarfParser {
             }
             gimli::DW_TAG_union_type => {
                 let name = Self::get_optional_name(unit_ref, entry)?;
-                let size = Self::get_byte_size(entry)?;
 
                 let fields = Self::map_subtree(unit_ref, entry, gimli::DW_TAG_member, |child_entry| {
                     let name = Self::get_name(unit_ref, child_entry)?;
@@ -333,6 +536,13 @@ impl DwarfParser {
                     Ok(UnionField { name, type_id })
                 })?;
 
+                let size = match Self::get_optional_byte_size(unit_ref, entry)? {
+                    Some(size) => size,
+                    None => fields.iter().try_fold(0, |size, field| -> Result<u16> {
+                        Ok(size.max(Self::get_static_type_size(unit_ref, entry, type_storage, field.type_id)?))
+                    })?,
+                };
+
                 Type::Union {
                     name,
                     size,
@@ -352,14 +562,19 @@ impl DwarfParser {
                 let args = Self::map_subtree(unit_ref, entry, gimli::DW_TAG_formal_parameter, |child_entry| {
                     Self::process_entry_type(type_storage, unit_ref, child_entry, visited_types)
                 })?;
+                let variadic = !Self::map_subtree(unit_ref, entry, gimli::DW_TAG_unspecified_parameters, |_| Ok(()))?.is_empty();
 
                 Type::FuncDef {
                     name,
                     return_type_id,
                     args: Rc::new(args),
+                    variadic,
                 }
             }
-            tag_type => bail!("unexpected tag type {}", tag_type),
+            tag => bail!(DwarfParseError::UnsupportedTag {
+                tag,
+                location: Self::get_entry_location(unit_ref, entry),
+            }),
         };
 
         type_storage.replace(type_id, typ)?;
@@ -367,8 +582,28 @@ impl DwarfParser {
         Ok(type_id)
     }
 
+    /// checks if pointer to the type is a string, either c-string or wide string
+    fn is_char_type<R: gimli::Reader>(type_storage: &TypeStorage<R>, type_id: TypeId) -> Result<bool> {
+        match type_storage.get(type_id)? {
+            Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) => Self::is_char_type(type_storage, subtype_id),
+            // c has no builtin wide chars, they are typedefs of integer types
+            Type::Typedef(name, subtype_id) => {
+                Ok(matches!(name.as_ref(), "wchar_t" | "char16_t" | "char32_t") || Self::is_char_type(type_storage, subtype_id)?)
+            }
+            Type::Base { name, encoding, size } => Ok(match encoding {
+                gimli::DW_ATE_signed_char => size == 1 || size == 2 || size == 4,
+                gimli::DW_ATE_unsigned_char => size == 2 || size == 4,
+                gimli::DW_ATE_UTF => size == 1 || size == 2 || size == 4,
+                _ => name.as_ref() == "wchar_t", // c++ wchar_t is a signed int
+            }),
+            _ => Ok(false),
+        }
+    }
+
     fn get_name<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<Rc<str>> {
-        let name_attr = entry.attr_value(gimli::DW_AT_name)?.ok_or(anyhow!("get name attr value"))?;
+        let name_attr = entry
+            .attr_value(gimli::DW_AT_name)?
+            .ok_or_else(|| Self::missing_attr(unit_ref, entry, gimli::DW_AT_name))?;
         let name = Rc::from(unit_ref.attr_string(name_attr)?.to_string()?);
         Ok(name)
     }
@@ -380,19 +615,126 @@ impl DwarfParser {
         }
     }
 
-    fn get_byte_size<R: gimli::Reader>(entry: &gimli::DebuggingInformationEntry<R>) -> Result<u16> {
-        entry
-            .attr_value(gimli::DW_AT_byte_size)?
-            .ok_or(anyhow!("get byte size value"))?
-            .u16_value()
-            .ok_or(anyhow!("convert byte size to u16"))
+    fn get_byte_size<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<u16> {
+        let size = Self::get_optional_byte_size(unit_ref, entry)?.ok_or_else(|| Self::missing_attr(unit_ref, entry, gimli::DW_AT_byte_size))?;
+        Ok(size)
+    }
+
+    fn get_optional_byte_size<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<Option<u16>> {
+        match entry.attr_value(gimli::DW_AT_byte_size)? {
+            Some(value) => Ok(Some(
+                value
+                    .u16_value()
+                    .ok_or_else(|| Self::unexpected_form(unit_ref, entry, gimli::DW_AT_byte_size))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// size of already parsed type, used when byte size is omitted.
+    /// Unlike session it can't evaluate vla bounds
+    fn get_static_type_size<R: gimli::Reader>(
+        unit_ref: &gimli::UnitRef<R>,
+        entry: &gimli::DebuggingInformationEntry<R>,
+        type_storage: &TypeStorage<R>,
+        type_id: TypeId,
+    ) -> Result<u16> {
+        let size = match type_storage.get(type_id)? {
+            Type::Base { size, .. } | Type::Struct { size, .. } | Type::Enum { size, .. } | Type::Union { size, .. } => size,
+            Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) | Type::Typedef(_, subtype_id) => {
+                Self::get_static_type_size(unit_ref, entry, type_storage, subtype_id)?
+            }
+            Type::Pointer(_) | Type::String(_) | Type::Func(_) => WORD_SIZE as u16,
+            Type::Array {
+                subtype_id,
+                count: ArrayCount::Static(count),
+                bit_stride,
+            } => {
+                let bit_stride = match bit_stride {
+                    Some(bit_stride) => bit_stride,
+                    None => Self::get_static_type_size(unit_ref, entry, type_storage, subtype_id)? as u64 * 8,
+                };
+                (count as u64)
+                    .checked_mul(bit_stride)
+                    .and_then(|bits| u16::try_from(bits.div_ceil(8)).ok())
+                    .ok_or_else(|| Self::missing_attr(unit_ref, entry, gimli::DW_AT_byte_size))?
+            }
+            Type::Array {
+                count: ArrayCount::Flexible, ..
+            } => 0,
+            Type::Void | Type::FuncDef { .. } | Type::Array { .. } => bail!(Self::missing_attr(unit_ref, entry, gimli::DW_AT_byte_size)),
+        };
+
+        Ok(size)
+    }
+
+    /// enum value as it's read from memory: sign or zero extended from the enum width,
+    /// values of unsigned enums past i64::MAX are kept as two's complement
+    fn extend_enum_value(value: i64, encoding: gimli::DwAte, size: u16) -> i64 {
+        if !(1..8).contains(&size) {
+            return value;
+        }
+
+        let shift = 64 - size as u32 * 8;
+        match encoding {
+            gimli::DW_ATE_unsigned => ((value as u64) << shift >> shift) as i64,
+            _ => value << shift >> shift,
+        }
+    }
+
+    fn get_encoding<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<gimli::DwAte> {
+        match entry.attr_value(gimli::DW_AT_encoding)? {
+            Some(gimli::AttributeValue::Encoding(encoding)) => Ok(encoding),
+            Some(_) => bail!(Self::unexpected_form(unit_ref, entry, gimli::DW_AT_encoding)),
+            None => bail!(Self::missing_attr(unit_ref, entry, gimli::DW_AT_encoding)),
+        }
+    }
+
+    /// offset of struct member, union members are all at the start of the union and aren't read here
+    fn get_member_location<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<u16> {
+        let location = match entry.attr_value(gimli::DW_AT_data_member_location)? {
+            Some(location) => location,
+            None => return Self::get_member_bit_location(unit_ref, entry),
+        };
+
+        let unexpected_form = || Self::unexpected_form(unit_ref, entry, gimli::DW_AT_data_member_location);
+
+        if let Some(offset) = location.udata_value() {
+            return Ok(u16::try_from(offset).map_err(|_| unexpected_form())?);
+        }
+
+        // dwarf 2 style location, like DW_OP_plus_uconst, is evaluated against struct address
+        let expr = location.exprloc_value().ok_or_else(unexpected_form)?;
+        let mut eval = expr.evaluation(unit_ref.encoding());
+        eval.set_initial_value(0);
+        if !matches!(eval.evaluate()?, gimli::EvaluationResult::Complete) {
+            bail!(unexpected_form());
+        }
+        let offset = match eval.result().first().map(|piece| &piece.location) {
+            Some(gimli::Location::Address { address }) => *address,
+            _ => bail!(unexpected_form()),
+        };
+
+        Ok(u16::try_from(offset).map_err(|_| unexpected_form())?)
+    }
+
+    /// dwarf 4+ gives bitfield members location in bits from the struct start,
+    /// bitfields aren't extracted, so member is read from the byte holding its first bit
+    fn get_member_bit_location<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<u16> {
+        let bit_offset = entry
+            .attr_value(gimli::DW_AT_data_bit_offset)?
+            .ok_or_else(|| Self::missing_attr(unit_ref, entry, gimli::DW_AT_data_member_location))?
+            .udata_value()
+            .ok_or_else(|| Self::unexpected_form(unit_ref, entry, gimli::DW_AT_data_bit_offset))?;
+
+        Ok(u16::try_from(bit_offset / 8).map_err(|_| Self::unexpected_form(unit_ref, entry, gimli::DW_AT_data_bit_offset))?)
     }
 
-    fn is_declaration<R: gimli::Reader>(entry: &gimli::DebuggingInformationEntry<R>) -> Result<bool> {
+    fn is_declaration<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<bool> {
         match entry.attr_value(gimli::DW_AT_declaration)? {
             Some(value) => match value {
                 gimli::AttributeValue::Flag(value) => Ok(value),
-                _ => bail!("unexpected declaration attr value"),
+                _ => bail!(Self::unexpected_form(unit_ref, entry, gimli::DW_AT_declaration)),
             },
             None => Ok(false),
         }
@@ -424,6 +766,10 @@ impl DwarfParser {
             None => return Ok(()),
         };
         let mut rows = program.rows();
+        let comp_dir = match unit_ref.comp_dir.clone() {
+            Some(comp_dir) => Some(PathBuf::from(comp_dir.to_string()?.as_ref())),
+            None => None,
+        };
 
         while let Some((header, row)) = rows.next_row()? {
             if row.end_sequence() {
@@ -442,20 +788,25 @@ impl DwarfParser {
                 path.push(unit_ref.attr_string(dir)?.to_string()?.as_ref());
             }
             path.push(unit_ref.attr_string(file.path_name())?.to_string()?.as_ref());
-            let filepath = Rc::from(path.into_os_string().into_string().map_err(|_| anyhow!("convert path to string"))?);
+            // relative paths are relative to compilation directory
+            let source_path = match &comp_dir {
+                Some(comp_dir) => comp_dir.join(&path),
+                None => path.clone(),
+            };
+            let filepath: Rc<str> = Rc::from(path.into_os_string().into_string().map_err(|_| anyhow!("convert path to string"))?);
+            loc_finder.add_source_path(filepath.clone(), source_path);
 
             let line = row.line().ok_or(anyhow!("get line number"))?.get() as usize;
 
-            loc_finder.add_line(filepath, line, row.address());
+            loc_finder.add_line(filepath, line, row.address(), row.is_stmt(), row.prologue_end());
         }
 
         Ok(())
     }
 
+    /// bounds which aren't constants (expressions or references to variables holding them) are evaluated at runtime
     fn get_array_count<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<ArrayCount<R>> {
-        let unit_offset = unit_ref.header.offset().as_debug_info_offset().ok_or(anyhow!("can't get debug_info offest"))?;
-        let entry_offset = entry.offset();
-        let entry_ref = EntryRef::new(unit_offset, entry_offset);
+        let entry_ref = Self::get_entry_ref(unit_ref, entry)?;
 
         match entry.attr_value(gimli::DW_AT_count)? {
             Some(value) => match value.udata_value() {
@@ -483,4 +834,639 @@ impl DwarfParser {
             }
         }
     }
+
+    /// stride of subrange or array, dynamic strides aren't supported
+    fn get_array_bit_stride<R: gimli::Reader>(entry: &gimli::DebuggingInformationEntry<R>) -> Result<Option<u64>> {
+        if let Some(bit_stride) = entry.attr_value(gimli::DW_AT_bit_stride)?.and_then(|value| value.udata_value()) {
+            return Ok(Some(bit_stride));
+        }
+
+        Ok(entry
+            .attr_value(gimli::DW_AT_byte_stride)?
+            .and_then(|value| value.udata_value())
+            .map(|byte_stride| byte_stride * 8))
+    }
+
+    fn get_entry_ref<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<EntryRef<R::Offset>> {
+        // only units from .debug_info are supported
+        let unit_offset = unit_ref.header.offset().as_debug_info_offset().ok_or_else(|| DwarfParseError::BadOffset {
+            location: Self::get_entry_location(unit_ref, entry),
+        })?;
+
+        Ok(EntryRef::new(unit_offset, entry.offset()))
+    }
+
+    fn get_entry_location<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> EntryLocation {
+        EntryLocation {
+            unit_offset: Self::get_section_offset(unit_ref.header.offset()),
+            entry_offset: Self::get_section_offset(entry.offset().to_unit_section_offset(unit_ref.unit)),
+        }
+    }
+
+    fn get_section_offset<T: ReaderOffset>(offset: gimli::UnitSectionOffset<T>) -> u64 {
+        match offset {
+            gimli::UnitSectionOffset::DebugInfoOffset(offset) => offset.0.into_u64(),
+            gimli::UnitSectionOffset::DebugTypesOffset(offset) => offset.0.into_u64(),
+        }
+    }
+
+    fn missing_attr<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>, attr: gimli::DwAt) -> DwarfParseError {
+        DwarfParseError::MissingAttr {
+            attr,
+            location: Self::get_entry_location(unit_ref, entry),
+        }
+    }
+
+    fn unexpected_form<R: gimli::Reader>(unit_ref: &gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>, attr: gimli::DwAt) -> DwarfParseError {
+        DwarfParseError::UnexpectedForm {
+            attr,
+            location: Self::get_entry_location(unit_ref, entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gimli::write::{Address, AttributeValue, UnitEntryId};
+
+    use super::*;
+    use crate::test_dwarf::{build_raw_unit, string, DwarfBuilder, RawEntry, RawValue, Reader};
+
+    fn get_var_type(loc_finder: &LocFinder<Reader>, type_storage: &TypeStorage<Reader>, name: &str) -> Type<Reader> {
+        let var_ref = loc_finder.get_var(name, None, None, None).unwrap().unwrap();
+        type_storage.get(var_ref.type_id).unwrap()
+    }
+
+    fn get_subtype(type_storage: &TypeStorage<Reader>, type_id: TypeId) -> Type<Reader> {
+        type_storage.get(type_id).unwrap()
+    }
+
+    fn add_type(builder: &mut DwarfBuilder, unit_id: gimli::write::UnitId, tag: gimli::DwTag, subtype: Option<UnitEntryId>) -> UnitEntryId {
+        let attrs = subtype
+            .map(|subtype| (gimli::DW_AT_type, AttributeValue::UnitRef(subtype)))
+            .into_iter()
+            .collect::<Vec<_>>();
+        builder.add_entry(unit_id, None, tag, &attrs)
+    }
+
+    #[test]
+    fn test_process_base_and_qualified_types() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        let const_int = add_type(&mut builder, unit, gimli::DW_TAG_const_type, Some(int));
+        let volatile_int = add_type(&mut builder, unit, gimli::DW_TAG_volatile_type, Some(int));
+        let atomic_int = add_type(&mut builder, unit, gimli::DW_TAG_atomic_type, Some(int));
+        let typedef = builder.add_entry(
+            unit,
+            None,
+            gimli::DW_TAG_typedef,
+            &[(gimli::DW_AT_name, string("number")), (gimli::DW_AT_type, AttributeValue::UnitRef(int))],
+        );
+        builder.add_var(unit, "i", int);
+        builder.add_var(unit, "c", const_int);
+        builder.add_var(unit, "v", volatile_int);
+        builder.add_var(unit, "a", atomic_int);
+        builder.add_var(unit, "n", typedef);
+        let (loc_finder, type_storage) = builder.parse();
+
+        match get_var_type(&loc_finder, &type_storage, "i") {
+            Type::Base { name, encoding, size } => {
+                assert_eq!(name.as_ref(), "int");
+                assert_eq!(encoding, gimli::DW_ATE_signed);
+                assert_eq!(size, 4);
+            }
+            typ => panic!("unexpected type {:?}", typ),
+        }
+
+        for name in ["c", "v", "a"] {
+            let subtype_id = match get_var_type(&loc_finder, &type_storage, name) {
+                Type::Const(subtype_id) if name == "c" => subtype_id,
+                Type::Volatile(subtype_id) if name == "v" => subtype_id,
+                Type::Atomic(subtype_id) if name == "a" => subtype_id,
+                typ => panic!("unexpected type {:?} of {}", typ, name),
+            };
+            assert!(matches!(get_subtype(&type_storage, subtype_id), Type::Base { .. }));
+        }
+
+        match get_var_type(&loc_finder, &type_storage, "n") {
+            Type::Typedef(name, subtype_id) => {
+                assert_eq!(name.as_ref(), "number");
+                assert!(matches!(get_subtype(&type_storage, subtype_id), Type::Base { .. }));
+            }
+            typ => panic!("unexpected type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn test_process_pointer_types() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        let char = builder.add_base_type(unit, "char", gimli::DW_ATE_signed_char, 1);
+        let int_ptr = add_type(&mut builder, unit, gimli::DW_TAG_pointer_type, Some(int));
+        let char_ptr = add_type(&mut builder, unit, gimli::DW_TAG_pointer_type, Some(char));
+        let void_ptr = add_type(&mut builder, unit, gimli::DW_TAG_pointer_type, None);
+        let func = add_type(&mut builder, unit, gimli::DW_TAG_subroutine_type, Some(int));
+        builder.add_entry(
+            unit,
+            Some(func),
+            gimli::DW_TAG_formal_parameter,
+            &[(gimli::DW_AT_type, AttributeValue::UnitRef(char_ptr))],
+        );
+        builder.add_entry(unit, Some(func), gimli::DW_TAG_unspecified_parameters, &[]);
+        let func_ptr = add_type(&mut builder, unit, gimli::DW_TAG_pointer_type, Some(func));
+        builder.add_var(unit, "p", int_ptr);
+        builder.add_var(unit, "s", char_ptr);
+        builder.add_var(unit, "v", void_ptr);
+        builder.add_var(unit, "f", func_ptr);
+        let (loc_finder, type_storage) = builder.parse();
+
+        match get_var_type(&loc_finder, &type_storage, "p") {
+            Type::Pointer(subtype_id) => assert!(matches!(get_subtype(&type_storage, subtype_id), Type::Base { size: 4, .. })),
+            typ => panic!("unexpected type {:?}", typ),
+        }
+        match get_var_type(&loc_finder, &type_storage, "s") {
+            Type::String(subtype_id) => assert!(matches!(get_subtype(&type_storage, subtype_id), Type::Base { size: 1, .. })),
+            typ => panic!("unexpected type {:?}", typ),
+        }
+        assert!(matches!(get_var_type(&loc_finder, &type_storage, "v"), Type::Pointer(VOID_TYPE_ID)));
+        match get_var_type(&loc_finder, &type_storage, "f") {
+            Type::Func(subtype_id) => match get_subtype(&type_storage, subtype_id) {
+                Type::FuncDef { args, variadic, .. } => {
+                    assert_eq!(args.len(), 1);
+                    assert!(variadic);
+                }
+                typ => panic!("unexpected type {:?}", typ),
+            },
+            typ => panic!("unexpected type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn test_process_array_type() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        let matrix = add_type(&mut builder, unit, gimli::DW_TAG_array_type, Some(int));
+        builder.add_entry(
+            unit,
+            Some(matrix),
+            gimli::DW_TAG_subrange_type,
+            &[(gimli::DW_AT_count, AttributeValue::Udata(2))],
+        );
+        builder.add_entry(
+            unit,
+            Some(matrix),
+            gimli::DW_TAG_subrange_type,
+            &[(gimli::DW_AT_upper_bound, AttributeValue::Udata(2))],
+        );
+        let flexible = add_type(&mut builder, unit, gimli::DW_TAG_array_type, Some(int));
+        builder.add_entry(unit, Some(flexible), gimli::DW_TAG_subrange_type, &[]);
+        builder.add_var(unit, "m", matrix);
+        builder.add_var(unit, "f", flexible);
+        let (loc_finder, type_storage) = builder.parse();
+
+        match get_var_type(&loc_finder, &type_storage, "m") {
+            Type::Array {
+                subtype_id,
+                count: ArrayCount::Static(2),
+                ..
+            } => assert!(matches!(
+                get_subtype(&type_storage, subtype_id),
+                Type::Array {
+                    count: ArrayCount::Static(3),
+                    ..
+                }
+            )),
+            typ => panic!("unexpected type {:?}", typ),
+        }
+        assert!(matches!(
+            get_var_type(&loc_finder, &type_storage, "f"),
+            Type::Array {
+                count: ArrayCount::Flexible,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_process_vla_count_ref() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        let size_type = builder.add_base_type(unit, "__ARRAY_SIZE_TYPE__", gimli::DW_ATE_unsigned, 8);
+        let len = builder.add_var(unit, "__vla_expr0", size_type);
+        let vla = add_type(&mut builder, unit, gimli::DW_TAG_array_type, Some(int));
+        builder.add_entry(
+            unit,
+            Some(vla),
+            gimli::DW_TAG_subrange_type,
+            &[(gimli::DW_AT_count, AttributeValue::UnitRef(len))],
+        );
+        builder.add_var(unit, "a", vla);
+        let (loc_finder, type_storage) = builder.parse();
+
+        // length is read from the variable at runtime
+        assert!(matches!(
+            get_var_type(&loc_finder, &type_storage, "a"),
+            Type::Array {
+                count: ArrayCount::Dynamic(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_process_array_stride() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let char = builder.add_base_type(unit, "char", gimli::DW_ATE_signed_char, 1);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        let packed = builder.add_entry(
+            unit,
+            None,
+            gimli::DW_TAG_array_type,
+            &[
+                (gimli::DW_AT_type, AttributeValue::UnitRef(char)),
+                (gimli::DW_AT_bit_stride, AttributeValue::Udata(4)),
+            ],
+        );
+        builder.add_entry(
+            unit,
+            Some(packed),
+            gimli::DW_TAG_subrange_type,
+            &[(gimli::DW_AT_count, AttributeValue::Udata(5))],
+        );
+        let strided = add_type(&mut builder, unit, gimli::DW_TAG_array_type, Some(int));
+        builder.add_entry(
+            unit,
+            Some(strided),
+            gimli::DW_TAG_subrange_type,
+            &[
+                (gimli::DW_AT_count, AttributeValue::Udata(2)),
+                (gimli::DW_AT_byte_stride, AttributeValue::Udata(8)),
+            ],
+        );
+        builder.add_entry(
+            unit,
+            Some(strided),
+            gimli::DW_TAG_subrange_type,
+            &[(gimli::DW_AT_count, AttributeValue::Udata(3))],
+        );
+        // size of struct without byte size is computed from its packed member
+        let holder = builder.add_entry(unit, None, gimli::DW_TAG_structure_type, &[]);
+        builder.add_entry(
+            unit,
+            Some(holder),
+            gimli::DW_TAG_member,
+            &[
+                (gimli::DW_AT_name, string("nibbles")),
+                (gimli::DW_AT_type, AttributeValue::UnitRef(packed)),
+                (gimli::DW_AT_data_member_location, AttributeValue::Udata(0)),
+            ],
+        );
+        builder.add_var(unit, "h", holder);
+        builder.add_var(unit, "s", strided);
+        let (loc_finder, type_storage) = builder.parse();
+
+        match get_var_type(&loc_finder, &type_storage, "h") {
+            Type::Struct { size, fields, .. } => {
+                assert_eq!(size, 3);
+                assert!(matches!(
+                    get_subtype(&type_storage, fields[0].type_id),
+                    Type::Array {
+                        count: ArrayCount::Static(5),
+                        bit_stride: Some(4),
+                        ..
+                    }
+                ));
+            }
+            typ => panic!("unexpected type {:?}", typ),
+        }
+        match get_var_type(&loc_finder, &type_storage, "s") {
+            Type::Array {
+                subtype_id,
+                bit_stride: Some(64),
+                ..
+            } => assert!(matches!(get_subtype(&type_storage, subtype_id), Type::Array { bit_stride: None, .. })),
+            typ => panic!("unexpected type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn test_process_struct_and_union_types() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        let char = builder.add_base_type(unit, "char", gimli::DW_ATE_signed_char, 1);
+        // no byte size, it's computed from fields
+        let point = builder.add_entry(unit, None, gimli::DW_TAG_structure_type, &[(gimli::DW_AT_name, string("point"))]);
+        for (name, offset) in [("x", 0), ("y", 4)] {
+            builder.add_entry(
+                unit,
+                Some(point),
+                gimli::DW_TAG_member,
+                &[
+                    (gimli::DW_AT_name, string(name)),
+                    (gimli::DW_AT_type, AttributeValue::UnitRef(int)),
+                    (gimli::DW_AT_data_member_location, AttributeValue::Udata(offset)),
+                ],
+            );
+        }
+        let value = builder.add_entry(
+            unit,
+            None,
+            gimli::DW_TAG_union_type,
+            &[(gimli::DW_AT_name, string("value")), (gimli::DW_AT_byte_size, AttributeValue::Data1(4))],
+        );
+        for (name, typ) in [("i", int), ("c", char)] {
+            builder.add_entry(
+                unit,
+                Some(value),
+                gimli::DW_TAG_member,
+                &[(gimli::DW_AT_name, string(name)), (gimli::DW_AT_type, AttributeValue::UnitRef(typ))],
+            );
+        }
+        builder.add_var(unit, "p", point);
+        builder.add_var(unit, "v", value);
+        let (loc_finder, type_storage) = builder.parse();
+
+        match get_var_type(&loc_finder, &type_storage, "p") {
+            Type::Struct { name, size, fields } => {
+                assert_eq!(name.as_deref(), Some("point"));
+                assert_eq!(size, 8);
+                let fields = fields.iter().map(|field| (field.name.as_ref(), field.offset)).collect::<Vec<_>>();
+                assert_eq!(fields, [("x", 0), ("y", 4)]);
+            }
+            typ => panic!("unexpected type {:?}", typ),
+        }
+        match get_var_type(&loc_finder, &type_storage, "v") {
+            Type::Union { name, size, fields } => {
+                assert_eq!(name.as_deref(), Some("value"));
+                assert_eq!(size, 4);
+                assert_eq!(fields.len(), 2);
+            }
+            typ => panic!("unexpected type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn test_process_dwarf5_implicit_const() {
+        fn entry(tag: gimli::DwTag, attrs: Vec<(gimli::DwAt, RawValue)>, children: Vec<RawEntry>) -> RawEntry {
+            RawEntry { tag, attrs, children }
+        }
+
+        fn member(name: &'static str, location: (gimli::DwAt, RawValue)) -> RawEntry {
+            let attrs = vec![(gimli::DW_AT_name, RawValue::String(name)), (gimli::DW_AT_type, RawValue::Ref(1)), location];
+            entry(gimli::DW_TAG_member, attrs, vec![])
+        }
+
+        // clang shares sizes and member locations between entries with DW_FORM_implicit_const
+        let int = entry(
+            gimli::DW_TAG_base_type,
+            vec![
+                (gimli::DW_AT_name, RawValue::String("int")),
+                (gimli::DW_AT_encoding, RawValue::Data1(gimli::DW_ATE_signed.0)),
+                (gimli::DW_AT_byte_size, RawValue::ImplicitConst(4)),
+            ],
+            vec![],
+        );
+        let point = entry(
+            gimli::DW_TAG_structure_type,
+            vec![
+                (gimli::DW_AT_name, RawValue::String("point")),
+                (gimli::DW_AT_byte_size, RawValue::ImplicitConst(12)),
+            ],
+            vec![
+                member("x", (gimli::DW_AT_data_member_location, RawValue::ImplicitConst(0))),
+                member("y", (gimli::DW_AT_data_member_location, RawValue::ImplicitConst(4))),
+                // bitfield location is in bits
+                member("flags", (gimli::DW_AT_data_bit_offset, RawValue::ImplicitConst(64))),
+            ],
+        );
+        let var = entry(
+            gimli::DW_TAG_variable,
+            vec![(gimli::DW_AT_name, RawValue::String("p")), (gimli::DW_AT_type, RawValue::Ref(2))],
+            vec![],
+        );
+        let unit = entry(
+            gimli::DW_TAG_compile_unit,
+            vec![
+                (gimli::DW_AT_name, RawValue::String("t.c")),
+                (gimli::DW_AT_low_pc, RawValue::Addr(0x1000)),
+                (gimli::DW_AT_high_pc, RawValue::Data8(0x1000)),
+            ],
+            vec![int, point, var],
+        );
+        let (loc_finder, type_storage) = DwarfParser::parse(&build_raw_unit(&unit), 0).unwrap();
+
+        match get_var_type(&loc_finder, &type_storage, "p") {
+            Type::Struct { name, size, fields } => {
+                assert_eq!(name.as_deref(), Some("point"));
+                assert_eq!(size, 12);
+                assert!(matches!(get_subtype(&type_storage, fields[0].type_id), Type::Base { size: 4, .. }));
+                let fields = fields.iter().map(|field| (field.name.as_ref(), field.offset)).collect::<Vec<_>>();
+                assert_eq!(fields, [("x", 0), ("y", 4), ("flags", 8)]);
+            }
+            typ => panic!("unexpected type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn test_process_enum_type() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let color = builder.add_entry(
+            unit,
+            None,
+            gimli::DW_TAG_enumeration_type,
+            &[
+                (gimli::DW_AT_name, string("color")),
+                (gimli::DW_AT_encoding, AttributeValue::Encoding(gimli::DW_ATE_unsigned)),
+            ],
+        );
+        for (name, value) in [("red", 0), ("blue", -1)] {
+            builder.add_entry(
+                unit,
+                Some(color),
+                gimli::DW_TAG_enumerator,
+                &[(gimli::DW_AT_name, string(name)), (gimli::DW_AT_const_value, AttributeValue::Sdata(value))],
+            );
+        }
+        builder.add_var(unit, "c", color);
+        let (loc_finder, type_storage) = builder.parse();
+
+        match get_var_type(&loc_finder, &type_storage, "c") {
+            Type::Enum {
+                name,
+                encoding,
+                size,
+                variants,
+            } => {
+                assert_eq!(name.as_deref(), Some("color"));
+                assert_eq!(encoding, gimli::DW_ATE_unsigned);
+                assert_eq!(size, 4);
+                let variants = variants.iter().map(|variant| (variant.name.as_ref(), variant.value)).collect::<Vec<_>>();
+                // unsigned values are zero extended from the enum width
+                assert_eq!(variants, [("red", 0), ("blue", 0xffffffff)]);
+            }
+            typ => panic!("unexpected type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn test_process_signed_enum_type() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        let sparse = builder.add_entry(
+            unit,
+            None,
+            gimli::DW_TAG_enumeration_type,
+            &[(gimli::DW_AT_name, string("sparse")), (gimli::DW_AT_type, AttributeValue::UnitRef(int))],
+        );
+        // fixed size forms carry no sign
+        for (name, value) in [("A", AttributeValue::Data4(0xffffffff)), ("B", AttributeValue::Data4(1000000))] {
+            builder.add_entry(
+                unit,
+                Some(sparse),
+                gimli::DW_TAG_enumerator,
+                &[(gimli::DW_AT_name, string(name)), (gimli::DW_AT_const_value, value)],
+            );
+        }
+        builder.add_var(unit, "s", sparse);
+        let (loc_finder, type_storage) = builder.parse();
+
+        match get_var_type(&loc_finder, &type_storage, "s") {
+            Type::Enum { encoding, variants, .. } => {
+                assert_eq!(encoding, gimli::DW_ATE_signed);
+                let variants = variants.iter().map(|variant| (variant.name.as_ref(), variant.value)).collect::<Vec<_>>();
+                assert_eq!(variants, [("A", -1), ("B", 1000000)]);
+            }
+            typ => panic!("unexpected type {:?}", typ),
+        }
+    }
+
+    #[test]
+    fn test_skip_unit_with_unsupported_tag() {
+        let mut builder = DwarfBuilder::new();
+        let broken_unit = builder.add_unit("broken.cpp", 0x1000, 0x2000);
+        let int = builder.add_base_type(broken_unit, "int", gimli::DW_ATE_signed, 4);
+        let reference = add_type(&mut builder, broken_unit, gimli::DW_TAG_reference_type, Some(int));
+        builder.add_var(broken_unit, "r", reference);
+        let unit = builder.add_unit("t.c", 0x2000, 0x3000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        builder.add_var(unit, "x", int);
+        let (loc_finder, _) = builder.parse();
+
+        assert!(loc_finder.get_var("r", None, None, None).unwrap().is_none());
+        assert!(loc_finder.get_var("x", None, None, None).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_qualified_globals() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        builder.add_var(unit, "counter", int);
+        let other_unit = builder.add_unit("lib/ns.cpp", 0x2000, 0x3000);
+        let int = builder.add_base_type(other_unit, "int", gimli::DW_ATE_signed, 4);
+        builder.add_var(other_unit, "counter", int);
+        // c++ namespace member is declared in the namespace and defined outside of it
+        let ns = builder.add_entry(other_unit, None, gimli::DW_TAG_namespace, &[(gimli::DW_AT_name, string("ns"))]);
+        let declaration = builder.add_entry(
+            other_unit,
+            Some(ns),
+            gimli::DW_TAG_variable,
+            &[
+                (gimli::DW_AT_name, string("total")),
+                (gimli::DW_AT_type, AttributeValue::UnitRef(int)),
+                (gimli::DW_AT_declaration, AttributeValue::Flag(true)),
+            ],
+        );
+        builder.add_entry(
+            other_unit,
+            None,
+            gimli::DW_TAG_variable,
+            &[(gimli::DW_AT_specification, AttributeValue::UnitRef(declaration))],
+        );
+        let (loc_finder, _) = builder.parse();
+
+        let get_unit_offset = |name, qualifier, unit_name| {
+            loc_finder
+                .get_var(name, qualifier, None, unit_name)
+                .map(|var_ref| var_ref.map(|var_ref| var_ref.entry_ref.unit_offset))
+        };
+        let main_unit_offset = get_unit_offset("counter", Some("t.c"), None).unwrap().unwrap();
+        let other_unit_offset = get_unit_offset("counter", Some("ns.cpp"), None).unwrap().unwrap();
+        assert_ne!(main_unit_offset, other_unit_offset);
+
+        assert!(get_unit_offset("counter", None, None).is_err());
+        assert_eq!(get_unit_offset("counter", None, Some("t.c")).unwrap(), Some(main_unit_offset));
+        assert_eq!(get_unit_offset("counter", Some("lib/ns.cpp"), Some("t.c")).unwrap(), Some(other_unit_offset));
+        assert_eq!(get_unit_offset("counter", Some("s.cpp"), None).unwrap(), None);
+        assert_eq!(get_unit_offset("total", Some("ns"), None).unwrap(), Some(other_unit_offset));
+        assert_eq!(get_unit_offset("total", Some("ns.cpp::ns"), None).unwrap(), Some(other_unit_offset));
+        assert_eq!(get_unit_offset("total", None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_lexical_block_vars() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x2000);
+        let int = builder.add_base_type(unit, "int", gimli::DW_ATE_signed, 4);
+        let func = builder.add_func(unit, "main", 0x1000, 0x1100);
+        let add_local = |builder: &mut DwarfBuilder, parent, name| {
+            builder.add_entry(
+                unit,
+                Some(parent),
+                gimli::DW_TAG_variable,
+                &[(gimli::DW_AT_name, string(name)), (gimli::DW_AT_type, AttributeValue::UnitRef(int))],
+            );
+        };
+        let add_block = |builder: &mut DwarfBuilder, parent, low_pc: u64, high_pc: u64| {
+            builder.add_entry(
+                unit,
+                Some(parent),
+                gimli::DW_TAG_lexical_block,
+                &[
+                    (gimli::DW_AT_low_pc, AttributeValue::Address(Address::Constant(low_pc))),
+                    (gimli::DW_AT_high_pc, AttributeValue::Udata(high_pc - low_pc)),
+                ],
+            )
+        };
+        add_local(&mut builder, func, "x");
+        let block = add_block(&mut builder, func, 0x1010, 0x1080);
+        add_local(&mut builder, block, "i");
+        add_local(&mut builder, block, "x");
+        let nested_block = add_block(&mut builder, block, 0x1020, 0x1040);
+        add_local(&mut builder, nested_block, "j");
+        // block split in two, the gap between them belongs to the outer block
+        let ranges = builder.add_range_list(unit, &[(0x1040, 0x1050), (0x1060, 0x1070)]);
+        let split_block = builder.add_entry(unit, Some(block), gimli::DW_TAG_lexical_block, &[(gimli::DW_AT_ranges, ranges)]);
+        add_local(&mut builder, split_block, "k");
+        let (loc_finder, _) = builder.parse();
+
+        let get_ranges = |name, pc| {
+            loc_finder
+                .get_var(name, None, Some(("main", pc)), None)
+                .unwrap()
+                .map(|var_ref| var_ref.pc_ranges.as_deref().map(<[_]>::to_vec))
+        };
+        assert_eq!(get_ranges("x", 0x1000), Some(None));
+        assert_eq!(get_ranges("i", 0x1000), None);
+        assert_eq!(get_ranges("i", 0x1010), Some(Some(vec![(0x1010, 0x107f)])));
+        assert_eq!(get_ranges("x", 0x1030), Some(Some(vec![(0x1010, 0x107f)])));
+        assert_eq!(get_ranges("j", 0x1030), Some(Some(vec![(0x1020, 0x103f)])));
+        assert_eq!(get_ranges("j", 0x1040), None);
+        assert_eq!(get_ranges("k", 0x1045), Some(Some(vec![(0x1040, 0x104f), (0x1060, 0x106f)])));
+        assert_eq!(get_ranges("k", 0x1055), None);
+        assert_eq!(get_ranges("k", 0x1065), Some(Some(vec![(0x1040, 0x104f), (0x1060, 0x106f)])));
+        assert_eq!(get_ranges("x", 0x1080), Some(None));
+
+        let mut names = loc_finder.get_func_vars("main", 0x1030).into_keys().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, [Rc::from("i"), Rc::from("j"), Rc::from("x")]);
+    }
 }
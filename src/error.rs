@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+use crate::consts::SUPPORTED_REGISTERS;
+use crate::watchpoint::WATCHPOINT_SLOTS;
+
 #[derive(Debug, Error)]
 pub enum DebuggerError {
     #[error("invalid command")]
@@ -12,10 +15,111 @@ pub enum DebuggerError {
     LocNotFound,
     #[error("{0} not found")]
     VarNotFound(String),
+    #[error("function {0} not found")]
+    FuncNotFound(String),
+    #[error("type {0} not found")]
+    TypeNotFound(String),
+    #[error("{0} is ambiguous, qualify it with a unit")]
+    AmbiguousVar(String),
     #[error("invalid path")]
     InvalidPath,
+    #[error("invalid cast")]
+    InvalidCast,
+    #[error("negative number")]
+    NegativeNumber,
     #[error("invalid value")]
     InvalidValue,
     #[error("invalid location")]
     InvalidLocation,
+    #[error("value has no address")]
+    NoAddress,
+    #[error("can't access memory at {0:#x}")]
+    InaccessibleMemory(u64),
+    #[error("can't dereference void pointer")]
+    VoidDeref,
+    #[error("can't dereference null pointer")]
+    NullDeref,
+    #[error("value optimized out")]
+    OptimizedOut,
+    #[error("thread-local variables are not supported")]
+    UnsupportedThreadLocal,
+    #[error("alias not found")]
+    AliasNotFound,
+    #[error("display not found")]
+    DisplayNotFound,
+    #[error("checkpoint not found")]
+    CheckpointNotFound,
+    #[error("memory layout changed since checkpoint")]
+    MemoryLayoutChanged,
+    #[error("inferior not found")]
+    InferiorNotFound,
+    #[error("invalid program")]
+    InvalidProgram,
+    #[error("not meaningful in the outermost frame")]
+    OutermostFrame,
+    #[error("frame not found")]
+    FrameNotFound,
+    #[error("innermost frame selected")]
+    InnermostFrame,
+    #[error("no debug info for frame")]
+    NoFrameDebugInfo,
+    #[error("no previous breakpoint hit")]
+    NoPreviousBreakpointHit,
+    #[error("program took another path while replaying")]
+    ReplayDiverged,
+    #[error("only variables in memory can be watched")]
+    UnsupportedWatchpoint,
+    #[error("watched variable must be aligned 1, 2, 4 or 8 bytes")]
+    UnsupportedWatchpointSize,
+    #[error("all {} hardware watchpoints are used", WATCHPOINT_SLOTS)]
+    NoFreeWatchpoint,
+    #[error("watchpoint not found")]
+    WatchpointNotFound,
+    #[error("invalid format")]
+    InvalidFormat,
+    #[error("can't access {path}: {source}")]
+    FileAccess { path: String, source: std::io::Error },
+    #[error("only integer and pointer arguments are supported")]
+    UnsupportedArgument,
+    #[error("at most {0} arguments are supported")]
+    TooManyArguments(usize),
+    #[error("functions returning {0} are not supported")]
+    UnsupportedReturnType(&'static str),
+    #[error("call of {0} was interrupted, the program is back where it was")]
+    CallInterrupted(String),
+    #[error("program exited during the call of {0}")]
+    ExitedDuringCall(String),
+    #[error("unsupported encoding {0}")]
+    UnsupportedEncoding(gimli::DwAte),
+    #[error("unsupported {class} register {name} (dwarf {number}), supported registers: {}", SUPPORTED_REGISTERS.join(", "))]
+    UnsupportedRegister { name: String, number: u16, class: &'static str },
+    #[error("cancelled")]
+    Cancelled,
+    #[error("program not running")]
+    NotRunning,
+}
+
+/// location of the offending entry, offsets are relative to the start of .debug_info
+#[derive(Debug, Clone, Copy)]
+pub struct EntryLocation {
+    pub unit_offset: u64,
+    pub entry_offset: u64,
+}
+
+impl std::fmt::Display for EntryLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "entry {:#x} in unit {:#x}", self.entry_offset, self.unit_offset)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DwarfParseError {
+    #[error("missing {attr} attr at {location}")]
+    MissingAttr { attr: gimli::DwAt, location: EntryLocation },
+    #[error("unexpected form of {attr} attr at {location}")]
+    UnexpectedForm { attr: gimli::DwAt, location: EntryLocation },
+    #[error("unsupported tag {tag} at {location}")]
+    UnsupportedTag { tag: gimli::DwTag, location: EntryLocation },
+    #[error("bad offset at {location}")]
+    BadOffset { location: EntryLocation },
 }
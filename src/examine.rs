@@ -0,0 +1,129 @@
+use std::ascii;
+
+use crate::error::DebuggerError;
+
+/// units printed on one line of `x` output
+pub const UNITS_PER_LINE: usize = 8;
+/// units read by one `x` command at most
+pub const MAX_COUNT: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnitFormat {
+    Hex,
+    Signed,
+    Unsigned,
+    Char,
+}
+
+/// `x/NFU` spec: count of units, format and size of a unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExamineFormat {
+    pub count: usize,
+    pub format: UnitFormat,
+    pub size: usize,
+}
+
+impl Default for ExamineFormat {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            format: UnitFormat::Hex,
+            size: 4,
+        }
+    }
+}
+
+impl ExamineFormat {
+    /// every part is optional, chars are bytes unless size is given
+    pub fn new(count: Option<&str>, format: Option<&str>, size: Option<&str>) -> Result<Self, DebuggerError> {
+        let mut examine_format = Self::default();
+
+        if let Some(count) = count {
+            examine_format.count = count
+                .parse::<usize>()
+                .ok()
+                .filter(|&count| count <= MAX_COUNT)
+                .ok_or(DebuggerError::InvalidFormat)?;
+        }
+
+        if let Some(format) = format {
+            examine_format.format = match format {
+                "x" => UnitFormat::Hex,
+                "d" => UnitFormat::Signed,
+                "u" => UnitFormat::Unsigned,
+                "c" => UnitFormat::Char,
+                _ => return Err(DebuggerError::InvalidFormat),
+            };
+        }
+
+        examine_format.size = match size {
+            Some("b") => 1,
+            Some("h") => 2,
+            Some("w") => 4,
+            Some("g") => 8,
+            Some(_) => return Err(DebuggerError::InvalidFormat),
+            None if examine_format.format == UnitFormat::Char => 1,
+            None => examine_format.size,
+        };
+
+        Ok(examine_format)
+    }
+
+    /// unit is little endian bytes of the unit size
+    pub fn format_unit(&self, unit: &[u8]) -> String {
+        let mut bytes = [0; 8];
+        bytes[..unit.len()].copy_from_slice(unit);
+        let value = u64::from_le_bytes(bytes);
+        let bits = unit.len() * 8;
+
+        match self.format {
+            UnitFormat::Hex => format!("{:#0width$x}", value, width = unit.len() * 2 + 2),
+            UnitFormat::Signed => {
+                // sign extend to 64 bits
+                let shift = 64 - bits;
+                format!("{}", ((value << shift) as i64) >> shift)
+            }
+            UnitFormat::Unsigned => format!("{}", value),
+            UnitFormat::Char => format!("'{}'", ascii::escape_default(unit[0])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(ExamineFormat::new(None, None, None).unwrap(), ExamineFormat::default());
+        assert_eq!(
+            ExamineFormat::new(Some("16"), Some("x"), Some("b")).unwrap(),
+            ExamineFormat {
+                count: 16,
+                format: UnitFormat::Hex,
+                size: 1
+            }
+        );
+        assert_eq!(ExamineFormat::new(None, Some("c"), None).unwrap().size, 1);
+        assert_eq!(ExamineFormat::new(None, Some("c"), Some("w")).unwrap().size, 4);
+        assert_eq!(ExamineFormat::new(None, Some("d"), None).unwrap().size, 4);
+        assert!(ExamineFormat::new(None, Some("s"), None).is_err());
+        assert!(ExamineFormat::new(None, None, Some("q")).is_err());
+        assert_eq!(ExamineFormat::new(Some("4096"), None, None).unwrap().count, MAX_COUNT);
+        assert!(ExamineFormat::new(Some("4097"), None, None).is_err());
+        assert!(ExamineFormat::new(Some("18446744073709551615"), None, Some("g")).is_err());
+    }
+
+    #[test]
+    fn test_format_unit() {
+        let format = |format, size| ExamineFormat { count: 1, format, size };
+
+        assert_eq!(format(UnitFormat::Hex, 1).format_unit(&[0x0a]), "0x0a");
+        assert_eq!(format(UnitFormat::Hex, 4).format_unit(&[0x01, 0x02, 0x00, 0x00]), "0x00000201");
+        assert_eq!(format(UnitFormat::Signed, 2).format_unit(&[0xfe, 0xff]), "-2");
+        assert_eq!(format(UnitFormat::Signed, 8).format_unit(&[0xff; 8]), "-1");
+        assert_eq!(format(UnitFormat::Unsigned, 2).format_unit(&[0xfe, 0xff]), "65534");
+        assert_eq!(format(UnitFormat::Char, 1).format_unit(b"H"), "'H'");
+        assert_eq!(format(UnitFormat::Char, 1).format_unit(b"\n"), "'\\n'");
+    }
+}
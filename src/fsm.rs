@@ -1,108 +1,628 @@
 use anyhow::{bail, Result};
 use pest::iterators::Pairs;
+use pest::Parser;
 use pest_derive::Parser;
 
+use crate::alias::Aliases;
 use crate::commands;
+use crate::condition::{CmpOp, Condition, Operand, Scalar};
 use crate::error::DebuggerError;
-use crate::path::{Path, PostfixOperator, PrefixOperator};
-use crate::session::{DebugSession, SessionState};
+use crate::examine::ExamineFormat;
+use crate::inferiors::Inferiors;
+use crate::loc_finder::VarKind;
+use crate::path::{Cast, Path, PostfixOperator, PrefixOperator};
+use crate::printer::Format;
+use crate::session::DebugSession;
+use crate::session::SessionState;
+use crate::utils::number_parser::{parse_unsigned, ParseError};
 
 #[derive(Parser)]
 #[grammar = "parser.pest"]
 pub struct CommandParser;
 
+/// commands typed after `commands LOC` until `end`
+struct CommandList {
+    loc: String,
+    commands: Vec<String>,
+}
+
 #[allow(clippy::upper_case_acronyms)]
 pub struct FSM<'a, R: gimli::Reader> {
-    session: &'a mut DebugSession<R>,
+    inferiors: Inferiors<'a, R>,
+    aliases: Aliases,
+    command_list: Option<CommandList>,
+    running_commands: bool,
 }
 
 impl<'a, R: gimli::Reader> FSM<'a, R> {
-    pub fn new(debugger: &'a mut DebugSession<R>) -> Self {
-        Self { session: debugger }
+    pub fn new(inferiors: Inferiors<'a, R>, aliases: Aliases) -> Self {
+        Self {
+            inferiors,
+            aliases,
+            command_list: None,
+            running_commands: false,
+        }
+    }
+
+    pub fn expand_alias(&self, line: &str) -> String {
+        self.aliases.expand(line)
+    }
+
+    /// stops all inferiors, the debugger exits after it
+    pub fn quit(&mut self) -> Result<()> {
+        self.inferiors.stop_all()
+    }
+
+    pub fn is_recording_commands(&self) -> bool {
+        self.command_list.is_some()
+    }
+
+    /// adds line to the command list of a breakpoint, `end` attaches the list to the breakpoint
+    pub fn record_command(&mut self, line: &str) -> Result<()> {
+        if line == "end" {
+            if let Some(CommandList { loc, commands }) = self.command_list.take() {
+                commands::breakpoints::set_commands(self.inferiors.current(), &loc, commands)?;
+            }
+            return Ok(());
+        }
+
+        // commands are checked now, so the list doesn't break when the breakpoint is hit
+        let pairs = CommandParser::parse(Rule::command, line).map_err(|_| DebuggerError::InvalidCommand)?;
+        if Self::get_rule(&pairs) == Rule::breakpoint_commands {
+            bail!(DebuggerError::InvalidCommand);
+        }
+
+        if let Some(command_list) = self.command_list.as_mut() {
+            command_list.commands.push(line.to_string());
+        }
+
+        Ok(())
     }
 
     pub fn handle(&mut self, mut pairs: Pairs<Rule>) -> Result<bool> {
         let pair = pairs.next().unwrap().into_inner().next().unwrap();
         let rule = pair.as_rule();
 
-        match self.session.get_state() {
+        // aliases don't depend on session state
+        match rule {
+            Rule::alias => {
+                let mut inner_pairs = pair.into_inner();
+                match (inner_pairs.next(), inner_pairs.next()) {
+                    (Some(name), Some(command)) => commands::alias::add(&mut self.aliases, name.as_str(), command.as_str())?,
+                    _ => commands::alias::list(&self.aliases),
+                }
+                return Ok(false);
+            }
+            Rule::unalias => {
+                commands::alias::remove(&mut self.aliases, pair.into_inner().next().unwrap().as_str())?;
+                return Ok(false);
+            }
+            Rule::info_inferiors => {
+                commands::inferior::list(&self.inferiors);
+                return Ok(false);
+            }
+            Rule::add_inferior => {
+                let args = pair.into_inner().map(|pair| pair.as_str()).collect::<Vec<_>>();
+                commands::inferior::add(&mut self.inferiors, args[0], &args[1..])?;
+                return Ok(false);
+            }
+            Rule::inferior => {
+                commands::inferior::select(&mut self.inferiors, pair.into_inner().next().unwrap().as_str())?;
+                return Ok(false);
+            }
+            Rule::quit => {
+                self.quit()?;
+                return Ok(true);
+            }
+            _ => (),
+        }
+
+        let session = self.inferiors.current();
+
+        match session.get_state() {
             SessionState::Started => match rule {
-                Rule::run => commands::control::run(self.session)?,
-                Rule::add_breakpoint => commands::breakpoints::add(self.session, pair.into_inner().next().unwrap().as_str())?,
-                Rule::remove_breakpoint => commands::breakpoints::remove(self.session, pair.into_inner().next().unwrap().as_str())?,
-                Rule::list_breakpoints => commands::breakpoints::list(self.session)?,
-                Rule::enable_breakpoint => commands::breakpoints::enable(self.session, pair.into_inner().next().unwrap().as_str())?,
-                Rule::disable_breakpoint => commands::breakpoints::disable(self.session, pair.into_inner().next().unwrap().as_str())?,
-                Rule::clear_breakpoints => commands::breakpoints::clear(self.session)?,
-                Rule::quit => commands::control::stop(self.session)?,
+                Rule::run => commands::control::run(session)?,
+                Rule::run_to_exit => commands::control::run_to_exit(session)?,
+                Rule::add_temporary_breakpoint => commands::breakpoints::tadd(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::add_logpoint => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (loc, expr) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::breakpoints::log(session, loc, expr)?
+                }
+                Rule::add_breakpoint => {
+                    let mut inner_pairs = pair.into_inner();
+                    let loc = inner_pairs.next().unwrap().as_str();
+                    commands::breakpoints::add(session, loc, inner_pairs.next().map(|pair| pair.as_str()))?
+                }
+                Rule::remove_breakpoint => commands::breakpoints::remove(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::list_breakpoints => commands::breakpoints::list(session)?,
+                Rule::enable_breakpoint => commands::breakpoints::enable(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::disable_breakpoint => commands::breakpoints::disable(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::clear_breakpoints => commands::breakpoints::clear(session)?,
+                Rule::save_breakpoints => commands::breakpoints::save(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::source_breakpoints => commands::breakpoints::source(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::breakpoint_commands => {
+                    let loc = pair.into_inner().next().unwrap().as_str();
+                    commands::breakpoints::start_commands(session, loc)?;
+                    self.command_list = Some(CommandList {
+                        loc: loc.to_string(),
+                        commands: Vec::new(),
+                    });
+                }
+                Rule::info_line_table => commands::info::line_table(session, pair.into_inner().next().map(|pair| pair.as_str()))?,
+                Rule::info_scope => commands::info::scope(session, pair.into_inner().next().map(|pair| pair.as_str()))?,
+                Rule::info_display => commands::display::list(session),
+                Rule::display => {
+                    // shown once the program stops
+                    if let Some(pair) = pair.into_inner().next() {
+                        commands::display::add(session, pair.as_str());
+                    }
+                }
+                Rule::undisplay => commands::display::remove(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_follow_fork_mode => commands::control::set_follow_fork_mode(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_disable_randomization => commands::control::set_disable_randomization(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_main_function => commands::control::set_main_function(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_print => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (setting, value) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::var::set_print_setting(session, setting, value)?
+                }
+                Rule::set_radix => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (setting, value) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::var::set_radix(session, setting, value)?
+                }
+                Rule::set_summary_format => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (type_name, format) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::var::set_summary_format(session, type_name, format)?
+                }
+                Rule::set_stop_context => commands::control::set_stop_context(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_substitute_path => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (from, to) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::control::add_substitute_path(session, from, to)?
+                }
+                Rule::restart => commands::control::restart(session)?,
                 Rule::help => commands::help::help(),
+                _ if Self::requires_running(rule) => bail!(DebuggerError::NotRunning),
                 _ => bail!(DebuggerError::InvalidCommand),
             },
             SessionState::Running => match rule {
-                Rule::stop | Rule::quit => commands::control::stop(self.session)?,
-                Rule::add_breakpoint => commands::breakpoints::add(self.session, pair.into_inner().next().unwrap().as_str())?,
-                Rule::remove_breakpoint => commands::breakpoints::remove(self.session, pair.into_inner().next().unwrap().as_str())?,
-                Rule::list_breakpoints => commands::breakpoints::list(self.session)?,
-                Rule::enable_breakpoint => commands::breakpoints::enable(self.session, pair.into_inner().next().unwrap().as_str())?,
-                Rule::disable_breakpoint => commands::breakpoints::disable(self.session, pair.into_inner().next().unwrap().as_str())?,
-                Rule::clear_breakpoints => commands::breakpoints::clear(self.session)?,
-                Rule::r#continue => commands::control::cont(self.session)?,
-                Rule::step => commands::control::step(self.session)?,
-                Rule::step_in => commands::control::step_in(self.session)?,
-                Rule::step_out => commands::control::step_out(self.session)?,
+                Rule::stop => commands::control::stop(session)?,
+                Rule::kill => commands::control::kill(session)?,
+                Rule::detach => commands::control::detach(session)?,
+                Rule::checkpoint => commands::checkpoint::save(session)?,
+                Rule::restart_checkpoint => commands::checkpoint::restore(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::add_temporary_breakpoint => commands::breakpoints::tadd(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::add_logpoint => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (loc, expr) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::breakpoints::log(session, loc, expr)?
+                }
+                Rule::add_breakpoint => {
+                    let mut inner_pairs = pair.into_inner();
+                    let loc = inner_pairs.next().unwrap().as_str();
+                    commands::breakpoints::add(session, loc, inner_pairs.next().map(|pair| pair.as_str()))?
+                }
+                Rule::remove_breakpoint => commands::breakpoints::remove(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::list_breakpoints => commands::breakpoints::list(session)?,
+                Rule::enable_breakpoint => commands::breakpoints::enable(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::disable_breakpoint => commands::breakpoints::disable(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::clear_breakpoints => commands::breakpoints::clear(session)?,
+                Rule::save_breakpoints => commands::breakpoints::save(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::source_breakpoints => commands::breakpoints::source(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::breakpoint_commands => {
+                    let loc = pair.into_inner().next().unwrap().as_str();
+                    commands::breakpoints::start_commands(session, loc)?;
+                    self.command_list = Some(CommandList {
+                        loc: loc.to_string(),
+                        commands: Vec::new(),
+                    });
+                }
+                Rule::r#continue => commands::control::cont(session)?,
+                Rule::run_to_exit => commands::control::run_to_exit(session)?,
+                Rule::reverse_continue => commands::control::reverse_continue(session)?,
+                Rule::step => commands::control::step(session, pair.into_inner().next().map(|pair| pair.as_str()))?,
+                Rule::step_in => commands::control::step_in(session)?,
+                Rule::step_out => commands::control::step_out(session)?,
+                Rule::finish => commands::control::finish(session)?,
+                Rule::call => {
+                    // call is printed as it's typed
+                    let expr = pair.as_str().trim_start_matches("call").trim_start();
+                    let mut inner_pairs = pair.into_inner();
+                    let func = inner_pairs.next().unwrap().as_str();
+                    let args = inner_pairs.map(parse_operand).collect::<Result<Vec<_>>>()?;
+                    commands::control::call(session, expr, func, &args)?
+                }
+                Rule::advance => commands::control::advance(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::until => commands::control::until(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::watch_change => {
+                    let pair = pair.into_inner().next().unwrap();
+                    commands::watch::add(session, pair.as_str(), &parse_path(pair)?)?
+                }
+                Rule::unwatch => commands::watch::remove(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::watch => {
+                    let pair = pair.into_inner().next().unwrap();
+                    commands::watch::watch(session, pair.as_str(), &parse_condition(pair)?)?
+                }
                 Rule::print_var => {
-                    let path = pair.into_inner().next().map(|pair| Self::parse_path(pair)).transpose()?;
-                    commands::var::print_var(self.session, path.as_ref())?
+                    let mut format = None;
+                    let mut path = None;
+                    for pair in pair.into_inner() {
+                        match pair.as_rule() {
+                            Rule::print_format => format = Some(Format::try_from(pair.into_inner().next().unwrap().as_str())?),
+                            _ => path = Some(parse_path(pair)?),
+                        }
+                    }
+
+                    match format {
+                        Some(format) => commands::var::print_formatted_var(session, path.as_ref(), format)?,
+                        None => commands::var::print_var(session, path.as_ref())?,
+                    }
+                }
+                Rule::examine => {
+                    let mut format = ExamineFormat::default();
+                    let mut address = None;
+                    let mut path = None;
+                    for pair in pair.into_inner() {
+                        match pair.as_rule() {
+                            Rule::examine_format => {
+                                let (mut count, mut unit_format, mut size) = (None, None, None);
+                                for pair in pair.into_inner() {
+                                    match pair.as_rule() {
+                                        Rule::examine_count => count = Some(pair.as_str()),
+                                        Rule::examine_unit_format => unit_format = Some(pair.as_str()),
+                                        _ => size = Some(pair.as_str()),
+                                    }
+                                }
+                                format = ExamineFormat::new(count, unit_format, size)?;
+                            }
+                            Rule::examine_address => address = Some(parse_unsigned(pair.as_str()).map_err(|_| DebuggerError::InvalidValue)?),
+                            _ => path = Some(parse_path(pair)?),
+                        }
+                    }
+
+                    commands::examine::examine(session, address, path.as_ref(), format)?
+                }
+                Rule::disassemble => commands::disassemble::disassemble(session, pair.into_inner().next().map(|pair| pair.as_str()))?,
+                Rule::ptype => {
+                    let mut offsets = false;
+                    let mut path = None;
+                    for pair in pair.into_inner() {
+                        match pair.as_rule() {
+                            Rule::ptype_offsets => offsets = true,
+                            _ => path = Some(parse_path(pair)?),
+                        }
+                    }
+                    commands::var::print_type(session, &path.unwrap(), offsets)?
                 }
+                Rule::whatis => commands::var::print_type_name(session, &parse_path(pair.into_inner().next().unwrap())?)?,
                 Rule::set_var => {
                     let mut inner_pairs = pair.into_inner();
-                    let path = Self::parse_path(inner_pairs.next().unwrap())?;
-                    commands::var::set_var(self.session, &path, inner_pairs.next().unwrap().as_str())?
+                    let path = parse_path(inner_pairs.next().unwrap())?;
+                    let value = inner_pairs.next().unwrap();
+                    match value.as_rule() {
+                        Rule::path => commands::var::set_var_from_path(session, &path, &parse_path(value)?)?,
+                        _ => commands::var::set_var(session, &path, value.as_str())?,
+                    }
                 }
-                Rule::location => commands::control::location(self.session)?,
+                Rule::location => commands::control::location(session)?,
+                Rule::frame => commands::control::frame(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::backtrace => commands::control::backtrace(session)?,
+                Rule::up => commands::control::up(session, pair.into_inner().next().map(|pair| pair.as_str()))?,
+                Rule::down => commands::control::down(session, pair.into_inner().next().map(|pair| pair.as_str()))?,
+                Rule::info_line_table => commands::info::line_table(session, pair.into_inner().next().map(|pair| pair.as_str()))?,
+                Rule::info_scope => commands::info::scope(session, pair.into_inner().next().map(|pair| pair.as_str()))?,
+                Rule::info_all_registers => commands::info::all_registers(session)?,
+                Rule::info_registers => commands::info::registers(session)?,
+                Rule::info_proc_mappings => commands::info::proc_mappings(session)?,
+                Rule::info_args => commands::info::vars(session, VarKind::Arg)?,
+                Rule::info_locals => commands::info::vars(session, VarKind::Local)?,
+                Rule::info_globals => commands::info::vars(session, VarKind::Global)?,
+                Rule::info_display => commands::display::list(session),
+                Rule::display => match pair.into_inner().next() {
+                    Some(pair) => {
+                        let id = commands::display::add(session, pair.as_str());
+                        Self::show_displays(session, Some(id))?
+                    }
+                    None => Self::show_displays(session, None)?,
+                },
+                Rule::undisplay => commands::display::remove(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_follow_fork_mode => commands::control::set_follow_fork_mode(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_disable_randomization => commands::control::set_disable_randomization(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_main_function => commands::control::set_main_function(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_print => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (setting, value) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::var::set_print_setting(session, setting, value)?
+                }
+                Rule::set_radix => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (setting, value) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::var::set_radix(session, setting, value)?
+                }
+                Rule::set_summary_format => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (type_name, format) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::var::set_summary_format(session, type_name, format)?
+                }
+                Rule::set_stop_context => commands::control::set_stop_context(session, pair.into_inner().next().unwrap().as_str())?,
+                Rule::set_substitute_path => {
+                    let mut inner_pairs = pair.into_inner();
+                    let (from, to) = (inner_pairs.next().unwrap().as_str(), inner_pairs.next().unwrap().as_str());
+                    commands::control::add_substitute_path(session, from, to)?
+                }
+                Rule::restart => commands::control::restart(session)?,
                 Rule::help => commands::help::help(),
                 _ => bail!(DebuggerError::InvalidCommand),
             },
             SessionState::Exited => match rule {
-                Rule::quit => (),
+                Rule::restart => commands::control::restart(session)?,
                 Rule::help => commands::help::help(),
+                _ if Self::requires_running(rule) => bail!(DebuggerError::NotRunning),
                 _ => bail!(DebuggerError::InvalidCommand),
             },
         }
 
-        Ok(rule == Rule::quit)
+        if Self::resumes_program(rule) {
+            Self::show_displays(session, None)?;
+        }
+
+        self.handle_exec()?;
+
+        if Self::resumes_program(rule) {
+            return self.run_breakpoint_commands();
+        }
+
+        Ok(false)
+    }
+
+    fn get_rule(pairs: &Pairs<Rule>) -> Rule {
+        pairs.clone().next().unwrap().into_inner().next().unwrap().as_rule()
     }
 
-    fn parse_path(pair: pest::iterators::Pair<'_, Rule>) -> Result<Path<'_>> {
-        if pair.as_rule() != Rule::path {
-            bail!(DebuggerError::InvalidPath);
+    /// commands of the hit breakpoint are handled as if they were typed. A command resuming the program ends the list,
+    /// the next stop runs commands of its breakpoint (nested lists aren't started, so logging in a loop doesn't recurse)
+    fn run_breakpoint_commands(&mut self) -> Result<bool> {
+        if self.running_commands {
+            return Ok(false);
         }
 
-        let mut path = Path::default();
-        let mut pairs = pair.into_inner();
+        self.running_commands = true;
+        let result = self.run_hit_breakpoint_commands();
+        self.running_commands = false;
+
+        result
+    }
+
+    fn run_hit_breakpoint_commands(&mut self) -> Result<bool> {
+        loop {
+            let session = self.inferiors.current();
+            if session.get_state() != SessionState::Running {
+                return Ok(false);
+            }
+
+            let commands = match session.get_current_breakpoint()? {
+                Some(breakpoint) if !breakpoint.commands.is_empty() => breakpoint.commands.clone(),
+                _ => return Ok(false),
+            };
+
+            let mut resumed = false;
+            for command in &commands {
+                let pairs = CommandParser::parse(Rule::command, command)?;
+                let rule = Self::get_rule(&pairs);
+
+                // failed command stops the list, like a typed command would stop the user
+                match self.handle(pairs) {
+                    Ok(true) => return Ok(true),
+                    Ok(false) => (),
+                    Err(e) if e.is::<DebuggerError>() => {
+                        eprintln!("{}", e);
+                        return Ok(false);
+                    }
+                    Err(e) => return Err(e),
+                }
 
-        for pair in pairs.by_ref() {
-            match pair.as_rule() {
-                Rule::operator => path.prefix_operators.push(PrefixOperator::try_from(pair.as_str())?),
-                Rule::name => {
-                    path.name = pair.as_str();
+                if Self::resumes_program(rule) {
+                    resumed = true;
                     break;
                 }
-                _ => bail!(DebuggerError::InvalidPath),
+            }
+
+            if !resumed {
+                return Ok(false);
             }
         }
+    }
 
-        for pair in pairs {
-            match pair.as_rule() {
-                Rule::name => path.postfix_operators.push(PostfixOperator::Field(pair.as_str())),
-                Rule::array_index => {
-                    let index = pair.into_inner().next().unwrap().as_str().parse::<usize>()?;
-                    path.postfix_operators.push(PostfixOperator::Index(index));
-                }
-                _ => bail!(DebuggerError::InvalidPath),
+    fn resumes_program(rule: Rule) -> bool {
+        matches!(
+            rule,
+            Rule::run
+                | Rule::run_to_exit
+                | Rule::r#continue
+                | Rule::reverse_continue
+                | Rule::step
+                | Rule::step_in
+                | Rule::step_out
+                | Rule::finish
+                | Rule::advance
+                | Rule::until
+                | Rule::watch
+        )
+    }
+
+    /// displays are stored as text and parsed again in the current scope, `id` picks a single display
+    fn show_displays(session: &DebugSession<R>, id: Option<usize>) -> Result<()> {
+        if session.get_state() != SessionState::Running {
+            return Ok(());
+        }
+
+        for display in session.get_displays().iter().filter(|display| id.is_none_or(|id| display.id == id)) {
+            let pair = CommandParser::parse(Rule::path, &display.expr)?.next().unwrap();
+            commands::display::show(session, display, &parse_path(pair)?);
+        }
+
+        Ok(())
+    }
+
+    fn requires_running(rule: Rule) -> bool {
+        matches!(
+            rule,
+            Rule::stop
+                | Rule::kill
+                | Rule::detach
+                | Rule::checkpoint
+                | Rule::restart_checkpoint
+                | Rule::r#continue
+                | Rule::reverse_continue
+                | Rule::step
+                | Rule::step_in
+                | Rule::step_out
+                | Rule::finish
+                | Rule::call
+                | Rule::advance
+                | Rule::until
+                | Rule::watch
+                | Rule::watch_change
+                | Rule::unwatch
+                | Rule::print_var
+                | Rule::examine
+                | Rule::disassemble
+                | Rule::ptype
+                | Rule::whatis
+                | Rule::set_var
+                | Rule::location
+                | Rule::frame
+                | Rule::backtrace
+                | Rule::up
+                | Rule::down
+                | Rule::info_all_registers
+                | Rule::info_registers
+                | Rule::info_proc_mappings
+                | Rule::info_args
+                | Rule::info_locals
+                | Rule::info_globals
+                | Rule::display
+        )
+    }
+
+    /// child could execute new program, continue with its image
+    fn handle_exec(&mut self) -> Result<()> {
+        while let Some(path) = self.inferiors.reload_current()? {
+            let session = self.inferiors.current();
+            if session.get_state() == SessionState::Running {
+                println!("executing new program {}", path.display());
+                commands::control::cont(session)?;
             }
         }
 
-        Ok(path)
+        Ok(())
+    }
+}
+
+pub fn parse_path(pair: pest::iterators::Pair<'_, Rule>) -> Result<Path<'_>> {
+    if pair.as_rule() != Rule::path {
+        bail!(DebuggerError::InvalidPath);
+    }
+
+    let mut path = Path::default();
+    let mut pairs = pair.into_inner();
+
+    for pair in pairs.by_ref() {
+        match pair.as_rule() {
+            Rule::operator => path.prefix_operators.push(PrefixOperator::try_from(pair.as_str())?),
+            Rule::cast => {
+                path.cast = Some(Cast {
+                    type_name: pair.clone().into_inner().next().unwrap().as_str(),
+                    pointers: pair.as_str().matches('*').count(),
+                    position: path.prefix_operators.len(),
+                })
+            }
+            Rule::cast_address => {
+                path.name = pair.as_str();
+                path.address = Some(parse_unsigned(pair.as_str()).map_err(|_| DebuggerError::InvalidPath)?);
+                break;
+            }
+            Rule::scope => path.scope = Some(pair.as_str()),
+            Rule::name => {
+                path.name = pair.as_str();
+                break;
+            }
+            _ => bail!(DebuggerError::InvalidPath),
+        }
+    }
+
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::name => path.postfix_operators.push(PostfixOperator::Field(pair.as_str())),
+            Rule::array_index => {
+                let index = parse_index(pair.into_inner().next().unwrap().as_str())?;
+                path.postfix_operators.push(PostfixOperator::Index(index));
+            }
+            Rule::array_slice => {
+                let mut inner_pairs = pair.into_inner();
+                let start = parse_index(inner_pairs.next().unwrap().as_str())?;
+                let end = parse_index(inner_pairs.next().unwrap().as_str())?;
+                path.postfix_operators.push(PostfixOperator::Slice(start, end));
+            }
+            _ => bail!(DebuggerError::InvalidPath),
+        }
+    }
+
+    Ok(path)
+}
+
+/// condition of `watch` and conditional breakpoints
+pub fn parse_condition(pair: pest::iterators::Pair<'_, Rule>) -> Result<Condition<'_>> {
+    let mut inner_pairs = pair.into_inner();
+    let lhs = Operand::Path(parse_path(inner_pairs.next().unwrap())?);
+    let rhs = match (inner_pairs.next(), inner_pairs.next()) {
+        (Some(op), Some(operand)) => Some((CmpOp::try_from(op.as_str())?, parse_operand(operand)?)),
+        _ => None,
+    };
+
+    Ok(Condition { lhs, rhs })
+}
+
+fn parse_operand(pair: pest::iterators::Pair<'_, Rule>) -> Result<Operand<'_>> {
+    match pair.as_rule() {
+        Rule::number => Ok(Operand::Scalar(Scalar::parse(pair.as_str())?)),
+        Rule::boolean => Ok(Operand::Scalar(Scalar::Int((pair.as_str() == "true") as i128))),
+        _ => Ok(Operand::Path(parse_path(pair)?)),
+    }
+}
+
+fn parse_index(index: &str) -> Result<usize> {
+    match parse_unsigned(index) {
+        Ok(index) => Ok(usize::try_from(index).map_err(|_| DebuggerError::InvalidPath)?),
+        Err(ParseError::NegativeNumber) => bail!(DebuggerError::NegativeNumber),
+        Err(ParseError::InvalidNumber) => bail!(DebuggerError::InvalidPath),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// rules of the parsed command arguments
+    fn parse_args(line: &str) -> Vec<(Rule, &str)> {
+        let command = CommandParser::parse(Rule::command, line).unwrap().next().unwrap().into_inner().next().unwrap();
+        command.into_inner().map(|pair| (pair.as_rule(), pair.as_str())).collect()
+    }
+
+    #[test]
+    fn test_parse_keyword_prefixed_names() {
+        assert_eq!(parse_args("print nullable"), [(Rule::path, "nullable")]);
+        assert_eq!(parse_args("set x = nullable"), [(Rule::path, "x"), (Rule::path, "nullable")]);
+        assert_eq!(parse_args("set x = true_count"), [(Rule::path, "x"), (Rule::path, "true_count")]);
+        assert_eq!(parse_args("set x = falsey"), [(Rule::path, "x"), (Rule::path, "falsey")]);
+        assert_eq!(parse_args("set x = null"), [(Rule::path, "x"), (Rule::null, "null")]);
+        assert_eq!(parse_args("set x = true"), [(Rule::path, "x"), (Rule::boolean, "true")]);
+        assert_eq!(
+            parse_args("call f(true, true_count)"),
+            [(Rule::name, "f"), (Rule::boolean, "true"), (Rule::path, "true_count")]
+        );
     }
 }
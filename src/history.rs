@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::consts::{ALIASES_SECTION, HISTORY_FILE_NAME};
+
+/// history dotfile keeps entered commands followed by the aliases section, each part is saved keeping the other one
+pub fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE_NAME))
+}
+
+/// commands and lines of the aliases section, there is no file on the first run
+pub fn read(path: &Path) -> (Vec<String>, Vec<String>) {
+    let Ok(content) = fs::read_to_string(path) else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let mut lines = content.lines().filter(|line| !line.is_empty());
+    // commands don't start with #, skip headers like the one of rustyline history file
+    let commands = lines
+        .by_ref()
+        .take_while(|&line| line != ALIASES_SECTION)
+        .filter(|line| !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+
+    (commands, lines.map(String::from).collect())
+}
+
+pub fn save_commands(path: &Path, commands: impl Iterator<Item = impl AsRef<str>>) -> Result<()> {
+    let (_, aliases) = read(path);
+    write(path, commands, aliases.iter())
+}
+
+pub fn save_aliases(path: &Path, aliases: impl Iterator<Item = impl AsRef<str>>) -> Result<()> {
+    let (commands, _) = read(path);
+    write(path, commands.iter(), aliases)
+}
+
+fn write(path: &Path, commands: impl Iterator<Item = impl AsRef<str>>, aliases: impl Iterator<Item = impl AsRef<str>>) -> Result<()> {
+    let mut content = String::new();
+    for command in commands {
+        content.push_str(command.as_ref());
+        content.push('\n');
+    }
+
+    let mut aliases = aliases.peekable();
+    if aliases.peek().is_some() {
+        content.push_str(ALIASES_SECTION);
+        content.push('\n');
+        for alias in aliases {
+            content.push_str(alias.as_ref());
+            content.push('\n');
+        }
+    }
+
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        let path = std::env::temp_dir().join(format!("dbg_history_test_{}", std::process::id()));
+        fs::write(&path, "#V2\nb 5\nr\n").unwrap();
+
+        save_aliases(&path, ["alias bt = backtrace"].iter()).unwrap();
+        save_commands(&path, ["b 5", "r", "bt"].iter()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "b 5\nr\nbt\n#aliases\nalias bt = backtrace\n");
+        assert_eq!(
+            read(&path),
+            (
+                vec!["b 5".to_string(), "r".to_string(), "bt".to_string()],
+                vec!["alias bt = backtrace".to_string()]
+            )
+        );
+
+        save_aliases(&path, std::iter::empty::<&str>()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "b 5\nr\nbt\n");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
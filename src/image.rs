@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use nix::unistd::Pid;
+
+use crate::procmaps;
+use crate::unwinder::Unwinder;
+
+/// debug info of executable file, process gets new image on exec
+pub struct Image<R: gimli::Reader> {
+    pub path: PathBuf,
+    pub dwarf: gimli::Dwarf<R>,
+    pub unwinder: Unwinder<R>,
+    pub is_dynamic: bool,
+    pub link_address: u64,
+    pub entry_address: u64, // link-time address of the first instruction
+}
+
+impl<R: gimli::Reader> Image<R> {
+    pub fn new(path: PathBuf, dwarf: gimli::Dwarf<R>, unwinder: Unwinder<R>, is_dynamic: bool, link_address: u64, entry_address: u64) -> Self {
+        Self {
+            path,
+            dwarf,
+            unwinder,
+            is_dynamic,
+            link_address,
+            entry_address,
+        }
+    }
+
+    pub fn get_base_address(&self, pid: Pid) -> Result<u64> {
+        if !self.is_dynamic {
+            return Ok(0);
+        }
+
+        let path = fs::canonicalize(&self.path)?;
+
+        // lowest mapping of the program file is its first segment
+        let load_address = procmaps::read(pid)?
+            .into_iter()
+            .find(|mapping| mapping.pathname.as_ref().is_some_and(|pathname| Path::new(pathname) == path))
+            .map(|mapping| mapping.start)
+            .ok_or(anyhow!("invalid proc maps"))?;
+
+        // addresses in debug info are link-time addresses, so base address is the difference between load and link address of the first segment
+        Ok(load_address - self.link_address)
+    }
+}
@@ -0,0 +1,85 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::error::DebuggerError;
+use crate::image::Image;
+use crate::launcher::Launcher;
+use crate::session::{DebugSession, SessionState};
+
+type Loader<'a, R> = Box<dyn Fn(&Path) -> Result<Image<R>> + 'a>;
+
+/// debugged programs, commands are applied to the selected one
+pub struct Inferiors<'a, R: gimli::Reader> {
+    sessions: Vec<DebugSession<R>>,
+    current: usize,
+    load: Loader<'a, R>,
+}
+
+impl<'a, R: gimli::Reader> Inferiors<'a, R> {
+    pub fn new(session: DebugSession<R>, load: Loader<'a, R>) -> Self {
+        Self {
+            sessions: vec![session],
+            current: 0,
+            load,
+        }
+    }
+
+    pub fn current(&mut self) -> &mut DebugSession<R> {
+        &mut self.sessions[self.current]
+    }
+
+    /// starts new program, returns inferior id
+    pub fn add(&mut self, prog: &str, args: &[&str]) -> Result<usize> {
+        let launcher = Launcher::new(PathBuf::from(prog), args.iter().map(OsString::from).collect());
+        let session = (self.load)(Path::new(prog))
+            .and_then(|image| DebugSession::start(launcher, image))
+            .map_err(|e| {
+                log::debug!("can't start {}: {}", prog, e);
+                DebuggerError::InvalidProgram
+            })?;
+        self.sessions.push(session);
+
+        Ok(self.sessions.len())
+    }
+
+    /// loads program executed by the current inferior, returns its path
+    pub fn reload_current(&mut self) -> Result<Option<PathBuf>> {
+        let session = &mut self.sessions[self.current];
+
+        match session.take_exec_path() {
+            Some(path) => {
+                session.reload((self.load)(&path)?)?;
+                Ok(Some(path))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn select(&mut self, id: usize) -> Result<()> {
+        match id.checked_sub(1).filter(|&index| index < self.sessions.len()) {
+            Some(index) => {
+                self.current = index;
+                Ok(())
+            }
+            None => Err(DebuggerError::InferiorNotFound.into()),
+        }
+    }
+
+    /// iterates over (id, session, is current)
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &DebugSession<R>, bool)> {
+        self.sessions
+            .iter()
+            .enumerate()
+            .map(|(index, session)| (index + 1, session, index == self.current))
+    }
+
+    pub fn stop_all(&mut self) -> Result<()> {
+        for session in self.sessions.iter_mut().filter(|session| session.get_state() != SessionState::Exited) {
+            session.stop()?;
+        }
+
+        Ok(())
+    }
+}
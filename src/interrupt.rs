@@ -0,0 +1,30 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle_sigint(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+/// remembers ctrl-c instead of being killed, so long operations can stop cleanly
+pub fn catch() -> Result<()> {
+    INTERRUPTED.store(false, Ordering::Relaxed);
+    let action = SigAction::new(SigHandler::Handler(handle_sigint), SaFlags::empty(), SigSet::empty());
+    unsafe { signal::sigaction(Signal::SIGINT, &action)? };
+    Ok(())
+}
+
+/// ctrl-c kills us again, remembered ctrl-c is forgotten so it doesn't cancel later operations
+pub fn release() -> Result<()> {
+    INTERRUPTED.store(false, Ordering::Relaxed);
+    let action = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+    unsafe { signal::sigaction(Signal::SIGINT, &action)? };
+    Ok(())
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
@@ -0,0 +1,99 @@
+use std::cell::Cell;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use anyhow::Result;
+use nix::sys::personality::{self, Persona};
+use nix::sys::{ptrace, wait};
+use nix::unistd::Pid;
+
+use crate::error::DebuggerError;
+
+/// spawns traced program, so it can be started again after kill or exit
+pub struct Launcher {
+    prog: PathBuf,
+    args: Vec<OsString>,
+    disable_randomization: Cell<bool>, // addresses are the same on every run
+    stdin: Option<PathBuf>,
+    env: Vec<(OsString, OsString)>,
+    clear_env: bool,
+}
+
+impl Launcher {
+    pub fn new(prog: PathBuf, args: Vec<OsString>) -> Self {
+        Self {
+            prog,
+            args,
+            disable_randomization: Cell::new(true),
+            stdin: None,
+            env: Vec::new(),
+            clear_env: false,
+        }
+    }
+
+    /// file is opened on every spawn, so the program reads it from the start after restart
+    pub fn with_stdin(mut self, stdin: Option<PathBuf>) -> Self {
+        self.stdin = stdin;
+        self
+    }
+
+    /// vars are added to the debugger environment, or replace it when it's cleared
+    pub fn with_env(mut self, env: Vec<(OsString, OsString)>, clear_env: bool) -> Self {
+        self.env = env;
+        self.clear_env = clear_env;
+        self
+    }
+
+    pub fn get_prog(&self) -> &Path {
+        &self.prog
+    }
+
+    /// applied to the next spawned child
+    pub fn set_disable_randomization(&self, disable_randomization: bool) {
+        self.disable_randomization.set(disable_randomization);
+    }
+
+    /// spawns the program stopped before its first instruction
+    pub fn spawn(&self) -> Result<process::Child> {
+        let mut command = process::Command::new(&self.prog);
+        let disable_randomization = self.disable_randomization.get();
+
+        if let Some(path) = self.stdin.as_ref() {
+            let file = fs::File::open(path).map_err(|source| DebuggerError::FileAccess {
+                path: path.display().to_string(),
+                source,
+            })?;
+            command.stdin(file);
+        }
+
+        if self.clear_env {
+            command.env_clear();
+        }
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+
+        unsafe {
+            command.pre_exec(move || {
+                if disable_randomization {
+                    personality::set(personality::get()? | Persona::ADDR_NO_RANDOMIZE)?;
+                }
+                ptrace::traceme()?;
+                Ok(())
+            });
+        }
+
+        let child = command.args(&self.args).spawn()?;
+        let pid = Pid::from_raw(child.id() as libc::pid_t);
+        wait::waitpid(pid, None)?;
+
+        // report forks and execs, so we could follow them
+        ptrace::setoptions(
+            pid,
+            ptrace::Options::PTRACE_O_TRACEFORK | ptrace::Options::PTRACE_O_TRACEVFORK | ptrace::Options::PTRACE_O_TRACEEXEC,
+        )?;
+
+        Ok(child)
+    }
+}
@@ -1,9 +1,14 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use crate::consts::{FUNC_EPILOGUE_SIZE, FUNC_PROLOGUE_SIZE, MAIN_FUNC_NAME};
+use crate::consts::{FUNC_EPILOGUE_SIZE, MAIN_FUNC_NAME};
+use crate::error::DebuggerError;
 use crate::types::TypeId;
 use crate::utils::ranges::Ranges;
 
@@ -19,32 +24,91 @@ impl<Offset: gimli::ReaderOffset> EntryRef<Offset> {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// inclusive address ranges, lexical block could be split in several of them
+pub type PcRanges = Rc<[(u64, u64)]>;
+
+#[derive(Debug, Clone)]
 pub struct VarRef<Offset: gimli::ReaderOffset> {
     pub entry_ref: EntryRef<Offset>,
     pub type_id: TypeId,
+    pub is_arg: bool,                // parameter of the function, not a local variable
+    pub pc_ranges: Option<PcRanges>, // inclusive ranges of the lexical block declaring the variable, whole function otherwise
 }
 
 impl<Offset: gimli::ReaderOffset> VarRef<Offset> {
-    pub fn new(entry_ref: EntryRef<Offset>, type_id: TypeId) -> Self {
-        Self { entry_ref, type_id }
+    pub fn new(entry_ref: EntryRef<Offset>, type_id: TypeId, is_arg: bool, pc_ranges: Option<PcRanges>) -> Self {
+        Self {
+            entry_ref,
+            type_id,
+            is_arg,
+            pc_ranges,
+        }
+    }
+
+    /// block with DW_AT_ranges could have gaps, which belong to other blocks
+    fn is_visible_at(&self, pc: u64) -> bool {
+        self.pc_ranges
+            .as_ref()
+            .is_none_or(|pc_ranges| pc_ranges.iter().any(|&(low_pc, high_pc)| (low_pc..=high_pc).contains(&pc)))
+    }
+
+    /// variable of the innermost block shadows the outer ones
+    fn block_size(&self) -> u64 {
+        self.pc_ranges
+            .as_ref()
+            .map_or(u64::MAX, |pc_ranges| pc_ranges.iter().map(|&(low_pc, high_pc)| high_pc - low_pc).sum())
     }
 }
 
+/// where variable is declared
+#[derive(Debug, Clone)]
+pub enum VarScope {
+    Func(Rc<str>),
+    Unit(Rc<str>),
+}
+
+/// variables listed by `info args`, `info locals` and `info globals`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VarKind {
+    Arg,
+    Local,
+    Global,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineRowKind {
+    Line,
+    Prologue,
+    Epilogue,
+}
+
+#[derive(Debug, Clone)]
+pub struct LineRow {
+    pub address: u64,
+    pub fileline: Rc<str>,
+    pub kind: LineRowKind,
+}
+
 #[allow(clippy::type_complexity)]
 #[derive(Debug)]
 pub struct LocFinder<R: gimli::Reader> {
     // todo string table
     base_address: u64,
-    locations: HashMap<Rc<str>, u64>,  // location -> address
-    addr2line: HashMap<u64, Rc<str>>,  // address -> line
-    lines: HashMap<Rc<str>, Vec<u64>>, // filepath -> { line: address }
+    locations: HashMap<Rc<str>, u64>,        // location -> address
+    addr2line: HashMap<u64, Rc<str>>,        // address -> line
+    line_rows: Vec<(u64, Rc<str>)>,          // every row of line programs, including skipped ones
+    non_stmt_locations: HashSet<Rc<str>>,    // locations resolved to non-statement rows, replaced by the first statement row
+    prologue_ends: HashMap<u64, u64>,        // func start -> address of the row marked as prologue end
+    source_paths: HashMap<Rc<str>, PathBuf>, // filepath -> path of source file
     funcs: HashMap<Rc<str>, EntryRef<R::Offset>>,
+    func_return_types: HashMap<Rc<str>, TypeId>,
     func_ranges: Ranges<Rc<str>>,
+    pending_func_ranges: Vec<(u64, u64, Rc<str>)>, // collected while parsing, loaded into func_ranges at once
     unit_ranges: Ranges<Rc<str>>,
-    main_unit: Option<Rc<str>>, // unit where main func is located
-    func_variables: HashMap<Rc<str>, HashMap<Rc<str>, VarRef<R::Offset>>>,
-    global_variables: HashMap<Rc<str>, VarRef<R::Offset>>,
+    main_func: Rc<str>,                                                         // entry function of the program, `main` by default
+    main_unit: Option<Rc<str>>,                                                 // unit where main func is located
+    func_variables: HashMap<Rc<str>, HashMap<Rc<str>, Vec<VarRef<R::Offset>>>>, // variables of nested blocks may share a name
+    global_variables: HashMap<Rc<str>, HashMap<Rc<str>, VarRef<R::Offset>>>,    // name -> { unit: var }, static globals may collide
 }
 
 impl<R: gimli::Reader> LocFinder<R> {
@@ -53,10 +117,16 @@ impl<R: gimli::Reader> LocFinder<R> {
             base_address,
             locations: HashMap::new(),
             addr2line: HashMap::new(),
-            lines: HashMap::new(),
+            line_rows: Vec::new(),
+            non_stmt_locations: HashSet::new(),
+            prologue_ends: HashMap::new(),
+            source_paths: HashMap::new(),
             funcs: HashMap::new(),
+            func_return_types: HashMap::new(),
             func_ranges: Ranges::new(),
+            pending_func_ranges: Vec::new(),
             unit_ranges: Ranges::new(),
+            main_func: Rc::from(MAIN_FUNC_NAME),
             main_unit: None,
             func_variables: HashMap::new(),
             global_variables: HashMap::new(),
@@ -71,51 +141,88 @@ impl<R: gimli::Reader> LocFinder<R> {
         self.funcs.insert(name, entry_ref);
     }
 
+    pub fn add_func_return_type(&mut self, name: Rc<str>, type_id: TypeId) {
+        self.func_return_types.insert(name, type_id);
+    }
+
     pub fn add_location(&mut self, name: Rc<str>, address: u64) {
         self.locations.insert(name, self.base_address + address);
     }
 
     // todo start_address, end_address
     pub fn add_func_range(&mut self, name: Rc<str>, low_pc: u64, high_pc: u64) {
-        self.func_ranges.add(self.base_address + low_pc, self.base_address + high_pc, name.clone());
+        self.pending_func_ranges
+            .push((self.base_address + low_pc, self.base_address + high_pc, name.clone()));
 
-        if name.as_ref() == MAIN_FUNC_NAME {
+        if name == self.main_func {
             // compile unit must be processed by now
             self.main_unit = self.unit_ranges.find_value(self.base_address + low_pc).cloned();
         }
     }
 
-    pub fn add_var(&mut self, name: Rc<str>, var_ref: VarRef<R::Offset>, func_name: Option<Rc<str>>) {
-        match func_name {
-            Some(func_name) => self.func_variables.entry(func_name).or_default().insert(name, var_ref),
-            None => self.global_variables.insert(name, var_ref),
+    /// must be called once all functions are added
+    pub fn build_func_ranges(&mut self) {
+        self.func_ranges = Ranges::bulk_load(mem::take(&mut self.pending_func_ranges));
+    }
+
+    pub fn add_var(&mut self, name: Rc<str>, mut var_ref: VarRef<R::Offset>, scope: VarScope) {
+        match scope {
+            VarScope::Func(func_name) => {
+                var_ref.pc_ranges = var_ref.pc_ranges.map(|pc_ranges| {
+                    pc_ranges
+                        .iter()
+                        .map(|&(low_pc, high_pc)| (self.base_address + low_pc, self.base_address + high_pc))
+                        .collect()
+                });
+                self.func_variables.entry(func_name).or_default().entry(name).or_default().push(var_ref);
+            }
+            VarScope::Unit(unit_name) => {
+                self.global_variables.entry(name).or_default().insert(unit_name, var_ref);
+            }
         };
     }
 
-    pub fn add_line(&mut self, filepath: Rc<str>, line: usize, address: u64) {
+    pub fn add_source_path(&mut self, filepath: Rc<str>, source_path: PathBuf) {
+        self.source_paths.entry(filepath).or_insert(source_path);
+    }
+
+    /// rows must be added in address order of their sequence
+    pub fn add_line(&mut self, filepath: Rc<str>, line: usize, address: u64, is_stmt: bool, is_prologue_end: bool) {
         let fileline: Rc<str> = Rc::from(format!("{}:{}", filepath, line));
 
         let address = self.base_address + address;
-        self.locations.entry(fileline.clone()).or_insert(address);
+        match self.locations.entry(fileline.clone()) {
+            Entry::Vacant(entry) => {
+                entry.insert(address);
+                if !is_stmt {
+                    self.non_stmt_locations.insert(fileline.clone());
+                }
+            }
+            Entry::Occupied(mut entry) => {
+                if is_stmt && self.non_stmt_locations.remove(&fileline) {
+                    entry.insert(address);
+                }
+            }
+        }
+        self.line_rows.push((address, fileline.clone()));
 
+        if is_prologue_end {
+            self.set_prologue_end(address);
+        }
         if self.is_func_prologue(address) || self.is_func_epilogue(address) {
             return;
         }
 
         self.addr2line.insert(address, fileline);
-
-        let lines = self.lines.entry(filepath).or_default();
-        // skip empty lines
-        while lines.len() < line {
-            lines.push(0);
-        }
-        // save only first line appearance
-        if lines.len() == line {
-            lines.push(address);
-        }
     }
 
     pub fn find_loc(&self, loc: &str) -> Result<Option<u64>> {
+        // raw address must point into some function
+        if let Some(hex) = loc.strip_prefix("0x") {
+            let address = u64::from_str_radix(hex, 16).map_err(|_| DebuggerError::InvalidLocation)?;
+            return Ok(self.find_func_by_address(address).map(|_| address));
+        }
+
         Ok(self.locations.get(loc).copied())
     }
 
@@ -123,15 +230,56 @@ impl<R: gimli::Reader> LocFinder<R> {
         self.addr2line.get(&address).cloned()
     }
 
-    pub fn find_next_line_address(&self, fileline: &str) -> Option<u64> {
-        let (filepath, line) = Self::parse_fileline(fileline)?;
-        self.lines.get(filepath)?.iter().skip(line as usize + 1).find(|&&address| address != 0).copied()
+    /// line of the last row at or before the address in its function, return addresses of callers don't start rows
+    pub fn find_enclosing_line(&self, address: u64) -> Option<Rc<str>> {
+        let (start, _) = self.func_ranges.find_range(address)?;
+
+        self.line_rows
+            .iter()
+            .filter(|&&(row_address, _)| start <= row_address && row_address <= address)
+            .max_by_key(|&&(row_address, _)| row_address)
+            .map(|(_, fileline)| fileline.clone())
+    }
+
+    pub fn find_source_path(&self, filepath: &str) -> Option<&Path> {
+        self.source_paths.get(filepath).map(PathBuf::as_path)
+    }
+
+    /// rows of other lines of the function, sorted by address. Code isn't laid out in source order (loops jump back),
+    /// so any of them could be executed after the line
+    pub fn find_other_line_addresses(&self, address: u64, fileline: &str) -> Option<Vec<u64>> {
+        let (start, end) = self.func_ranges.find_range(address)?;
+
+        let mut addresses = self
+            .line_rows
+            .iter()
+            .map(|&(address, _)| address)
+            .filter(|&address| start <= address && address <= end)
+            .filter(|address| self.addr2line.get(address).is_some_and(|line| line.as_ref() != fileline))
+            .collect::<Vec<_>>();
+        addresses.sort();
+        addresses.dedup();
+
+        Some(addresses)
     }
 
     pub fn find_func(&self, func_name: &str) -> Option<EntryRef<R::Offset>> {
         self.funcs.get(func_name).copied()
     }
 
+    /// entry address of the function, before its prologue
+    pub fn find_func_address(&self, func_name: &str) -> Option<u64> {
+        if !self.funcs.contains_key(func_name) {
+            return None;
+        }
+
+        self.locations.get(func_name).copied()
+    }
+
+    pub fn find_func_return_type(&self, func_name: &str) -> Option<TypeId> {
+        self.func_return_types.get(func_name).copied()
+    }
+
     pub fn find_func_by_address(&self, address: u64) -> Option<Rc<str>> {
         self.func_ranges.find_value(address).cloned()
     }
@@ -151,17 +299,80 @@ impl<R: gimli::Reader> LocFinder<R> {
         self.func_ranges.find_range(address).map(|(_, end)| end)
     }
 
+    /// line table rows of the function ordered by address
+    pub fn get_line_table(&self, func_name: &str) -> Option<Vec<LineRow>> {
+        if !self.funcs.contains_key(func_name) {
+            return None;
+        }
+
+        let (start, end) = self.func_ranges.find_range(*self.locations.get(func_name)?)?;
+        let mut rows = self
+            .line_rows
+            .iter()
+            .filter(|&&(address, _)| start <= address && address <= end)
+            .map(|(address, fileline)| {
+                let kind = if self.is_func_prologue(*address) {
+                    LineRowKind::Prologue
+                } else if self.is_func_epilogue(*address) {
+                    LineRowKind::Epilogue
+                } else {
+                    LineRowKind::Line
+                };
+
+                LineRow {
+                    address: *address,
+                    fileline: fileline.clone(),
+                    kind,
+                }
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|row| row.address);
+
+        Some(rows)
+    }
+
     pub fn is_inside_main(&self, address: u64) -> bool {
         match self.find_func_by_address(address) {
-            Some(func) => func.as_ref() == MAIN_FUNC_NAME,
+            Some(func) => func == self.main_func,
             None => false,
         }
     }
 
+    /// treats the function as the entry of the program, its unit becomes the default one. False if there is no such function
+    pub fn set_main_func(&mut self, func_name: &str) -> bool {
+        let Some(address) = self.find_func_address(func_name) else {
+            return false;
+        };
+
+        self.main_func = Rc::from(func_name);
+        self.main_unit = self.unit_ranges.find_value(address).cloned();
+
+        true
+    }
+
+    /// rows before the one marked as prologue end belong to the prologue, rows already added are dropped
+    fn set_prologue_end(&mut self, address: u64) {
+        let Some(start) = self.find_func_start(address) else {
+            return;
+        };
+        if self.prologue_ends.contains_key(&start) {
+            return;
+        }
+
+        self.prologue_ends.insert(start, address);
+        for (row_address, _) in &self.line_rows {
+            if start <= *row_address && *row_address < address {
+                self.addr2line.remove(row_address);
+            }
+        }
+    }
+
+    /// compilers that don't mark prologue end (gcc) put the whole prologue into the first row of the function
     fn is_func_prologue(&self, address: u64) -> bool {
-        self.find_func_start(address)
-            .map(|start| address - start < FUNC_PROLOGUE_SIZE as u64)
-            .unwrap_or(false)
+        self.find_func_start(address).is_some_and(|start| match self.prologue_ends.get(&start) {
+            Some(&prologue_end) => address < prologue_end,
+            None => address == start,
+        })
     }
 
     fn is_func_epilogue(&self, address: u64) -> bool {
@@ -170,34 +381,274 @@ impl<R: gimli::Reader> LocFinder<R> {
             .unwrap_or(false)
     }
 
-    fn parse_fileline(fileline: &str) -> Option<(&str, u64)> {
+    pub fn parse_fileline(fileline: &str) -> Option<(&str, u64)> {
         fileline
             .rsplit_once(':')
             .and_then(|(filepath, line)| line.parse::<u64>().map(|line| (filepath, line)).ok())
     }
 
-    pub fn get_vars(&self, func_name: Option<&str>) -> HashMap<Rc<str>, VarRef<R::Offset>> {
+    /// variables of the function shadow globals, `frame` is the function and pc in it
+    pub fn get_vars(&self, frame: Option<(&str, u64)>, unit_name: Option<&str>) -> HashMap<Rc<str>, VarRef<R::Offset>> {
+        let mut vars = self.get_global_vars(unit_name);
+
+        if let Some((func_name, pc)) = frame {
+            vars.extend(self.get_func_vars(func_name, pc));
+        }
+
+        vars
+    }
+
+    /// parameters and local variables of the function, variables of lexical blocks are visible only inside the block
+    pub fn get_func_vars(&self, func_name: &str, pc: u64) -> HashMap<Rc<str>, VarRef<R::Offset>> {
+        let Some(func_vars) = self.func_variables.get(func_name) else {
+            return HashMap::new();
+        };
+
+        func_vars
+            .iter()
+            .filter_map(|(name, var_refs)| Self::find_visible_var(var_refs, pc).map(|var_ref| (name.clone(), var_ref)))
+            .collect()
+    }
+
+    fn find_visible_var(var_refs: &[VarRef<R::Offset>], pc: u64) -> Option<VarRef<R::Offset>> {
+        var_refs
+            .iter()
+            .filter(|var_ref| var_ref.is_visible_at(pc))
+            .min_by_key(|var_ref| var_ref.block_size())
+            .cloned()
+    }
+
+    /// globals colliding with the ones of the current unit are listed by qualified name
+    pub fn get_global_vars(&self, unit_name: Option<&str>) -> HashMap<Rc<str>, VarRef<R::Offset>> {
         let mut vars = HashMap::new();
 
-        for (name, &var_ref) in self.global_variables.iter() {
-            vars.insert(name.clone(), var_ref);
+        for (name, units) in self.global_variables.iter() {
+            for (unit, var_ref) in units.iter() {
+                let name = if units.len() == 1 || Some(unit.as_ref()) == unit_name {
+                    name.clone()
+                } else {
+                    Rc::from(format!("{}::{}", unit, name))
+                };
+                vars.insert(name, var_ref.clone());
+            }
         }
 
-        if let Some(func_name) = func_name {
-            self.func_variables.get(func_name).inspect(|&func_vars| {
-                for (name, &var_ref) in func_vars.iter() {
-                    vars.insert(name.clone(), var_ref);
+        vars
+    }
+
+    /// qualifier is either a unit (`file.c`), a namespace (`ns`) or both (`file.cpp::ns`), `frame` is the function and pc in it
+    pub fn get_var(&self, name: &str, qualifier: Option<&str>, frame: Option<(&str, u64)>, unit_name: Option<&str>) -> Result<Option<VarRef<R::Offset>>> {
+        let qualifier = match qualifier {
+            Some(qualifier) => qualifier,
+            None => {
+                let func_var = frame.and_then(|(func_name, pc)| {
+                    self.func_variables
+                        .get(func_name)
+                        .and_then(|vars| vars.get(name))
+                        .and_then(|var_refs| Self::find_visible_var(var_refs, pc))
+                });
+
+                return match func_var {
+                    Some(var_ref) => Ok(Some(var_ref)),
+                    None => self.get_global_var(name, unit_name),
+                };
+            }
+        };
+
+        let (unit_qualifier, unit_var_name) = match qualifier.split_once("::") {
+            Some((unit_qualifier, namespace)) => (unit_qualifier, Cow::from(format!("{}::{}", namespace, name))),
+            None => (qualifier, Cow::from(name)),
+        };
+        if let Some(units) = self.global_variables.get(unit_var_name.as_ref()) {
+            let mut matching_units = units.iter().filter(|(unit, _)| Self::is_unit_match(unit, unit_qualifier));
+            if let Some((_, var_ref)) = matching_units.next() {
+                if matching_units.next().is_some() {
+                    bail!(DebuggerError::AmbiguousVar(format!("{}::{}", qualifier, name)));
                 }
-            });
+
+                return Ok(Some(var_ref.clone()));
+            }
         }
 
-        vars
+        self.get_global_var(&format!("{}::{}", qualifier, name), unit_name)
+    }
+
+    /// global of the current unit wins, globals of other units must be unambiguous
+    fn get_global_var(&self, name: &str, unit_name: Option<&str>) -> Result<Option<VarRef<R::Offset>>> {
+        let units = match self.global_variables.get(name) {
+            Some(units) => units,
+            None => return Ok(None),
+        };
+
+        if let Some(var_ref) = unit_name.and_then(|unit_name| units.get(unit_name)) {
+            return Ok(Some(var_ref.clone()));
+        }
+
+        match units.len() {
+            1 => Ok(units.values().next().cloned()),
+            _ => bail!(DebuggerError::AmbiguousVar(String::from(name))),
+        }
+    }
+
+    /// unit name is matched by its full path or by trailing path components
+    fn is_unit_match(unit_name: &str, qualifier: &str) -> bool {
+        unit_name == qualifier || unit_name.strip_suffix(qualifier).is_some_and(|prefix| prefix.ends_with('/'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dwarf::{DwarfBuilder, Reader};
+
+    // foo is [0x1000, 0x1020) with a loop jumping back to line 4, main is [0x1020, 0x1040)
+    fn build_loc_finder() -> LocFinder<Reader> {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x1040);
+        builder.add_func(unit, "foo", 0x1000, 0x1020);
+        builder.add_func(unit, "main", 0x1020, 0x1040);
+        builder.add_line_sequence(unit, 0x1000, &[(0, 3), (0x8, 4), (0x10, 5), (0x14, 4), (0x18, 6), (0x1e, 7)], 0x20);
+        builder.add_line_sequence(unit, 0x1020, &[(0, 10), (0x8, 11), (0x1e, 12)], 0x20);
+
+        builder.parse().0
     }
 
-    pub fn get_var(&self, name: &str, func_name: Option<&str>) -> Option<VarRef<R::Offset>> {
-        func_name
-            .and_then(|func_name| self.func_variables.get(func_name))
-            .and_then(|vars| vars.get(name).copied())
-            .or_else(|| self.global_variables.get(name).copied())
+    #[test]
+    fn test_find_loc() {
+        let loc_finder = build_loc_finder();
+
+        assert_eq!(loc_finder.find_loc("foo").unwrap(), Some(0x1000));
+        assert_eq!(loc_finder.find_loc("t.c:4").unwrap(), Some(0x1008));
+        assert_eq!(loc_finder.find_loc("t.c:11").unwrap(), Some(0x1028));
+        assert_eq!(loc_finder.find_loc("t.c:100").unwrap(), None);
+        assert_eq!(loc_finder.find_loc("0x1010").unwrap(), Some(0x1010));
+        assert_eq!(loc_finder.find_loc("0x5000").unwrap(), None);
+        assert!(matches!(
+            loc_finder.find_loc("0xzz").unwrap_err().downcast_ref::<DebuggerError>(),
+            Some(DebuggerError::InvalidLocation)
+        ));
+    }
+
+    #[test]
+    fn test_find_line() {
+        let loc_finder = build_loc_finder();
+
+        assert_eq!(loc_finder.find_line(0x1008).as_deref(), Some("t.c:4"));
+        assert_eq!(loc_finder.find_line(0x1014).as_deref(), Some("t.c:4"));
+        assert_eq!(loc_finder.find_line(0x1018).as_deref(), Some("t.c:6"));
+        // prologue and epilogue rows are skipped
+        assert_eq!(loc_finder.find_line(0x1000), None);
+        assert_eq!(loc_finder.find_line(0x101e), None);
+        assert_eq!(loc_finder.find_line(0x100c), None);
+        assert_eq!(loc_finder.find_source_path("t.c"), Some(Path::new("/src/t.c")));
+    }
+
+    #[test]
+    fn test_prologue_end() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x1040);
+        builder.add_func(unit, "foo", 0x1000, 0x1020);
+        builder.add_func(unit, "main", 0x1020, 0x1040);
+        // clang marks the first row after the prologue
+        builder.add_line_sequence_with_prologue_end(unit, 0x1000, &[(0, 3), (0x4, 3), (0xc, 4), (0x18, 5)], Some(0xc), 0x20);
+        // gcc doesn't, only the first row is the prologue even if the body starts right after it
+        builder.add_line_sequence(unit, 0x1020, &[(0, 10), (0x4, 11), (0x10, 12)], 0x20);
+        let loc_finder = builder.parse().0;
+
+        assert_eq!(loc_finder.find_line(0x1000), None);
+        assert_eq!(loc_finder.find_line(0x1004), None);
+        assert_eq!(loc_finder.find_line(0x100c).as_deref(), Some("t.c:4"));
+        assert_eq!(loc_finder.find_line(0x1020), None);
+        assert_eq!(loc_finder.find_line(0x1024).as_deref(), Some("t.c:11"));
+
+        let kinds = loc_finder.get_line_table("foo").unwrap().into_iter().map(|row| row.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [LineRowKind::Prologue, LineRowKind::Prologue, LineRowKind::Line, LineRowKind::Line]);
+    }
+
+    #[test]
+    fn test_find_enclosing_line() {
+        let loc_finder = build_loc_finder();
+
+        assert_eq!(loc_finder.find_enclosing_line(0x1008).as_deref(), Some("t.c:4"));
+        assert_eq!(loc_finder.find_enclosing_line(0x1012).as_deref(), Some("t.c:5"));
+        assert_eq!(loc_finder.find_enclosing_line(0x1027).as_deref(), Some("t.c:10"));
+        assert_eq!(loc_finder.find_enclosing_line(0x1040), None);
+    }
+
+    #[test]
+    fn test_find_func_and_unit() {
+        let loc_finder = build_loc_finder();
+
+        assert_eq!(loc_finder.find_func_by_address(0x1000).as_deref(), Some("foo"));
+        assert_eq!(loc_finder.find_func_by_address(0x101f).as_deref(), Some("foo"));
+        assert_eq!(loc_finder.find_func_by_address(0x1020).as_deref(), Some("main"));
+        assert_eq!(loc_finder.find_func_by_address(0x1040), None);
+        assert_eq!(loc_finder.find_func_start(0x1010), Some(0x1000));
+        assert_eq!(loc_finder.find_func_end(0x1010), Some(0x101f));
+        assert!(loc_finder.is_inside_main(0x1028));
+        assert!(!loc_finder.is_inside_main(0x1008));
+        assert_eq!(loc_finder.find_unit(Some(0x1030)).as_deref(), Some("t.c"));
+        assert_eq!(loc_finder.find_unit(Some(0x2000)), None);
+        assert_eq!(loc_finder.find_unit(None).as_deref(), Some("t.c"));
+    }
+
+    #[test]
+    fn test_set_main_func() {
+        let mut builder = DwarfBuilder::new();
+        let unit = builder.add_unit("t.c", 0x1000, 0x1020);
+        builder.add_func(unit, "my_start", 0x1000, 0x1020);
+        let other_unit = builder.add_unit("lib.c", 0x2000, 0x2020);
+        builder.add_func(other_unit, "lib_main", 0x2000, 0x2020);
+        let mut loc_finder = builder.parse().0;
+
+        // no main
+        assert!(!loc_finder.is_inside_main(0x1008));
+        assert_eq!(loc_finder.find_unit(None), None);
+
+        assert!(loc_finder.set_main_func("my_start"));
+        assert!(loc_finder.is_inside_main(0x1008));
+        assert_eq!(loc_finder.find_unit(None).as_deref(), Some("t.c"));
+
+        assert!(loc_finder.set_main_func("lib_main"));
+        assert!(!loc_finder.is_inside_main(0x1008));
+        assert!(loc_finder.is_inside_main(0x2008));
+        assert_eq!(loc_finder.find_unit(None).as_deref(), Some("lib.c"));
+
+        assert!(!loc_finder.set_main_func("nope"));
+        assert!(loc_finder.is_inside_main(0x2008));
+    }
+
+    #[test]
+    fn test_get_line_table() {
+        let loc_finder = build_loc_finder();
+
+        let rows = loc_finder
+            .get_line_table("foo")
+            .unwrap()
+            .into_iter()
+            .map(|row| (row.address, row.fileline.to_string(), row.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                (0x1000, "t.c:3".to_string(), LineRowKind::Prologue),
+                (0x1008, "t.c:4".to_string(), LineRowKind::Line),
+                (0x1010, "t.c:5".to_string(), LineRowKind::Line),
+                (0x1014, "t.c:4".to_string(), LineRowKind::Line),
+                (0x1018, "t.c:6".to_string(), LineRowKind::Line),
+                (0x101e, "t.c:7".to_string(), LineRowKind::Epilogue),
+            ]
+        );
+        assert!(loc_finder.get_line_table("bar").is_none());
+    }
+
+    #[test]
+    fn test_find_other_line_addresses() {
+        let loc_finder = build_loc_finder();
+
+        assert_eq!(loc_finder.find_other_line_addresses(0x1008, "t.c:4"), Some(vec![0x1010, 0x1018]));
+        assert_eq!(loc_finder.find_other_line_addresses(0x1018, "t.c:6"), Some(vec![0x1008, 0x1010, 0x1014]));
+        assert_eq!(loc_finder.find_other_line_addresses(0x1028, "t.c:11"), Some(vec![]));
+        assert_eq!(loc_finder.find_other_line_addresses(0x5000, "t.c:4"), None);
     }
 }
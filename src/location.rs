@@ -61,3 +61,42 @@ impl TypedValueLoc {
         }
     }
 }
+
+/// replaces bytes of register value starting at offset, other bytes are kept
+pub fn replace_register_bytes(register_value: u64, offset: u16, value: &[u8]) -> Result<u64, DebuggerError> {
+    let offset = offset as usize;
+    if offset + value.len() > WORD_SIZE {
+        return Err(DebuggerError::InvalidValue);
+    }
+
+    let mut buf = register_value.to_ne_bytes();
+    buf[offset..offset + value.len()].copy_from_slice(value);
+
+    Ok(u64::from_ne_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_register_bytes() {
+        let register_value = 0x1122334455667788u64;
+
+        assert_eq!(replace_register_bytes(register_value, 0, &[0xaa]).unwrap(), 0x11223344556677aa);
+        assert_eq!(replace_register_bytes(register_value, 1, &[0xaa]).unwrap(), 0x112233445566aa88);
+        assert_eq!(replace_register_bytes(register_value, 2, &0xbbccu16.to_ne_bytes()).unwrap(), 0x11223344bbcc7788);
+        assert_eq!(replace_register_bytes(register_value, 6, &0xbbccu16.to_ne_bytes()).unwrap(), 0xbbcc334455667788);
+        assert_eq!(
+            replace_register_bytes(register_value, 4, &0xdeadbeefu32.to_ne_bytes()).unwrap(),
+            0xdeadbeef55667788
+        );
+        assert_eq!(replace_register_bytes(register_value, 0, &0xdeadbeefu64.to_ne_bytes()).unwrap(), 0xdeadbeef);
+    }
+
+    #[test]
+    fn test_replace_register_bytes_out_of_register() {
+        assert!(replace_register_bytes(0, 7, &[0xaa, 0xbb]).is_err());
+        assert!(replace_register_bytes(0, 1, &[0; WORD_SIZE]).is_err());
+    }
+}
@@ -1,73 +1,136 @@
+mod abi;
+mod alias;
 mod breakpoint;
+mod checkpoint;
 mod commands;
+mod condition;
 mod consts;
 mod context;
 mod debugger;
+mod disasm;
+mod display;
 mod dwarf_parser;
 mod error;
+mod examine;
 mod fsm;
+mod history;
+mod image;
+mod inferiors;
+mod interrupt;
+mod launcher;
 mod loc_finder;
 mod location;
 mod path;
 mod printer;
+mod procmaps;
+mod readline;
 mod session;
+#[cfg(test)]
+mod test_dwarf;
 mod trap;
 mod types;
 mod unwinder;
 mod utils;
 mod var;
+mod watchpoint;
+mod xstate;
 
-use std::{io::Write, path::Path};
+use std::ffi::OsString;
+use std::path::PathBuf;
 
-use error::DebuggerError;
+use alias::Aliases;
+use error::{DebuggerError, DwarfParseError};
 use fsm::{CommandParser, Rule, FSM};
+use inferiors::Inferiors;
+use launcher::Launcher;
+use readline::LineReader;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use debugger::Debugger;
 use pest::Parser;
 
 fn main() -> Result<()> {
     env_logger::init();
 
-    let args = std::env::args().skip(1).collect::<Vec<_>>();
-    if args.is_empty() {
-        bail!("pass program");
+    let mut args = std::env::args_os().skip(1).peekable();
+    let mut stdin = None;
+    let mut env = Vec::new();
+    let mut clear_env = false;
+    // debugger options go before the program, everything after it is passed to the program
+    while let Some(option) = args.next_if(|arg| arg.to_string_lossy().starts_with("--")) {
+        match option.to_string_lossy().as_ref() {
+            "--stdin" => stdin = Some(PathBuf::from(args.next().ok_or(anyhow!("pass stdin file"))?)),
+            "--env" => {
+                let var = args.next().ok_or(anyhow!("pass env var"))?;
+                let (key, value) = var.to_str().and_then(|var| var.split_once('=')).ok_or(anyhow!("env var must be KEY=VALUE"))?;
+                env.push((OsString::from(key), OsString::from(value)));
+            }
+            "--clear-env" => clear_env = true,
+            option => bail!("unknown option {option}"),
+        }
     }
 
-    let prog_path = Path::new(&args[0]);
+    let Some(prog) = args.next() else {
+        bail!("pass program");
+    };
+    let launcher = Launcher::new(PathBuf::from(prog), args.collect::<Vec<OsString>>())
+        .with_stdin(stdin)
+        .with_env(env, clear_env);
 
     let debugger = Debugger::new();
-    let mut session = debugger.start(prog_path, &args[1..])?;
-    let mut fsm = FSM::new(&mut session);
+    // reading debug info of a big program takes a while
+    interrupt::catch()?;
+    let session = match debugger.start(launcher) {
+        Ok(session) => session,
+        Err(e) if matches!(e.downcast_ref::<DebuggerError>(), Some(DebuggerError::Cancelled)) => {
+            eprintln!("{}", e);
+            std::process::exit(130);
+        }
+        Err(e) if e.is::<DwarfParseError>() => bail!("failed to parse debug info: {e}"),
+        Err(e) => return Err(e),
+    };
+    interrupt::release()?;
+    let inferiors = Inferiors::new(session, Box::new(|prog| debugger.load(prog)));
+    let mut fsm = FSM::new(inferiors, Aliases::load(history::history_path()));
+    let mut line_reader = LineReader::new()?;
 
     loop {
-        let line = readline()?;
+        let line = match line_reader.readline()? {
+            Some(line) => line,
+            // input is over, e.g. all piped commands are executed
+            None => {
+                fsm.quit()?;
+                return Ok(());
+            }
+        };
         let line = line.trim();
         if line.is_empty() {
             continue;
         }
 
-        match CommandParser::parse(Rule::command, line) {
-            Ok(pairs) => match fsm.handle(pairs) {
-                Ok(should_quit) => {
-                    if should_quit {
-                        return Ok(());
-                    }
+        let line = fsm.expand_alias(line);
+
+        let result = if fsm.is_recording_commands() {
+            fsm.record_command(&line).map(|_| false)
+        } else {
+            match CommandParser::parse(Rule::command, &line) {
+                Ok(pairs) => fsm.handle(pairs),
+                Err(e) => {
+                    eprintln!("parser error {e}");
+                    continue;
                 }
-                Err(e) => match e.downcast_ref::<DebuggerError>() {
-                    Some(_) => eprintln!("{}", e),
-                    None => return Err(e),
-                },
-            },
-            Err(e) => eprintln!("parser error {e}"),
+            }
         };
-    }
-}
 
-fn readline() -> Result<String> {
-    print!("> ");
-    std::io::stdout().flush()?;
-    let mut buf = String::new();
-    std::io::stdin().read_line(&mut buf)?;
-    Ok(buf)
+        match result {
+            Ok(should_quit) => {
+                if should_quit {
+                    return Ok(());
+                }
+            }
+            Err(e) if e.is::<DebuggerError>() => eprintln!("{}", e),
+            Err(e) if e.is::<DwarfParseError>() => eprintln!("failed to parse debug info: {}", e),
+            Err(e) => return Err(e),
+        }
+    }
 }
@@ -1,12 +1,44 @@
-use anyhow::anyhow;
+use anyhow::{anyhow, Result};
+use pest::Parser;
+
+use crate::error::DebuggerError;
+use crate::fsm::{self, CommandParser, Rule};
 
 #[derive(Debug, Default)]
 pub struct Path<'a> {
     pub prefix_operators: Vec<PrefixOperator>,
+    pub cast: Option<Cast<'a>>,
+    pub scope: Option<&'a str>, // unit or namespace of global variable
     pub name: &'a str,
+    pub address: Option<u64>, // number in place of variable, like `(struct node *)0x55f0`, name is the number then
     pub postfix_operators: Vec<PostfixOperator<'a>>,
 }
 
+impl<'a> Path<'a> {
+    /// parses path saved as text, like the one of a logpoint
+    pub fn parse(text: &'a str) -> Result<Self> {
+        let mut pairs = CommandParser::parse(Rule::path, text).map_err(|_| DebuggerError::InvalidPath)?;
+        fsm::parse_path(pairs.next().unwrap())
+    }
+}
+
+/// C-style cast like `(struct node *)`
+#[derive(Debug)]
+pub struct Cast<'a> {
+    pub type_name: &'a str,
+    pub pointers: usize,
+    pub position: usize, // number of prefix operators before the cast, they are applied to the cast value
+}
+
+impl std::fmt::Display for Cast<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.pointers {
+            0 => write!(f, "({})", self.type_name),
+            pointers => write!(f, "({} {})", self.type_name, "*".repeat(pointers)),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum PrefixOperator {
     Ref,
@@ -38,4 +70,5 @@ impl From<&PrefixOperator> for char {
 pub enum PostfixOperator<'a> {
     Field(&'a str),
     Index(usize),
+    Slice(usize, usize), // [start..end)
 }
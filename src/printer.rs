@@ -1,32 +1,318 @@
+use std::fmt;
 use std::io;
 use std::io::Write;
 
 use anyhow::{bail, Result};
-use bytes::Buf;
+use bytes::{Buf, Bytes};
 
 use crate::error::DebuggerError;
+use crate::location::{TypedValueLoc, ValueLoc};
 use crate::session::DebugSession;
-use crate::types::{ArrayCount, Type, TypeId};
+use crate::types::{ArrayCount, EnumVariant, Field, Type, TypeId};
 use crate::var::{Value, Var};
 
+const HEX_DUMP_LINE_SIZE: usize = 16;
+const DEFAULT_STRING_LENGTH: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Raw,
+    Hex,
+    Decimal,
+}
+
+impl TryFrom<&str> for Format {
+    type Error = DebuggerError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "r" => Ok(Format::Raw),
+            "x" => Ok(Format::Hex),
+            "d" => Ok(Format::Decimal),
+            _ => Err(DebuggerError::InvalidFormat),
+        }
+    }
+}
+
+/// base of integers which are printed or parsed without explicit format
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Radix {
+    Octal,
+    Decimal,
+    Hex,
+}
+
+impl Radix {
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Hex => 16,
+        }
+    }
+}
+
+impl TryFrom<&str> for Radix {
+    type Error = DebuggerError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "8" => Ok(Radix::Octal),
+            "10" => Ok(Radix::Decimal),
+            "16" => Ok(Radix::Hex),
+            _ => Err(DebuggerError::InvalidValue),
+        }
+    }
+}
+
+/// print options set by `set print` and `set output-radix`
+#[derive(Debug, Clone, Copy)]
+pub struct PrintSettings {
+    pub string_length: Option<usize>, // longer strings are cut with ...
+    pub escape_strings: bool,
+    pub radix: Radix, // format specifiers override it
+}
+
+impl Default for PrintSettings {
+    fn default() -> Self {
+        Self {
+            string_length: Some(DEFAULT_STRING_LENGTH),
+            escape_strings: true,
+            radix: Radix::Decimal,
+        }
+    }
+}
+
+/// part of summary format set by `set summary-format`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SummaryPart {
+    Text(String),
+    Field(String), // `{field}` placeholder, expanded with the value of struct member
+}
+
+/// `{{` and `}}` are literal braces
+pub fn parse_summary_format(format: &str) -> Result<Vec<SummaryPart>, DebuggerError> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let (field, rest) = chars.as_str().split_once('}').ok_or(DebuggerError::InvalidFormat)?;
+                if field.is_empty() || !field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(DebuggerError::InvalidFormat);
+                }
+                if !text.is_empty() {
+                    parts.push(SummaryPart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(SummaryPart::Field(field.to_string()));
+                chars = rest.chars();
+            }
+            '}' => return Err(DebuggerError::InvalidFormat),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(SummaryPart::Text(text));
+    }
+
+    Ok(parts)
+}
+
 pub struct Printer<'a, R: gimli::Reader> {
     session: &'a DebugSession<R>,
+    format: Option<Format>,
 }
 
 impl<'a, R: gimli::Reader> Printer<'a, R> {
     pub fn new(session: &'a DebugSession<R>) -> Self {
-        Self { session }
+        Self { session, format: None }
+    }
+
+    pub fn with_format(self, format: Format) -> Self {
+        Self { format: Some(format), ..self }
     }
 
     pub fn print(&self, var: &Var) -> Result<()> {
         // we don't use stdout lock because we want print nothing in case of error
+        println!("{}", self.format(var)?);
+
+        Ok(())
+    }
+
+    pub fn format(&self, var: &Var) -> Result<String> {
         let mut buf = Vec::new();
 
         self.print_type(&mut buf, var.value.type_id)?;
         write!(buf, " {} = ", var.name)?;
         self.print_value(&mut buf, var.value.clone())?;
 
-        println!("{}", std::str::from_utf8(&buf)?);
+        Ok(String::from_utf8(buf)?)
+    }
+
+    /// type with members of struct or union and values of enum variants listed, `offsets` adds offset and size of members and padding holes
+    pub fn format_type_definition(&self, type_id: TypeId, offsets: bool) -> Result<String> {
+        let type_storage = self.session.get_type_storage();
+        let mut buf = Vec::new();
+        write!(buf, "type = ")?;
+
+        let mut type_id = type_id;
+        while let Type::Typedef(_, subtype_id) = type_storage.get(type_id)? {
+            type_id = subtype_id;
+        }
+
+        match type_storage.unwind_type(type_id)? {
+            Type::Struct { name, size, fields } => {
+                self.write_aggregate_name(&mut buf, "struct", name.as_deref())?;
+
+                let mut end = 0;
+                for field in fields.iter() {
+                    let offset = field.offset as usize;
+                    let field_size = self.session.get_type_size(field.type_id)?;
+                    if offsets && offset > end {
+                        writeln!(buf, "    /* XXX {}-byte hole */", offset - end)?;
+                    }
+                    self.write_member(&mut buf, field.type_id, &field.name, offsets.then_some((offset, field_size)))?;
+                    end = end.max(offset + field_size);
+                }
+
+                self.write_aggregate_end(&mut buf, size as usize, end, offsets)?;
+            }
+            Type::Union { name, size, fields } => {
+                self.write_aggregate_name(&mut buf, "union", name.as_deref())?;
+
+                let mut end = 0;
+                for field in fields.iter() {
+                    let field_size = self.session.get_type_size(field.type_id)?;
+                    self.write_member(&mut buf, field.type_id, &field.name, offsets.then_some((0, field_size)))?;
+                    end = end.max(field_size);
+                }
+
+                self.write_aggregate_end(&mut buf, size as usize, end, offsets)?;
+            }
+            Type::Enum {
+                name,
+                encoding,
+                size,
+                variants,
+                ..
+            } => {
+                self.write_aggregate_name(&mut buf, "enum", name.as_deref())?;
+
+                for variant in variants.iter() {
+                    match encoding {
+                        gimli::DW_ATE_unsigned => writeln!(buf, "    {} = {},", variant.name, variant.value as u64)?,
+                        _ => writeln!(buf, "    {} = {},", variant.name, variant.value)?,
+                    };
+                }
+
+                self.write_aggregate_end(&mut buf, size as usize, size as usize, offsets)?;
+            }
+            _ => self.print_type(&mut buf, type_id)?,
+        }
+
+        Ok(String::from_utf8(buf)?)
+    }
+
+    /// type as it's named in the code, typedefs are not followed and members are not listed
+    pub fn format_type_name(&self, type_id: TypeId) -> Result<String> {
+        let mut buf = Vec::new();
+        write!(buf, "type = ")?;
+
+        match self.session.get_type_storage().get(type_id)? {
+            Type::Struct { name: None, .. } => write!(buf, "struct {{...}}")?,
+            Type::Union { name: None, .. } => write!(buf, "union {{...}}")?,
+            Type::Enum { name: None, .. } => write!(buf, "enum {{...}}")?,
+            _ => self.print_type(&mut buf, type_id)?,
+        }
+
+        Ok(String::from_utf8(buf)?)
+    }
+
+    fn write_aggregate_name(&self, f: &mut impl io::Write, keyword: &str, name: Option<&str>) -> Result<()> {
+        match name {
+            Some(name) => writeln!(f, "{} {} {{", keyword, name)?,
+            None => writeln!(f, "{} {{", keyword)?,
+        };
+
+        Ok(())
+    }
+
+    fn write_member(&self, f: &mut impl io::Write, type_id: TypeId, name: &str, layout: Option<(usize, usize)>) -> Result<()> {
+        write!(f, "    ")?;
+        if let Some((offset, size)) = layout {
+            write!(f, "/* offset: {}, size: {} */ ", offset, size)?;
+        }
+        self.print_type(f, type_id)?;
+        writeln!(f, " {};", name)?;
+
+        Ok(())
+    }
+
+    /// `end` is where the last member ends, the rest of the size is padding
+    fn write_aggregate_end(&self, f: &mut impl io::Write, size: usize, end: usize, offsets: bool) -> Result<()> {
+        if offsets && size > end {
+            writeln!(f, "    /* XXX {}-byte padding */", size - end)?;
+        }
+        write!(f, "}}")?;
+        if offsets {
+            write!(f, " /* size: {} */", size)?;
+        }
+
+        Ok(())
+    }
+
+    /// print bytes of the value as is, memory values are dumped with their addresses
+    pub fn print_raw(&self, name: &str, loc: &TypedValueLoc, buf: &[u8]) -> Result<()> {
+        let mut out = Vec::new();
+
+        self.print_type(&mut out, loc.type_id)?;
+        match loc.location {
+            ValueLoc::Register { register, .. } => writeln!(
+                out,
+                " {} ({} bytes in {})",
+                name,
+                buf.len(),
+                gimli::X86_64::register_name(register).unwrap_or("unknown register")
+            )?,
+            _ => writeln!(out, " {} ({} bytes)", name, buf.len())?,
+        }
+        let address = match loc.location {
+            ValueLoc::Address(address) => Some(address),
+            _ => None,
+        };
+        Self::write_hex_dump(&mut out, address, buf)?;
+
+        print!("{}", std::str::from_utf8(&out)?);
+
+        Ok(())
+    }
+
+    /// lines are prefixed with the address, values without one (registers, constants) are dumped as is
+    pub fn write_hex_dump(f: &mut impl io::Write, address: Option<u64>, buf: &[u8]) -> Result<()> {
+        for (i, line) in buf.chunks(HEX_DUMP_LINE_SIZE).enumerate() {
+            if let Some(address) = address {
+                write!(f, "{:#x}: ", address + (i * HEX_DUMP_LINE_SIZE) as u64)?;
+            }
+            let bytes = line.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>();
+            write!(f, "{}", bytes.join(" "))?;
+            // align ascii column on the last line
+            write!(f, "{}  |", "   ".repeat(HEX_DUMP_LINE_SIZE - line.len()))?;
+            for &byte in line {
+                let c = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f, "|")?;
+        }
 
         Ok(())
     }
@@ -56,7 +342,7 @@ impl<'a, R: gimli::Reader> Printer<'a, R> {
                 self.print_type(f, subtype_id)?;
                 write!(f, "*")?;
             }
-            Type::Array { subtype_id, count } => {
+            Type::Array { subtype_id, count, .. } => {
                 self.print_type(f, subtype_id)?;
                 match count {
                     ArrayCount::Static(count) => write!(f, "[{}]", count)?,
@@ -111,7 +397,12 @@ impl<'a, R: gimli::Reader> Printer<'a, R> {
                 }
             },
             Type::Typedef(name, _) => write!(f, "{}", name)?,
-            Type::FuncDef { name, return_type_id, args } => {
+            Type::FuncDef {
+                name,
+                return_type_id,
+                args,
+                variadic,
+            } => {
                 self.print_type(f, return_type_id)?;
                 write!(f, " ")?;
                 if let Some(name) = name {
@@ -124,6 +415,12 @@ impl<'a, R: gimli::Reader> Printer<'a, R> {
                     }
                     self.print_type(f, arg_type_id)?;
                 }
+                if variadic {
+                    if !args.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "...")?;
+                }
                 write!(f, ")")?;
             }
             Type::Func(subtype_id) => self.print_type(f, subtype_id)?,
@@ -132,70 +429,108 @@ impl<'a, R: gimli::Reader> Printer<'a, R> {
         Ok(())
     }
 
+    fn print_summary(&self, f: &mut impl io::Write, summary: &[SummaryPart], fields: &[Field], buf: &Bytes) -> Result<()> {
+        for part in summary {
+            match part {
+                SummaryPart::Text(text) => write!(f, "{}", text)?,
+                SummaryPart::Field(field_name) => match fields.iter().find(|field| field.name.as_ref() == field_name) {
+                    Some(field) => self.print_value(f, Value::new(field.type_id, buf.slice((field.offset as usize)..)))?,
+                    None => write!(f, "<no field {}>", field_name)?,
+                },
+            }
+        }
+
+        Ok(())
+    }
+
     fn print_value(&self, f: &mut impl io::Write, mut value: Value) -> Result<()> {
         let typ = self.session.get_type_storage().get(value.type_id)?;
 
         match typ {
             Type::Void | Type::Union { .. } | Type::FuncDef { .. } => bail!(DebuggerError::InvalidPath),
-            Type::Base { encoding, size, .. } => {
-                match encoding {
-                    gimli::DW_ATE_boolean => write!(f, "{}", value.buf.get_u8() != 0)?,
-                    gimli::DW_ATE_signed => match size {
-                        1 => write!(f, "{}", value.buf.get_i8())?,
-                        2 => write!(f, "{}", value.buf.get_i16_ne())?,
-                        4 => write!(f, "{}", value.buf.get_i32_ne())?,
-                        8 => write!(f, "{}", value.buf.get_i64_ne())?,
-                        _ => bail!("unsupported byte size"),
-                    },
-                    gimli::DW_ATE_unsigned => match size {
-                        1 => write!(f, "{}", value.buf.get_u8())?,
-                        2 => write!(f, "{}", value.buf.get_u16_ne())?,
-                        4 => write!(f, "{}", value.buf.get_u32_ne())?,
-                        8 => write!(f, "{}", value.buf.get_u64_ne())?,
-                        _ => bail!("unsupported byte size"),
-                    },
-                    gimli::DW_ATE_float => match size {
-                        4 => write!(f, "{}", value.buf.get_f32_ne())?,
-                        8 => write!(f, "{}", value.buf.get_f64_ne())?,
-                        _ => bail!("unsupported byte size"),
-                    },
-                    _ => bail!("unsupported encoding"),
-                };
-            }
-            Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) | Type::Typedef(_, subtype_id) => {
-                self.print_value(f, Value::new(subtype_id, value.buf))?
-            }
+            Type::Base { encoding, size, .. } => self.print_base(f, encoding, size, value.buf)?,
+            Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) => self.print_value(f, Value::new(subtype_id, value.buf))?,
+            // summary of anonymous struct is registered by typedef name
+            Type::Typedef(name, subtype_id) => match (self.session.get_summary_format(&name), self.session.get_type_storage().unwind_type(subtype_id)?) {
+                (Some(summary), Type::Struct { fields, .. }) => self.print_summary(f, &summary, &fields, &value.buf)?,
+                _ => self.print_value(f, Value::new(subtype_id, value.buf))?,
+            },
             Type::Pointer(_) => {
                 let ptr = value.buf.get_u64_ne();
                 if ptr == 0 {
                     return Ok(write!(f, "null")?);
                 }
 
-                write!(f, "{:#x}", ptr)?;
+                match self.format {
+                    Some(Format::Decimal) => write!(f, "{}", ptr)?,
+                    _ => write!(f, "{:#x}", ptr)?,
+                }
             }
-            Type::String(_) => {
+            Type::String(subtype_id) => {
                 let ptr = value.buf.get_u64_ne();
-                let s = self.session.read_c_string(ptr)?;
-                write!(f, "{:?}", s)?;
+                if ptr == 0 {
+                    return Ok(write!(f, "null")?);
+                }
+
+                let settings = self.session.get_print_settings();
+                let truncated = match self.session.get_type_size(subtype_id)? {
+                    1 => {
+                        let (s, truncated) = self.session.read_c_string(ptr, settings.string_length)?;
+                        if settings.escape_strings {
+                            write!(f, "{:?}", String::from_utf8_lossy(&s))?;
+                        } else {
+                            write!(f, "\"")?;
+                            f.write_all(&s)?;
+                            write!(f, "\"")?;
+                        }
+                        truncated
+                    }
+                    char_size => {
+                        let (s, truncated) = self.session.read_wide_string(ptr, char_size, settings.string_length)?;
+                        if settings.escape_strings {
+                            write!(f, "{:?}", s)?;
+                        } else {
+                            write!(f, "\"{}\"", s)?;
+                        }
+                        truncated
+                    }
+                };
+                if truncated {
+                    write!(f, "...")?;
+                }
             }
-            Type::Array { subtype_id, count } => {
+            Type::Array { subtype_id, count, bit_stride } => {
                 let count = match count {
                     ArrayCount::Flexible => return Ok(write!(f, "[...]")?),
                     _ => self.session.get_array_count(count)?,
                 };
                 let subtype_size = self.session.get_type_size(subtype_id)?;
+                let bit_stride = self.session.get_array_bit_stride(subtype_id, bit_stride)?;
+                let is_signed = matches!(
+                    self.session.get_type_storage().unwind_type(subtype_id)?,
+                    Type::Base {
+                        encoding: gimli::DW_ATE_signed | gimli::DW_ATE_signed_char,
+                        ..
+                    } | Type::Enum {
+                        encoding: gimli::DW_ATE_signed,
+                        ..
+                    }
+                );
 
                 write!(f, "[")?;
                 for i in 0..count {
                     if i != 0 {
                         write!(f, ", ")?;
                     }
-                    let offset = i * subtype_size;
-                    self.print_value(f, Value::new(subtype_id, value.buf.slice(offset..offset + subtype_size)))?;
+                    let element = get_array_element(&value.buf, subtype_size, i * bit_stride, bit_stride, is_signed);
+                    self.print_value(f, Value::new(subtype_id, element))?;
                 }
                 write!(f, "]")?;
             }
-            Type::Struct { fields, .. } => {
+            Type::Struct { name, fields, .. } => {
+                if let Some(summary) = name.and_then(|name| self.session.get_summary_format(&name)) {
+                    return self.print_summary(f, &summary, &fields, &value.buf);
+                }
                 if fields.is_empty() {
                     return Ok(write!(f, "{{}}")?);
                 }
@@ -212,30 +547,7 @@ impl<'a, R: gimli::Reader> Printer<'a, R> {
 
                 write!(f, " }}")?;
             }
-            Type::Enum { encoding, size, variants, .. } => {
-                let enum_value = match encoding {
-                    gimli::DW_ATE_signed => match size {
-                        1 => value.buf.get_i8() as i64,
-                        2 => value.buf.get_i16_ne() as i64,
-                        4 => value.buf.get_i32_ne() as i64,
-                        8 => value.buf.get_i64_ne(),
-                        _ => bail!("invalid enum subtype byte size"),
-                    },
-                    gimli::DW_ATE_unsigned => match size {
-                        1 => value.buf.get_u8() as i64,
-                        2 => value.buf.get_u16_ne() as i64,
-                        4 => value.buf.get_u32_ne() as i64,
-                        8 => value.buf.get_u64_ne() as i64,
-                        _ => bail!("invalid enum subtype byte size"),
-                    },
-                    _ => bail!("invalid enum subtype encoding"),
-                };
-
-                match variants.iter().find(|&variant| variant.value == enum_value) {
-                    Some(variant) => write!(f, "{}", variant.name)?,
-                    None => write!(f, "{}", enum_value)?,
-                };
-            }
+            Type::Enum { encoding, size, variants, .. } => self.print_enum(f, encoding, size, &variants, value.buf)?,
             Type::Func(_) => {
                 let ptr = value.buf.get_u64_ne();
                 if ptr == 0 {
@@ -243,12 +555,159 @@ impl<'a, R: gimli::Reader> Printer<'a, R> {
                 }
 
                 match self.session.get_loc_finder().find_func_by_address(ptr) {
-                    Some(func_name) => write!(f, "{}", func_name)?,
-                    None => write!(f, "{:#x}", ptr)?,
+                    Some(func_name) if self.format.is_none() => write!(f, "{}", func_name)?,
+                    _ if self.format == Some(Format::Decimal) => write!(f, "{}", ptr)?,
+                    _ => write!(f, "{:#x}", ptr)?,
                 }
             }
         };
 
         Ok(())
     }
+
+    /// scalars are formatted here whether they are printed on their own or as members of arrays and structs
+    fn print_base(&self, f: &mut impl io::Write, encoding: gimli::DwAte, size: u16, mut buf: Bytes) -> Result<()> {
+        match encoding {
+            gimli::DW_ATE_boolean => write!(f, "{}", buf.get_u8() != 0)?,
+            gimli::DW_ATE_signed | gimli::DW_ATE_signed_char => match size {
+                1 => self.print_int(f, buf.get_i8())?,
+                2 => self.print_int(f, buf.get_i16_ne())?,
+                4 => self.print_int(f, buf.get_i32_ne())?,
+                8 => self.print_int(f, buf.get_i64_ne())?,
+                _ => bail!("unsupported byte size"),
+            },
+            gimli::DW_ATE_unsigned | gimli::DW_ATE_unsigned_char | gimli::DW_ATE_UTF => match size {
+                1 => self.print_int(f, buf.get_u8())?,
+                2 => self.print_int(f, buf.get_u16_ne())?,
+                4 => self.print_int(f, buf.get_u32_ne())?,
+                8 => self.print_int(f, buf.get_u64_ne())?,
+                _ => bail!("unsupported byte size"),
+            },
+            gimli::DW_ATE_float => match size {
+                4 => write!(f, "{}", buf.get_f32_ne())?,
+                8 => write!(f, "{}", buf.get_f64_ne())?,
+                _ => bail!("unsupported byte size"),
+            },
+            _ => bail!(DebuggerError::UnsupportedEncoding(encoding)),
+        };
+
+        Ok(())
+    }
+
+    fn print_enum(&self, f: &mut impl io::Write, encoding: gimli::DwAte, size: u16, variants: &[EnumVariant], mut buf: Bytes) -> Result<()> {
+        let enum_value = match encoding {
+            gimli::DW_ATE_signed => match size {
+                1 => buf.get_i8() as i64,
+                2 => buf.get_i16_ne() as i64,
+                4 => buf.get_i32_ne() as i64,
+                8 => buf.get_i64_ne(),
+                _ => bail!("invalid enum subtype byte size"),
+            },
+            gimli::DW_ATE_unsigned => match size {
+                1 => buf.get_u8() as i64,
+                2 => buf.get_u16_ne() as i64,
+                4 => buf.get_u32_ne() as i64,
+                8 => buf.get_u64_ne() as i64,
+                _ => bail!("invalid enum subtype byte size"),
+            },
+            _ => bail!("invalid enum subtype encoding"),
+        };
+
+        match variants.iter().find(|&variant| variant.value == enum_value) {
+            Some(variant) if self.format.is_none() => write!(f, "{}", variant.name)?,
+            // keep two's complement at enum width
+            _ if self.format == Some(Format::Hex) => write!(f, "{:#x}", enum_value as u64 & (u64::MAX >> (64 - size as u32 * 8)))?,
+            _ if encoding == gimli::DW_ATE_unsigned => write!(f, "{}", enum_value as u64)?,
+            _ => write!(f, "{}", enum_value)?,
+        };
+
+        Ok(())
+    }
+
+    /// signed values in hex and octal are printed as two's complement of their width, like C does
+    fn print_int<T: fmt::Display + fmt::LowerHex + fmt::Octal + Default + PartialEq>(&self, f: &mut impl io::Write, value: T) -> Result<()> {
+        let radix = match self.format {
+            Some(Format::Hex) => Radix::Hex,
+            Some(Format::Decimal) => Radix::Decimal,
+            _ => self.session.get_print_settings().radix,
+        };
+
+        match radix {
+            Radix::Hex => write!(f, "{:#x}", value)?,
+            Radix::Decimal => write!(f, "{}", value)?,
+            // C octal literal
+            Radix::Octal if value == T::default() => write!(f, "0")?,
+            Radix::Octal => write!(f, "0{:o}", value)?,
+        };
+
+        Ok(())
+    }
+}
+
+/// element of packed array could start in the middle of a byte and take less bits than its type,
+/// such element is copied into a buffer of the type size, signed integers are sign extended
+fn get_array_element(buf: &Bytes, size: usize, bit_offset: usize, bit_stride: usize, is_signed: bool) -> Bytes {
+    let bit_size = bit_stride.min(size * 8);
+    if bit_offset.is_multiple_of(8) && bit_size == size * 8 {
+        let offset = bit_offset / 8;
+        return buf.slice(offset..offset + size);
+    }
+
+    let get_bit = |bytes: &[u8], bit: usize| bytes[bit / 8] >> (bit % 8) & 1 == 1;
+    let mut element = vec![0u8; size];
+    for bit in 0..bit_size {
+        if get_bit(buf, bit_offset + bit) {
+            element[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    if is_signed && bit_size > 0 && get_bit(&element, bit_size - 1) {
+        for bit in bit_size..size * 8 {
+            element[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    Bytes::from(element)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_summary_format() {
+        assert_eq!(
+            parse_summary_format("{len} items @ {data}").unwrap(),
+            [
+                SummaryPart::Field("len".to_string()),
+                SummaryPart::Text(" items @ ".to_string()),
+                SummaryPart::Field("data".to_string()),
+            ]
+        );
+        assert_eq!(parse_summary_format("{{x}}").unwrap(), [SummaryPart::Text("{x}".to_string())]);
+        assert_eq!(parse_summary_format("").unwrap(), []);
+        assert!(parse_summary_format("{len").is_err());
+        assert!(parse_summary_format("len}").is_err());
+        assert!(parse_summary_format("{}").is_err());
+        assert!(parse_summary_format("{a.b}").is_err());
+    }
+
+    #[test]
+    fn test_get_array_element() {
+        let buf = Bytes::from_static(&[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(get_array_element(&buf, 2, 16, 16, false).as_ref(), [0x03, 0x04]);
+        // byte stride smaller than element
+        assert_eq!(get_array_element(&buf, 2, 8, 8, false).as_ref(), [0x02, 0x00]);
+
+        // 4 bit elements 0x1, 0x0, 0x2, 0x0, 0x3, ...
+        assert_eq!(get_array_element(&buf, 1, 0, 4, false).as_ref(), [0x01]);
+        assert_eq!(get_array_element(&buf, 1, 4, 4, false).as_ref(), [0x00]);
+        assert_eq!(get_array_element(&buf, 1, 8, 4, false).as_ref(), [0x02]);
+
+        // 3 bit elements crossing bytes, 0b111 is -1
+        let buf = Bytes::from_static(&[0b1100_0000, 0b0000_0001]);
+        assert_eq!(get_array_element(&buf, 1, 6, 3, false).as_ref(), [0b111]);
+        assert_eq!(get_array_element(&buf, 4, 6, 3, true).as_ref(), [0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(get_array_element(&buf, 1, 0, 3, true).as_ref(), [0x00]);
+    }
 }
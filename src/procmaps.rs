@@ -0,0 +1,141 @@
+use std::fs;
+
+use anyhow::{anyhow, Result};
+use nix::unistd::Pid;
+
+/// memory region of the child, line of `/proc/<pid>/maps`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryMapping {
+    pub start: u64,
+    pub end: u64,
+    /// `rwxp` like string, last char is `p` for private and `s` for shared mappings
+    pub perms: String,
+    pub offset: u64,
+    /// file path or pseudo path like `[heap]` and `[stack]`, anonymous mappings have no pathname
+    pub pathname: Option<String>,
+}
+
+impl MemoryMapping {
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_readable(&self) -> bool {
+        self.perms.starts_with('r')
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.perms[1..].starts_with('w')
+    }
+}
+
+/// checks that every byte of `[address, address + size)` is in a readable mapping, range could span adjacent mappings.
+/// Mappings must be sorted by address
+pub fn is_readable(mappings: &[MemoryMapping], address: u64, size: u64) -> bool {
+    let Some(end) = address.checked_add(size) else {
+        return false;
+    };
+
+    let mut pos = address;
+    for mapping in mappings.iter().filter(|mapping| mapping.is_readable()) {
+        if pos >= end {
+            break;
+        }
+        if mapping.start <= pos && pos < mapping.end {
+            pos = mapping.end;
+        }
+    }
+
+    pos >= end
+}
+
+/// mappings are sorted by address
+pub fn read(pid: Pid) -> Result<Vec<MemoryMapping>> {
+    parse(&fs::read_to_string(format!("/proc/{}/maps", pid))?)
+}
+
+pub fn parse(procmaps: &str) -> Result<Vec<MemoryMapping>> {
+    procmaps.lines().map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> Result<MemoryMapping> {
+    // pathname is padded with spaces and can contain spaces itself
+    let mut parts = line.splitn(6, ' ');
+    let (range, perms, offset) = match (parts.next(), parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(range), Some(perms), Some(offset), Some(_dev), Some(_inode)) if perms.len() == 4 => (range, perms, offset),
+        _ => return Err(anyhow!("invalid proc maps")),
+    };
+    let pathname = parts.next().map(str::trim_start).filter(|pathname| !pathname.is_empty());
+
+    let (start, end) = range.split_once('-').ok_or(anyhow!("invalid proc maps"))?;
+
+    Ok(MemoryMapping {
+        start: u64::from_str_radix(start, 16)?,
+        end: u64::from_str_radix(end, 16)?,
+        perms: perms.to_string(),
+        offset: u64::from_str_radix(offset, 16)?,
+        pathname: pathname.map(String::from),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mappings = parse(
+            "555555554000-555555555000 r--p 00000000 08:01 1234                       /tmp/my prog
+555555555000-555555556000 r-xp 00001000 08:01 1234                       /tmp/my prog
+555555559000-55555557a000 rw-p 00000000 00:00 0                          [heap]
+7ffff7fb6000-7ffff7fb9000 rw-p 00000000 00:00 0
+7ffffffde000-7ffffffff000 rw-p 00000000 00:00 0                          [stack]
+",
+        )
+        .unwrap();
+
+        assert_eq!(mappings.len(), 5);
+        assert_eq!(
+            mappings[1],
+            MemoryMapping {
+                start: 0x555555555000,
+                end: 0x555555556000,
+                perms: "r-xp".to_string(),
+                offset: 0x1000,
+                pathname: Some("/tmp/my prog".to_string()),
+            }
+        );
+        assert!(mappings[1].is_readable() && !mappings[1].is_writable());
+        assert_eq!(mappings[2].pathname.as_deref(), Some("[heap]"));
+        assert_eq!(mappings[2].size(), 0x21000);
+        assert!(mappings[3].pathname.is_none());
+        assert!(mappings[4].is_writable());
+    }
+
+    #[test]
+    fn test_is_readable() {
+        let mappings = parse(
+            "555555554000-555555555000 r--p 00000000 08:01 1234                       /tmp/prog
+555555555000-555555556000 r-xp 00001000 08:01 1234                       /tmp/prog
+555555556000-555555557000 ---p 00000000 00:00 0
+555555557000-555555558000 rw-p 00000000 00:00 0
+",
+        )
+        .unwrap();
+
+        assert!(is_readable(&mappings, 0x555555554ff0, 0x10));
+        assert!(is_readable(&mappings, 0x555555554ff0, 0x20)); // spans two mappings
+        assert!(is_readable(&mappings, 0x555555557000, 0x1000));
+        assert!(is_readable(&mappings, 0x555555557000, 0));
+        assert!(!is_readable(&mappings, 0x555555555ff0, 0x20)); // runs into unreadable mapping
+        assert!(!is_readable(&mappings, 0x555555557ff0, 0x20)); // runs past the last mapping
+        assert!(!is_readable(&mappings, 0x10, 4));
+        assert!(!is_readable(&mappings, u64::MAX, 2));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse("555555554000 r--p 00000000 08:01 1234").is_err());
+        assert!(parse("555555554000-555555555000 r--p").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use std::io::{BufRead, IsTerminal};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use rustyline::error::ReadlineError;
+use rustyline::{Config, DefaultEditor};
+
+use crate::consts::HISTORY_SIZE;
+use crate::history;
+
+const PROMPT: &str = "> ";
+
+/// line editing and history are for interactive input only, so piped commands produce clean output.
+/// Entered commands are saved to the history dotfile, the last HISTORY_SIZE of them are kept
+pub struct LineReader {
+    editor: Option<DefaultEditor>,
+    history_path: Option<PathBuf>,
+}
+
+impl LineReader {
+    pub fn new() -> Result<Self> {
+        let history_path = history::history_path();
+
+        let editor = if std::io::stdin().is_terminal() {
+            let config = Config::builder().max_history_size(HISTORY_SIZE)?.build();
+            let mut editor = DefaultEditor::with_config(config)?;
+            if let Some(path) = history_path.as_ref() {
+                // dotfile has aliases too, so history is read by ourselves
+                let (commands, _) = history::read(path);
+                for command in commands {
+                    editor.add_history_entry(command)?;
+                }
+            }
+            Some(editor)
+        } else {
+            None
+        };
+
+        Ok(Self { editor, history_path })
+    }
+
+    /// None means EOF, ctrl-c cancels the line and gives an empty one
+    pub fn readline(&mut self) -> Result<Option<String>> {
+        let line = match self.editor.as_mut() {
+            Some(editor) => match editor.readline(PROMPT) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => return Ok(Some(String::new())),
+                Err(ReadlineError::Eof) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            },
+            None => {
+                let mut buf = String::new();
+                if std::io::stdin().lock().read_line(&mut buf)? == 0 {
+                    return Ok(None);
+                }
+                buf
+            }
+        };
+
+        self.add_history_entry(line.trim())?;
+
+        Ok(Some(line))
+    }
+
+    fn add_history_entry(&mut self, line: &str) -> Result<()> {
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        let Some(editor) = self.editor.as_mut() else {
+            return Ok(());
+        };
+
+        editor.add_history_entry(line)?;
+
+        // saved after every command, so history survives a crash of the debugger
+        if let Some(path) = self.history_path.as_ref() {
+            history::save_commands(path, editor.history().iter())?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,30 +1,52 @@
 use std::borrow::Cow;
-use std::cell::{Cell, RefCell};
+use std::cell::{Cell, Ref, RefCell};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fs;
-use std::io::{self, Read, Seek, Write};
+use std::io::{self, IoSliceMut};
+use std::mem;
+use std::os::unix::fs::FileExt;
+use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
 
+use crate::abi::{self, EightbyteClass, ReturnClass};
 use crate::breakpoint::Breakpoint;
-use crate::consts::{FUNC_PROLOGUE_MAGIC_BYTES, WORD_SIZE};
+use crate::checkpoint::{Checkpoint, MemoryRegion};
+use crate::condition::Condition;
+use crate::consts::{
+    CALLEE_SAVED_REGISTERS, ENDBR64_BYTES, FUNC_PROLOGUES, INT3, MAIN_FUNC_NAME, MAX_FUNC_PROLOGUE_SIZE, SIGRETURN_TRAMPOLINE_BYTES, WORD_SIZE,
+};
 use crate::context::Context;
+use crate::display::Display;
+use crate::dwarf_parser::DwarfParser;
 use crate::error::DebuggerError;
-use crate::loc_finder::{EntryRef, LocFinder, VarRef};
-use crate::location::{TypedValueLoc, ValueLoc};
+use crate::image::Image;
+use crate::interrupt;
+use crate::launcher::Launcher;
+use crate::loc_finder::{EntryRef, LocFinder, VarKind, VarRef};
+use crate::location::{self, TypedValueLoc, ValueLoc};
 use crate::path::{Path, PostfixOperator, PrefixOperator};
+use crate::printer::{PrintSettings, Printer, Radix, SummaryPart};
+use crate::procmaps::{self, MemoryMapping};
 use crate::trap::Trap;
 use crate::types::{ArrayCount, Type, TypeId, TypeStorage};
-use crate::unwinder::Unwinder;
+use crate::utils::number_parser::parse_unsigned;
 use crate::var::{Value, Var};
+use crate::watchpoint::{self, Watchpoint, WatchpointHit, WATCHPOINT_SLOTS};
+use crate::xstate::XState;
 
 use anyhow::{anyhow, bail, Result};
 use bytes::{Buf, Bytes};
+use nix::errno::Errno;
+use nix::sys::signal::{self, Signal};
+use nix::sys::uio::{self, RemoteIoVec};
 use nix::sys::{ptrace, wait};
 use nix::unistd::Pid;
 
 const READ_MEM_BUF_SIZE: usize = 512;
+const CALL_ARGS_REGISTERS_COUNT: usize = 6; // rdi, rsi, rdx, rcx, r8, r9
+const RED_ZONE_SIZE: u64 = 128;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SessionState {
@@ -33,40 +55,162 @@ pub enum SessionState {
     Exited,
 }
 
+/// how the program ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitStatus {
+    Code(i32),
+    Signal(Signal),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FollowForkMode {
+    Parent,
+    Child,
+}
+
+impl TryFrom<&str> for FollowForkMode {
+    type Error = DebuggerError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "parent" => Ok(FollowForkMode::Parent),
+            "child" => Ok(FollowForkMode::Child),
+            _ => Err(DebuggerError::InvalidValue),
+        }
+    }
+}
+
+/// units parsed on demand for reading variables
+type UnitCache<R> = HashMap<gimli::DebugInfoOffset<<R as gimli::Reader>::Offset>, Rc<gimli::Unit<R>>>;
+
 pub struct DebugSession<R: gimli::Reader> {
     state: Cell<SessionState>,
-    dwarf: gimli::Dwarf<R>,
-    unwinder: Unwinder<R>,
+    exit_status: Cell<Option<ExitStatus>>, // none if the program was killed by us
+    main_func: Option<Rc<str>>,            // entry function set by user, kept for new images
+    launcher: Launcher,
+    image: Image<R>,
     loc_finder: LocFinder<R>,
     type_storage: TypeStorage<R>,
+    units: RefCell<UnitCache<R>>,
     child: process::Child,
+    pid: Cell<Pid>,                     // traced process, it's not our child after following fork
+    vfork_parent: Cell<Option<Pid>>,    // parent of the followed vfork child, kept stopped till the child leaves the shared memory
+    procmem: RefCell<Option<fs::File>>, // /proc/<pid>/mem, opened on first use and dropped once the process or its memory is gone
     base_address: u64,
-    breakpoints: HashMap<u64, Breakpoint>,
+    follow_fork_mode: Cell<FollowForkMode>,
+    exec_path: RefCell<Option<PathBuf>>,
+    print_settings: Cell<PrintSettings>,
+    input_radix: Cell<Radix>,                                      // base of numbers without 0x prefix in `set`
+    stop_context: Cell<usize>,                                     // source lines printed on stop, 0 disables it
+    substitute_paths: RefCell<Vec<(PathBuf, PathBuf)>>,            // source path prefix -> replacement
+    summary_formats: RefCell<HashMap<Rc<str>, Rc<[SummaryPart]>>>, // struct or typedef name -> summary
+    breakpoints: RefCell<HashMap<u64, Breakpoint>>,
     traps: RefCell<HashMap<u64, Trap>>,
+    pending_signal: Cell<Option<Signal>>,
+    stepping_over: Cell<Option<u64>>,   // breakpoint lifted while single stepping over it, enabled again once ip leaves it
+    single_stepping: Cell<bool>,        // child was resumed with ptrace::step, fork event resumes it the same way
+    breakpoint_hits: RefCell<Vec<u64>>, // addresses of breakpoints hit since the program started, replayed by reverse-continue
+    selected_frame: Cell<usize>,        // variables are read in this frame, 0 is the innermost one
+    frames: RefCell<Vec<Context>>,      // unwound frames, innermost first, filled on demand until the program moves
+    checkpoints: Vec<Checkpoint>,
+    displays: Vec<Display>, // ordered by id
+    next_display_id: usize,
+    watchpoints: Vec<Watchpoint>, // ordered by id
+    next_breakpoint_id: usize,
+    next_watchpoint_id: usize,
+    watchpoint_hit: RefCell<Option<WatchpointHit>>, // watchpoint which stopped the program on the last wait
+    stopped_after_step_over: Cell<bool>,            // watchpoint was hit by the instruction under the breakpoint, nothing to wait for
 }
 
 impl<R: gimli::Reader> DebugSession<R> {
-    pub fn new(
-        child: process::Child,
-        dwarf: gimli::Dwarf<R>,
-        loc_finder: LocFinder<R>,
-        type_storage: TypeStorage<R>,
-        unwinder: Unwinder<R>,
-        base_address: u64,
-    ) -> Self {
-        Self {
+    pub fn start(launcher: Launcher, image: Image<R>) -> Result<Self> {
+        let mut child = launcher.spawn()?;
+        let pid = Self::get_child_pid(&child);
+        let base_address = image.get_base_address(pid)?;
+        log::trace!("base address {:#x}", base_address);
+
+        let (loc_finder, type_storage) = match DwarfParser::parse(&image.dwarf, base_address) {
+            Ok(result) => result,
+            Err(e) => {
+                // don't leave the program running without tracer
+                child.kill()?;
+                child.wait()?;
+                return Err(e);
+            }
+        };
+
+        let mut session = Self {
             state: Cell::new(SessionState::Started),
-            dwarf,
-            unwinder,
+            exit_status: Cell::new(None),
+            main_func: None,
+            launcher,
+            image,
             loc_finder,
             type_storage,
+            units: RefCell::new(HashMap::new()),
             child,
+            pid: Cell::new(pid),
+            vfork_parent: Cell::new(None),
+            procmem: RefCell::new(None),
             base_address,
-            breakpoints: HashMap::new(),
+            follow_fork_mode: Cell::new(FollowForkMode::Parent),
+            exec_path: RefCell::new(None),
+            print_settings: Cell::new(PrintSettings::default()),
+            input_radix: Cell::new(Radix::Decimal),
+            stop_context: Cell::new(0),
+            substitute_paths: RefCell::new(Vec::new()),
+            summary_formats: RefCell::new(HashMap::new()),
+            breakpoints: RefCell::new(HashMap::new()),
             traps: RefCell::new(HashMap::new()),
+            pending_signal: Cell::new(None),
+            stepping_over: Cell::new(None),
+            single_stepping: Cell::new(false),
+            breakpoint_hits: RefCell::new(Vec::new()),
+            selected_frame: Cell::new(0),
+            frames: RefCell::new(Vec::new()),
+            checkpoints: Vec::new(),
+            displays: Vec::new(),
+            next_display_id: 1,
+            watchpoints: Vec::new(),
+            next_breakpoint_id: 1,
+            next_watchpoint_id: 1,
+            watchpoint_hit: RefCell::new(None),
+            stopped_after_step_over: Cell::new(false),
+        };
+        session.init_main_func();
+
+        Ok(session)
+    }
+
+    /// programs without `main` (custom entry, `wmain`) use function containing the entry point, unless one is set
+    fn init_main_func(&mut self) {
+        let func_name = match &self.main_func {
+            Some(func_name) => Some(func_name.clone()),
+            None if self.loc_finder.find_func(MAIN_FUNC_NAME).is_some() => None,
+            None => self.loc_finder.find_func_by_address(self.base_address + self.image.entry_address),
+        };
+
+        if let Some(func_name) = func_name {
+            log::trace!("main func is {}", func_name);
+            if !self.loc_finder.set_main_func(&func_name) {
+                log::warn!("main func {} is not found in {}", func_name, self.image.path.display());
+            }
         }
     }
 
+    pub fn set_main_func(&mut self, func_name: &str) -> Result<()> {
+        if !self.loc_finder.set_main_func(func_name) {
+            bail!(DebuggerError::FuncNotFound(func_name.to_string()));
+        }
+        self.main_func = Some(Rc::from(func_name));
+
+        Ok(())
+    }
+
+    pub fn get_prog(&self) -> &std::path::Path {
+        self.launcher.get_prog()
+    }
+
     pub fn get_type_storage(&self) -> &TypeStorage<R> {
         &self.type_storage
     }
@@ -76,15 +220,93 @@ impl<R: gimli::Reader> DebugSession<R> {
     }
 
     fn child_pid(&self) -> Pid {
-        Pid::from_raw(self.child.id() as libc::pid_t)
+        self.pid.get()
+    }
+
+    fn get_child_pid(child: &process::Child) -> Pid {
+        Pid::from_raw(child.id() as libc::pid_t)
+    }
+
+    pub fn get_print_settings(&self) -> PrintSettings {
+        self.print_settings.get()
+    }
+
+    pub fn set_print_settings(&self, settings: PrintSettings) {
+        self.print_settings.set(settings);
+    }
+
+    pub fn get_input_radix(&self) -> Radix {
+        self.input_radix.get()
+    }
+
+    pub fn set_input_radix(&self, radix: Radix) {
+        self.input_radix.set(radix);
+    }
+
+    pub fn get_stop_context(&self) -> usize {
+        self.stop_context.get()
+    }
+
+    pub fn set_stop_context(&self, lines: usize) {
+        self.stop_context.set(lines);
+    }
+
+    pub fn add_substitute_path(&self, from: PathBuf, to: PathBuf) {
+        self.substitute_paths.borrow_mut().push((from, to));
+    }
+
+    /// empty summary removes it
+    pub fn set_summary_format(&self, type_name: &str, summary: Vec<SummaryPart>) {
+        let mut summary_formats = self.summary_formats.borrow_mut();
+        if summary.is_empty() {
+            summary_formats.remove(type_name);
+        } else {
+            summary_formats.insert(Rc::from(type_name), Rc::from(summary));
+        }
+    }
+
+    pub fn get_summary_format(&self, type_name: &str) -> Option<Rc<[SummaryPart]>> {
+        self.summary_formats.borrow().get(type_name).cloned()
+    }
+
+    /// path of source file with the first matching substitution applied
+    pub fn get_source_path(&self, filepath: &str) -> Option<PathBuf> {
+        let source_path = self.loc_finder.find_source_path(filepath)?;
+
+        for (from, to) in self.substitute_paths.borrow().iter() {
+            if let Ok(rest) = source_path.strip_prefix(from) {
+                return Some(to.join(rest));
+            }
+        }
+
+        Some(source_path.to_path_buf())
+    }
+
+    pub fn set_follow_fork_mode(&self, mode: FollowForkMode) {
+        self.follow_fork_mode.set(mode);
+    }
+
+    /// takes effect once the program is launched again (after kill)
+    pub fn set_disable_randomization(&self, disable_randomization: bool) {
+        self.launcher.set_disable_randomization(disable_randomization);
+    }
+
+    /// program executed by the child, its image must be loaded
+    pub fn take_exec_path(&self) -> Option<PathBuf> {
+        self.exec_path.borrow_mut().take()
     }
 
     pub fn get_state(&self) -> SessionState {
         self.state.get()
     }
 
+    pub fn get_exit_status(&self) -> Option<ExitStatus> {
+        self.exit_status.get()
+    }
+
     pub fn run(&self) -> Result<()> {
         ptrace::cont(self.child_pid(), None)?;
+        self.single_stepping.set(false);
 
         self.state.set(SessionState::Running);
 
@@ -92,46 +314,555 @@ impl<R: gimli::Reader> DebugSession<R> {
     }
 
     pub fn stop(&mut self) -> Result<()> {
+        // don't leave int3 behind in case the child survives
+        self.restore_bytecode()?;
+        if self.child_pid() != Self::get_child_pid(&self.child) {
+            signal::kill(self.child_pid(), Signal::SIGKILL)?;
+            wait::waitpid(self.child_pid(), None)?;
+        }
+        self.release_vfork_parent()?;
         self.child.kill()?;
+        self.child.wait()?;
 
+        self.procmem.take();
         self.state.set(SessionState::Exited);
 
         Ok(())
     }
 
-    pub fn wait(&self) -> Result<()> {
+    /// lets the program run on its own, breakpoints are kept for the next run
+    pub fn detach(&mut self) -> Result<()> {
+        // int3 or watchpoint left behind would kill the program with SIGTRAP
+        self.restore_bytecode()?;
+        self.write_debug_register(watchpoint::DR7, 0)?;
+        ptrace::detach(self.child_pid(), self.pending_signal.take())?;
+        self.release_vfork_parent()?;
+
+        self.procmem.take();
+        self.stepping_over.set(None);
+        self.state.set(SessionState::Exited);
+
+        Ok(())
+    }
+
+    /// kills the child if it's alive and spawns the program again, keeping breakpoints
+    pub fn restart(&mut self) -> Result<()> {
+        if self.get_state() != SessionState::Exited {
+            self.stop()?;
+        } else if self.child_pid() != Self::get_child_pid(&self.child) {
+            // followed fork child exited, but the program is still there
+            self.child.kill()?;
+            self.child.wait()?;
+        }
+
+        let child = self.launcher.spawn()?;
+        self.pid.set(Self::get_child_pid(&child));
+        self.procmem.take();
+        self.child = child;
+        self.exit_status.set(None);
+        self.traps.borrow_mut().clear();
+        self.pending_signal.set(None);
+        self.stepping_over.set(None);
+        self.breakpoint_hits.borrow_mut().clear();
+        self.checkpoints.clear(); // memory of the old child is useless
+        self.watchpoints.clear(); // debug registers are gone with the old child
+        self.state.set(SessionState::Started);
+
+        // image was replaced by exec, breakpoints are set after loading the program again
+        if self.image.path != self.launcher.get_prog() {
+            *self.exec_path.borrow_mut() = Some(self.launcher.get_prog().to_path_buf());
+            return Ok(());
+        }
+
+        let base_address = self.image.get_base_address(self.child_pid())?;
+        log::trace!("restarted child {}, base address {:#x}", self.child_pid(), base_address);
+
+        if base_address != self.base_address {
+            (self.loc_finder, self.type_storage) = DwarfParser::parse(&self.image.dwarf, base_address)?;
+            self.base_address = base_address;
+            self.init_main_func();
+        }
+
+        self.reset_breakpoints()
+    }
+
+    /// switches to the program executed by the child, breakpoints are set again if they are found in the new program
+    pub fn reload(&mut self, image: Image<R>) -> Result<()> {
+        let base_address = image.get_base_address(self.child_pid())?;
+        log::trace!("loaded {}, base address {:#x}", image.path.display(), base_address);
+
+        (self.loc_finder, self.type_storage) = DwarfParser::parse(&image.dwarf, base_address)?;
+        self.image = image;
+        self.units.borrow_mut().clear();
+        self.base_address = base_address;
+        self.init_main_func();
+        self.traps.borrow_mut().clear(); // old code is gone
+        self.stepping_over.set(None);
+        self.breakpoint_hits.borrow_mut().clear();
+        self.checkpoints.clear();
+        self.watchpoints.clear(); // exec clears debug registers
+
+        self.reset_breakpoints()
+    }
+
+    /// sets breakpoints again in the new code, keeping disabled ones disabled.
+    /// Breakpoint we are stopped at is lifted only until continue, so it's enabled
+    fn reset_breakpoints(&mut self) -> Result<()> {
+        let breakpoints = mem::take(self.breakpoints.get_mut());
+        for breakpoint in breakpoints.into_values() {
+            // ids stay the same in the new program
+            let addr = match self.insert_breakpoint(breakpoint.loc.as_str(), breakpoint.id) {
+                Ok(addr) => addr,
+                Err(e) if matches!(e.downcast_ref::<DebuggerError>(), Some(DebuggerError::LocNotFound)) => {
+                    log::trace!("breakpoint {} is not found in {}", breakpoint.loc, self.image.path.display());
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let new_breakpoint = self.breakpoints.get_mut().get_mut(&addr).unwrap();
+            new_breakpoint.log = breakpoint.log;
+            new_breakpoint.temporary = breakpoint.temporary;
+            if !breakpoint.enabled.get() && !breakpoint.lifted.get() {
+                self.disable_breakpoint(&breakpoint.loc)?;
+            }
+            self.set_breakpoint_commands(&breakpoint.loc, breakpoint.commands)?;
+            self.set_breakpoint_condition(&breakpoint.loc, breakpoint.condition)?;
+        }
+
+        Ok(())
+    }
+
+    fn follow_fork(&self, is_vfork: bool) -> Result<()> {
+        let new_pid = Pid::from_raw(ptrace::getevent(self.child_pid())? as libc::pid_t);
+        wait::waitpid(new_pid, None)?; // new child starts with SIGSTOP
+        log::trace!("child {} forked {}", self.child_pid(), new_pid);
+
+        let (followed_pid, detached_pid) = match self.follow_fork_mode.get() {
+            FollowForkMode::Parent => (self.child_pid(), new_pid),
+            FollowForkMode::Child => (new_pid, self.child_pid()),
+        };
+
+        // forked memory is a copy with our int3. Vfork child shares memory with parent till exec, so it's left as is.
+        // Vfork parent would hit int3 once the child execs, it's detached after cleaning its memory then
+        if !is_vfork {
+            self.clear_traps(detached_pid)?;
+            ptrace::detach(detached_pid, None)?;
+        } else if followed_pid == new_pid {
+            self.vfork_parent.set(Some(detached_pid));
+        } else {
+            ptrace::detach(detached_pid, None)?;
+        }
+
+        self.pid.set(followed_pid);
+        self.procmem.take();
+        // forked child doesn't inherit debug registers
+        if followed_pid == new_pid {
+            self.set_debug_registers()?;
+        }
+        // single step stopped by the fork event hasn't finished yet
+        if self.single_stepping.get() {
+            ptrace::step(followed_pid, None)?;
+        } else {
+            ptrace::cont(followed_pid, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// lets vfork parent go, the followed child doesn't share memory with it anymore
+    fn release_vfork_parent(&self) -> Result<()> {
+        if let Some(pid) = self.vfork_parent.take() {
+            log::trace!("release vfork parent {}", pid);
+            self.clear_traps(pid)?;
+            ptrace::detach(pid, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// writes original bytecode to the process memory without touching breakpoints
+    fn clear_traps(&self, pid: Pid) -> Result<()> {
+        // traps are set on top of breakpoints, so restore them first
+        for (&addr, trap) in self.traps.borrow().iter() {
+            Self::write_first_byte(pid, addr, trap.original_bytecode)?;
+        }
+
+        for breakpoint in self.breakpoints.borrow().values().filter(|breakpoint| breakpoint.enabled.get()) {
+            Self::write_first_byte(pid, breakpoint.addr, breakpoint.original_bytecode.get())?;
+        }
+
+        Ok(())
+    }
+
+    /// registers expression for printing on stop, returns display id
+    pub fn add_display(&mut self, expr: &str) -> usize {
+        let id = self.next_display_id;
+        self.next_display_id += 1;
+        self.displays.push(Display::new(id, expr));
+        id
+    }
+
+    pub fn remove_display(&mut self, id: usize) -> Result<()> {
+        let index = self
+            .displays
+            .iter()
+            .position(|display| display.id == id)
+            .ok_or(DebuggerError::DisplayNotFound)?;
+        self.displays.remove(index);
+        Ok(())
+    }
+
+    pub fn get_displays(&self) -> &[Display] {
+        &self.displays
+    }
+
+    /// watches writes to the variable memory with a free debug register
+    pub fn add_watchpoint(&mut self, path: &Path, expr: &str) -> Result<&Watchpoint> {
+        let loc = self.get_var_loc(path)?;
+        let ValueLoc::Address(address) = loc.location else {
+            bail!(DebuggerError::UnsupportedWatchpoint);
+        };
+        let slot = (0..WATCHPOINT_SLOTS)
+            .find(|&slot| self.watchpoints.iter().all(|watchpoint| watchpoint.slot != slot))
+            .ok_or(DebuggerError::NoFreeWatchpoint)?;
+        let dr7_bits = Watchpoint::get_dr7_bits(slot, address, self.get_type_size(loc.type_id)?).ok_or(DebuggerError::UnsupportedWatchpointSize)?;
+        let value = self.read_typed(address, loc.type_id)?;
+
+        log::trace!("watch {:#x} with dr{}", address, slot);
+        self.write_debug_register(slot, address)?;
+        let dr7 = self.read_debug_register(watchpoint::DR7)?;
+        self.write_debug_register(watchpoint::DR7, dr7 | dr7_bits)?;
+
+        let id = self.next_watchpoint_id;
+        self.next_watchpoint_id += 1;
+        self.watchpoints.push(Watchpoint::new(id, slot, expr, address, value));
+
+        Ok(self.watchpoints.last().unwrap())
+    }
+
+    pub fn remove_watchpoint(&mut self, id: usize) -> Result<()> {
+        let index = self
+            .watchpoints
+            .iter()
+            .position(|watchpoint| watchpoint.id == id)
+            .ok_or(DebuggerError::WatchpointNotFound)?;
+
+        let slot = self.watchpoints[index].slot;
+        let dr7 = self.read_debug_register(watchpoint::DR7)?;
+        self.write_debug_register(watchpoint::DR7, dr7 & !Watchpoint::get_dr7_mask(slot))?;
+        self.write_debug_register(slot, 0)?;
+        self.watchpoints.remove(index);
+
+        Ok(())
+    }
+
+    pub fn get_watchpoint_hit(&self) -> Option<WatchpointHit> {
+        self.watchpoint_hit.borrow().clone()
+    }
+
+    /// programs debug registers of the child with all watchpoints
+    fn set_debug_registers(&self) -> Result<()> {
+        let mut dr7 = 0;
+        for watchpoint in &self.watchpoints {
+            let size = self.get_type_size(watchpoint.type_id)?;
+            self.write_debug_register(watchpoint.slot, watchpoint.address)?;
+            dr7 |= Watchpoint::get_dr7_bits(watchpoint.slot, watchpoint.address, size).ok_or(DebuggerError::UnsupportedWatchpointSize)?;
+        }
+
+        self.write_debug_register(watchpoint::DR7, dr7)
+    }
+
+    /// checks dr6 after sigtrap, returns false if watched memory was written with the same value and the program should go on
+    fn check_watchpoints(&self) -> Result<bool> {
+        let dr6 = self.read_debug_register(watchpoint::DR6)?;
+        // dr6 is never cleared by the cpu
+        self.write_debug_register(watchpoint::DR6, 0)?;
+
+        let Some(watchpoint) = self.watchpoints.iter().find(|watchpoint| dr6 & (1 << watchpoint.slot) != 0) else {
+            return Ok(true);
+        };
+        log::trace!("watchpoint {} is hit", watchpoint.id);
+
+        let new_value = self.read_typed(watchpoint.address, watchpoint.type_id)?;
+        let old_buf = watchpoint.value.replace(new_value.buf.clone());
+        if old_buf == new_value.buf {
+            // single step is done anyway
+            return Ok(dr6 & watchpoint::DR6_SINGLE_STEP != 0);
+        }
+
+        *self.watchpoint_hit.borrow_mut() = Some(WatchpointHit {
+            id: watchpoint.id,
+            expr: watchpoint.expr.clone(),
+            old_value: Value::new(watchpoint.type_id, old_buf),
+            new_value,
+        });
+
+        Ok(true)
+    }
+
+    fn read_debug_register(&self, index: usize) -> Result<u64> {
+        let offset = mem::offset_of!(libc::user, u_debugreg) + index * WORD_SIZE;
+        Ok(ptrace::read_user(self.child_pid(), offset as ptrace::AddressType)? as u64)
+    }
+
+    fn write_debug_register(&self, index: usize, value: u64) -> Result<()> {
+        let offset = mem::offset_of!(libc::user, u_debugreg) + index * WORD_SIZE;
+        ptrace::write_user(self.child_pid(), offset as ptrace::AddressType, value as libc::c_long)?;
+        Ok(())
+    }
+
+    /// saves registers and writable memory, returns checkpoint id
+    pub fn save_checkpoint(&mut self) -> Result<usize> {
+        let regs = ptrace::getregs(self.child_pid())?;
+        let mut regions = Vec::new();
+
+        for (start, end) in self.get_writable_regions()? {
+            let mut data = vec![0; (end - start) as usize];
+            self.read_memory(start, &mut data)?;
+            regions.push(MemoryRegion { address: start, data });
+        }
+
+        log::trace!("saved {} memory regions", regions.len());
+        self.checkpoints
+            .push(Checkpoint::new(regs, regions, self.pending_signal.get(), self.breakpoint_hits.borrow().len()));
+
+        Ok(self.checkpoints.len())
+    }
+
+    /// brings registers and writable memory back, side effects outside of the child memory (I/O, files, etc.) are not undone
+    pub fn restore_checkpoint(&self, id: usize) -> Result<()> {
+        let checkpoint = id
+            .checked_sub(1)
+            .and_then(|index| self.checkpoints.get(index))
+            .ok_or(DebuggerError::CheckpointNotFound)?;
+
+        // saved regions must still be mapped
+        let current_regions = self.get_writable_regions()?;
+        for region in &checkpoint.regions {
+            let region_end = region.address + region.data.len() as u64;
+            if !current_regions.iter().any(|&(start, end)| start <= region.address && region_end <= end) {
+                bail!(DebuggerError::MemoryLayoutChanged);
+            }
+        }
+
+        // breakpoint we are stopped at is lifted until continue, put it back as we are leaving it
+        let ip = self.get_ip()?;
+        if let Some(breakpoint) = self.breakpoints.borrow().get(&ip).filter(|breakpoint| breakpoint.lifted.get()) {
+            self.enable_bp(breakpoint)?;
+        }
+        self.stepping_over.set(None);
+
+        for region in &checkpoint.regions {
+            self.write_memory(region.address, &region.data)?;
+        }
+        ptrace::setregs(self.child_pid(), checkpoint.regs)?;
+        self.pending_signal.set(checkpoint.pending_signal);
+        self.breakpoint_hits.borrow_mut().truncate(checkpoint.breakpoint_hits);
+        self.reset_frames();
+        for watchpoint in &self.watchpoints {
+            *watchpoint.value.borrow_mut() = self.read_typed(watchpoint.address, watchpoint.type_id)?.buf;
+        }
+
+        // act like we stopped at the breakpoint
+        if let Some(breakpoint) = self.breakpoints.borrow().get(&checkpoint.regs.rip) {
+            self.lift_bp(breakpoint)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_memory_mappings(&self) -> Result<Vec<MemoryMapping>> {
+        procmaps::read(self.child_pid())
+    }
+
+    fn get_writable_regions(&self) -> Result<Vec<(u64, u64)>> {
+        Ok(self
+            .get_memory_mappings()?
+            .into_iter()
+            .filter(|mapping| mapping.is_readable() && mapping.is_writable())
+            .map(|mapping| (mapping.start, mapping.end))
+            .collect())
+    }
+
+    fn restore_bytecode(&self) -> Result<()> {
         if self.get_state() == SessionState::Exited {
             return Ok(());
         }
 
-        log::trace!("wait for signal");
+        // traps are set on top of breakpoints, so restore them first
+        let addresses = self.traps.borrow().keys().copied().collect::<Vec<_>>();
+        for addr in addresses {
+            self.remove_trap(addr)?;
+        }
 
-        if let wait::WaitStatus::Exited(_, _) = wait::waitpid(self.child_pid(), None)? {
-            log::trace!("child exited");
-            self.state.set(SessionState::Exited);
+        // flags are kept, so restart sets enabled breakpoints again
+        for breakpoint in self.breakpoints.borrow().values().filter(|breakpoint| breakpoint.enabled.get()) {
+            self.disable_bp(breakpoint)?;
+            breakpoint.enabled.set(true);
+        }
+
+        Ok(())
+    }
+
+    /// waits for the program to stop, breakpoints with false condition are continued silently
+    pub fn wait(&self) -> Result<()> {
+        // cont stopped right after stepping over the breakpoint, the hit is kept for reporting
+        if self.stopped_after_step_over.take() {
             return Ok(());
         }
+        self.watchpoint_hit.take();
+        while !self.wait_stop()? {
+            self.cont()?;
+            if self.stopped_after_step_over.take() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// returns false if the program stopped at a breakpoint with false condition
+    fn wait_stop(&self) -> Result<bool> {
+        if self.get_state() == SessionState::Exited {
+            return Ok(true);
+        }
+
+        log::trace!("wait for signal");
+        // frames change once the program moves
+        self.reset_frames();
+
+        let status = loop {
+            match wait::waitpid(self.child_pid(), None) {
+                Ok(wait::WaitStatus::PtraceEvent(_, _, event)) if event == libc::PTRACE_EVENT_FORK || event == libc::PTRACE_EVENT_VFORK => {
+                    self.follow_fork(event == libc::PTRACE_EVENT_VFORK)?
+                }
+                // ctrl-c caught by us, the child gets it too and stops
+                Err(nix::errno::Errno::EINTR) => (),
+                status => break status?,
+            }
+        };
+
+        match status {
+            wait::WaitStatus::PtraceEvent(_, _, libc::PTRACE_EVENT_EXEC) => {
+                let path = fs::read_link(format!("/proc/{}/exe", self.child_pid()))?;
+                log::trace!("child executed {}", path.display());
+                self.release_vfork_parent()?;
+                *self.exec_path.borrow_mut() = Some(path);
+                // opened file refers to the memory of the old program
+                self.procmem.take();
+                // breakpoint we were stepping over is gone with the old code
+                self.stepping_over.set(None);
+                self.state.set(SessionState::Running);
+                return Ok(true);
+            }
+            wait::WaitStatus::Exited(_, _) | wait::WaitStatus::Signaled(_, _, _) => {
+                log::trace!("child exited");
+                self.release_vfork_parent()?;
+                self.exit_status.set(match status {
+                    wait::WaitStatus::Signaled(_, signal, _) => Some(ExitStatus::Signal(signal)),
+                    wait::WaitStatus::Exited(_, code) => Some(ExitStatus::Code(code)),
+                    _ => None,
+                });
+                self.stepping_over.set(None);
+                self.traps.borrow_mut().clear(); // code of the dead child can't be restored
+                self.procmem.take();
+                self.state.set(SessionState::Exited);
+                return Ok(true);
+            }
+            wait::WaitStatus::Stopped(_, signal) if signal != Signal::SIGTRAP => {
+                // signal will be delivered to the child on the next continue
+                log::trace!("child received signal {:?}", signal);
+                self.pending_signal.set(Some(signal));
+                self.state.set(SessionState::Running);
+                // signal could stop the child before the instruction under the breakpoint is executed
+                if let Some(addr) = self.stepping_over.get() {
+                    if self.get_ip()? != addr {
+                        self.finish_step_over(addr)?;
+                    }
+                }
+                return Ok(true);
+            }
+            _ => (),
+        }
 
         self.state.set(SessionState::Running);
+        if let Some(addr) = self.stepping_over.get() {
+            self.finish_step_over(addr)?;
+        }
         let ip = self.get_ip()?;
         log::trace!("stopped at {:#x}", ip);
+
+        // watchpoint trap comes after the write, so ip is not rewound
+        if !self.watchpoints.is_empty() && !self.check_watchpoints()? {
+            return Ok(false);
+        }
+
+        // single step over one byte instruction (like push rbp) stops right after it, as int3 there would
+        if ptrace::getsiginfo(self.child_pid())?.si_code != libc::SI_KERNEL {
+            return Ok(true);
+        }
         let prev_addr = ip - 1;
 
         if self.traps.borrow().contains_key(&prev_addr) {
             log::trace!("stopped at trap {:#x}", prev_addr);
             self.remove_trap(prev_addr)?;
+            // breakpoint under the trap is stepped over on continue
+            if let Some(breakpoint) = self.breakpoints.borrow().get(&prev_addr) {
+                self.lift_bp(breakpoint)?;
+            }
             self.rewind()?;
-            return Ok(());
+            return Ok(true);
         }
 
-        if let Some(breakpoint) = self.breakpoints.get(&prev_addr) {
-            log::trace!("stopped at breakpoint {}", breakpoint.loc);
-            self.disable_bp(breakpoint)?;
-            self.rewind()?;
-            return Ok(());
+        let is_temporary = match self.breakpoints.borrow().get(&prev_addr) {
+            Some(breakpoint) => {
+                log::trace!("stopped at breakpoint {}", breakpoint.loc);
+                self.lift_bp(breakpoint)?;
+                self.rewind()?;
+                if !self.is_breakpoint_condition_true(breakpoint) {
+                    log::trace!("breakpoint condition is false");
+                    return Ok(false);
+                }
+                if let Some(expr) = &breakpoint.log {
+                    self.print_logpoint(expr);
+                    return Ok(false);
+                }
+                breakpoint.temporary
+            }
+            None => return Ok(true),
+        };
+
+        // temporary breakpoint is gone after the first hit, so it's not replayed by reverse-continue
+        if is_temporary {
+            log::trace!("remove temporary breakpoint {:#x}", prev_addr);
+            self.breakpoints.borrow_mut().remove(&prev_addr);
+        } else {
+            self.breakpoint_hits.borrow_mut().push(prev_addr);
         }
 
-        Ok(())
+        Ok(true)
+    }
+
+    /// variable which can't be printed doesn't stop the program, logpoint is meant to not stop it
+    fn print_logpoint(&self, expr: &str) {
+        if let Err(e) = Path::parse(expr).and_then(|path| Printer::new(self).print(&self.get_var(&path)?)) {
+            println!("can't print {}: {:#}", expr, e);
+        }
+    }
+
+    /// condition which can't be evaluated stops the program, so the breakpoint isn't lost
+    fn is_breakpoint_condition_true(&self, breakpoint: &Breakpoint) -> bool {
+        let Some(condition) = &breakpoint.condition else {
+            return true;
+        };
+
+        match Condition::parse(condition).and_then(|condition| condition.evaluate(self)) {
+            Ok(is_true) => is_true,
+            Err(e) => {
+                log::warn!("can't evaluate breakpoint condition {}: {:#}", condition, e);
+                true
+            }
+        }
     }
 
     pub fn cont(&self) -> Result<()> {
@@ -141,46 +872,267 @@ impl<R: gimli::Reader> DebugSession<R> {
         let ip = self.get_ip()?;
         log::trace!("now at {:#x}", ip);
 
-        if let Some(breakpoint) = self.breakpoints.get(&ip) {
-            log::trace!("stopped at breakpoint {}", breakpoint.loc);
+        if self.breakpoints.borrow().contains_key(&ip) {
+            log::trace!("stopped at breakpoint {:#x}", ip);
 
-            self.single_step()?;
+            self.start_step_over(ip)?;
+            // signal stopped the child before the step, it's delivered by the next step
+            while self.stepping_over.get().is_some() {
+                self.single_step()?;
+            }
             if self.get_state() == SessionState::Exited {
                 return Ok(());
             }
-            self.enable_bp(breakpoint)?;
+            if self.watchpoint_hit.borrow().is_some() {
+                self.stopped_after_step_over.set(true);
+                return Ok(());
+            }
         }
 
         log::trace!("continue from {:#x}", self.get_ip()?);
-        ptrace::cont(self.child_pid(), None)?;
+        ptrace::cont(self.child_pid(), self.pending_signal.take())?;
+        self.single_stepping.set(false);
         self.state.set(SessionState::Running);
         Ok(())
     }
 
+    /// runs the program again from the start and stops at the breakpoint hit before the current stop.
+    /// Replay relies on the program taking the same path, it ends at the first breakpoint hit that differs from the recorded one
+    pub fn reverse_continue(&mut self) -> Result<()> {
+        let mut hits = self.breakpoint_hits.borrow().clone();
+        // after stepping away from the last hit going back means stopping at it again
+        if self.get_state() == SessionState::Running && hits.last().copied() == Some(self.get_ip()?) {
+            hits.pop();
+        }
+        if hits.is_empty() {
+            bail!(DebuggerError::NoPreviousBreakpointHit);
+        }
+
+        self.restart()?;
+        log::trace!("replaying {} breakpoint hits", hits.len());
+        self.run()?;
+        self.wait()?;
+        loop {
+            let replayed = self.breakpoint_hits.borrow().len();
+            if self.get_state() == SessionState::Exited || self.breakpoint_hits.borrow()[..] != hits[..replayed] {
+                bail!(DebuggerError::ReplayDiverged);
+            }
+            if replayed == hits.len() {
+                return Ok(());
+            }
+            self.cont()?;
+            self.wait()?;
+        }
+    }
+
+    /// continues with all breakpoints lifted until the program exits, signals are delivered to the program.
+    /// Ctrl-C stops the program where it is and breakpoints are set again
+    pub fn run_to_exit(&self) -> Result<()> {
+        // breakpoint we are stopped at is lifted only until continue.
+        // Only addresses are kept, wait can remove a temporary breakpoint and needs breakpoints borrowed mutably
+        let lifted = self
+            .breakpoints
+            .borrow()
+            .iter()
+            .filter(|(_, breakpoint)| breakpoint.enabled.get() || breakpoint.lifted.get())
+            .map(|(addr, _)| *addr)
+            .collect::<Vec<_>>();
+        for addr in &lifted {
+            if let Some(breakpoint) = self.breakpoints.borrow().get(addr).filter(|breakpoint| breakpoint.enabled.get()) {
+                self.disable_bp(breakpoint)?;
+            }
+        }
+        log::trace!("run to exit with {} breakpoints lifted", lifted.len());
+
+        interrupt::catch()?;
+        let result = self.cont_to_exit();
+        interrupt::release()?;
+        result?;
+
+        let breakpoints = self.breakpoints.borrow();
+        for breakpoint in lifted.iter().filter_map(|addr| breakpoints.get(addr)) {
+            match self.get_state() {
+                // nothing to write into, breakpoints are set again on restart
+                SessionState::Exited => {
+                    breakpoint.enabled.set(true);
+                    breakpoint.lifted.set(false);
+                }
+                _ => self.enable_bp(breakpoint)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cont_to_exit(&self) -> Result<()> {
+        loop {
+            ptrace::cont(self.child_pid(), self.pending_signal.take())?;
+            self.single_stepping.set(false);
+            self.state.set(SessionState::Running);
+            self.wait()?;
+
+            if self.get_state() == SessionState::Exited || self.exec_path.borrow().is_some() {
+                return Ok(());
+            }
+            if self.pending_signal.get() == Some(Signal::SIGINT) && interrupt::is_interrupted() {
+                log::trace!("run to exit interrupted");
+                self.pending_signal.set(None);
+                return Ok(());
+            }
+        }
+    }
+
     fn single_step(&self) -> Result<()> {
-        ptrace::step(self.child_pid(), None)?;
+        ptrace::step(self.child_pid(), self.pending_signal.take())?;
+        self.single_stepping.set(true);
         self.wait()
     }
 
+    /// lifts breakpoint at addr until wait sees the child leaving it
+    fn start_step_over(&self, addr: u64) -> Result<()> {
+        if let Some(breakpoint) = self.breakpoints.borrow().get(&addr) {
+            self.lift_bp(breakpoint)?;
+        }
+        self.stepping_over.set(Some(addr));
+        Ok(())
+    }
+
+    fn finish_step_over(&self, addr: u64) -> Result<()> {
+        self.stepping_over.set(None);
+        // breakpoint could be removed or disabled while the child was stopped in the middle of the step
+        if let Some(breakpoint) = self.breakpoints.borrow().get(&addr).filter(|breakpoint| breakpoint.lifted.get()) {
+            log::trace!("stepped over breakpoint {}", breakpoint.loc);
+            if let Err(e) = self.enable_bp(breakpoint) {
+                bail!("can't enable breakpoint {} after stepping over it: {}", breakpoint.loc, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// single steps until predicate is true, stopping early at breakpoints, signals, exit and ctrl-c.
+    /// Returns whether the predicate stopped the program
+    pub fn step_until<F>(&self, predicate: F) -> Result<bool>
+    where
+        F: FnMut(&Self) -> Result<bool>,
+    {
+        // stepping takes a while, ctrl-c stops the program where it is
+        interrupt::catch()?;
+        let result = self.step_until_interrupted(predicate);
+        interrupt::release()?;
+        result
+    }
+
+    fn step_until_interrupted<F>(&self, mut predicate: F) -> Result<bool>
+    where
+        F: FnMut(&Self) -> Result<bool>,
+    {
+        loop {
+            let ip = self.get_ip()?;
+            if self.breakpoints.borrow().contains_key(&ip) {
+                self.start_step_over(ip)?;
+            }
+            self.single_step()?;
+            if self.get_state() == SessionState::Exited {
+                return Ok(false);
+            }
+            if interrupt::is_interrupted() {
+                log::trace!("step until interrupted");
+                // ctrl-c is sent to the program too, it's not delivered
+                if self.pending_signal.get() == Some(Signal::SIGINT) {
+                    self.pending_signal.set(None);
+                }
+                return Ok(false);
+            }
+            if self.pending_signal.get().is_some() || self.watchpoint_hit.borrow().is_some() {
+                return Ok(false);
+            }
+
+            if predicate(self)? {
+                return Ok(true);
+            }
+
+            // stop as if the breakpoint was hit
+            if let Some(breakpoint) = self.breakpoints.borrow().get(&self.get_ip()?) {
+                if breakpoint.enabled.get() {
+                    self.lift_bp(breakpoint)?;
+                    return Ok(false);
+                }
+            }
+        }
+    }
+
+    /// signal which stopped the child and will be delivered on continue
+    pub fn get_pending_signal(&self) -> Option<Signal> {
+        self.pending_signal.get()
+    }
+
+    /// runs to the next line of the current function or to its caller, calls are stepped over
     pub fn step(&self) -> Result<()> {
-        let ip = self.get_ip()?;
+        let ctx = self.get_context()?;
+        let ip = ctx.ip();
+        let start_cfa = self.get_cfa(&ctx)?;
         let start_line = self.loc_finder.find_line(ip).ok_or(anyhow!("can't find start line"))?;
         log::trace!("start line {}", start_line);
-        let next_line_address = match self.loc_finder.find_next_line_address(&start_line) {
-            Some(address) => address,
-            None => return self.step_out(),
+        let line_addresses = self
+            .loc_finder
+            .find_other_line_addresses(ip, &start_line)
+            .ok_or(anyhow!("can't find current func"))?;
+        let return_ip = match self.loc_finder.is_inside_main(ip) {
+            true => None,
+            false => Some(self.get_func_return_addr(ctx)?),
         };
-        log::trace!("next line address {:#x}", next_line_address);
-        let func_end = self.loc_finder.find_func_end(ip).ok_or(anyhow!("can't find func end"))?;
+        log::trace!("step to one of {} lines, return to {:?}", line_addresses.len(), return_ip);
 
-        if next_line_address >= func_end {
-            log::trace!("next line is outside of func. Step out");
-            return self.step_out();
+        for &address in line_addresses.iter() {
+            self.add_trap(address)?;
+        }
+        if let Some(return_ip) = return_ip {
+            self.add_trap(return_ip)?;
         }
 
-        self.add_trap(next_line_address)?;
-        self.cont()?;
-        self.wait()
+        loop {
+            self.cont()?;
+            self.wait()?;
+            if self.get_state() == SessionState::Exited {
+                return Ok(());
+            }
+
+            // recursive calls hit the same traps in deeper frames, return address is hit when they return to the current frame
+            let ip = self.get_ip()?;
+            let is_return = return_ip == Some(ip);
+            if !(is_return || line_addresses.contains(&ip)) || self.breakpoints.borrow().contains_key(&ip) {
+                break;
+            }
+            let is_other_frame = match (start_cfa, self.get_cfa(&self.get_context()?)?) {
+                (Some(start_cfa), Some(cfa)) if is_return => cfa <= start_cfa,
+                (Some(start_cfa), Some(cfa)) => cfa < start_cfa,
+                _ => false,
+            };
+            if !is_other_frame {
+                break;
+            }
+
+            log::trace!("trap at {:#x} is hit in other frame", ip);
+            // trap is put back once the instruction under it is executed
+            self.single_step()?;
+            if self.get_state() == SessionState::Exited {
+                return Ok(());
+            }
+            self.add_trap(ip)?;
+        }
+
+        // only one trap was hit, if any
+        for address in line_addresses {
+            self.remove_trap(address)?;
+        }
+        if let Some(return_ip) = return_ip {
+            self.remove_trap(return_ip)?;
+            if self.get_ip()? == return_ip {
+                self.step_to_line()?;
+            }
+        }
+
+        Ok(())
     }
 
     pub fn step_in(&self) -> Result<()> {
@@ -190,8 +1142,11 @@ impl<R: gimli::Reader> DebugSession<R> {
         loop {
             self.single_step()?;
             match self.get_state() {
-                SessionState::Started => panic!("child is not running"),
+                SessionState::Started => bail!(DebuggerError::NotRunning),
                 SessionState::Running => {
+                    if self.watchpoint_hit.borrow().is_some() {
+                        return Ok(());
+                    }
                     if let Some(line) = self.get_current_line()? {
                         if line != start_line {
                             log::trace!("stepped in to {}", line);
@@ -206,7 +1161,7 @@ impl<R: gimli::Reader> DebugSession<R> {
 
     pub fn step_out(&self) -> Result<()> {
         let ctx = self.get_context()?;
-        if self.loc_finder.is_inside_main(ctx.ip) {
+        if self.loc_finder.is_inside_main(ctx.ip()) {
             log::trace!("step out of main");
             self.cont()?;
             self.wait()?;
@@ -216,12 +1171,186 @@ impl<R: gimli::Reader> DebugSession<R> {
         let return_ip = self.get_func_return_addr(ctx)?;
         log::trace!("step out to {:#x}", return_ip);
 
+        self.run_to_return(return_ip)?;
+        self.step_to_line()
+    }
+
+    /// runs till the location, stops earlier if the current function returns first
+    pub fn advance(&self, loc: &str) -> Result<()> {
+        let (_, addr) = self.resolve_loc(loc)?;
+        self.run_to_address(addr)
+    }
+
+    /// runs till the line of the current function, like `advance` it stops when the function returns.
+    /// Line outside the current function runs out of it
+    pub fn until(&self, loc: &str) -> Result<()> {
+        let (_, addr) = self.resolve_loc(loc)?;
+        let ip = self.get_ip()?;
+        let func_start = self.loc_finder.find_func_start(ip).ok_or(anyhow!("find func start"))?;
+        let func_end = self.loc_finder.find_func_end(ip).ok_or(anyhow!("find func end"))?;
+        if !(func_start..=func_end).contains(&addr) {
+            log::trace!("until {:#x} outside of current function", addr);
+            return self.step_out();
+        }
+
+        self.run_to_address(addr)
+    }
+
+    fn run_to_address(&self, addr: u64) -> Result<()> {
+        let ctx = self.get_context()?;
+        let return_ip = match self.loc_finder.is_inside_main(ctx.ip()) {
+            true => None,
+            false => Some(self.get_func_return_addr(ctx)?),
+        };
+        log::trace!("advance to {:#x}, return to {:?}", addr, return_ip);
+
+        self.add_trap(addr)?;
+        if let Some(return_ip) = return_ip {
+            self.add_trap(return_ip)?;
+        }
+        self.cont()?;
+        self.wait()?;
+
+        if self.get_state() == SessionState::Exited {
+            return Ok(());
+        }
+
+        // one of traps is still there
+        self.remove_trap(addr)?;
+        if let Some(return_ip) = return_ip {
+            self.remove_trap(return_ip)?;
+            if self.get_ip()? == return_ip {
+                self.step_to_line()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// runs till the current function returns, gives the value it returned
+    pub fn finish(&self) -> Result<Option<Value>> {
+        let ctx = self.get_context()?;
+        if self.loc_finder.is_inside_main(ctx.ip()) {
+            bail!(DebuggerError::OutermostFrame);
+        }
+
+        let func = self.loc_finder.find_func_by_address(ctx.ip()).ok_or(anyhow!("get current func"))?;
+        let return_type_id = self.loc_finder.find_func_return_type(&func).ok_or(anyhow!("get {} return type", func))?;
+        let return_class = abi::classify_return_type(self, return_type_id)?;
+
+        let return_ip = self.get_func_return_addr(ctx)?;
+        log::trace!("finish {} at {:#x}", func, return_ip);
+
+        self.run_to_return(return_ip)?;
+        // something stopped us before return
+        if self.get_state() == SessionState::Exited || self.get_ip()? != return_ip {
+            return Ok(None);
+        }
+
+        let value = self.read_return_value(return_type_id, return_class)?;
+        self.step_to_line()?;
+
+        Ok(value)
+    }
+
+    /// calls the function of the program with integer or pointer arguments passed in registers like SysV ABI does.
+    /// Registers are restored after the call, memory written by the function is kept
+    pub fn call_func(&self, func: &str, args: &[u64]) -> Result<Option<Value>> {
+        let func_address = self
+            .loc_finder
+            .find_func_address(func)
+            .ok_or_else(|| DebuggerError::FuncNotFound(func.to_string()))?;
+        let return_type_id = self.loc_finder.find_func_return_type(func).ok_or(anyhow!("get {} return type", func))?;
+        let return_class = abi::classify_return_type(self, return_type_id)?;
+        if return_class == ReturnClass::Memory {
+            bail!(DebuggerError::UnsupportedReturnType("values in memory"));
+        }
+        if args.len() > CALL_ARGS_REGISTERS_COUNT {
+            bail!(DebuggerError::TooManyArguments(CALL_ARGS_REGISTERS_COUNT));
+        }
+
+        let original_regs = ptrace::getregs(self.child_pid())?; // backup registers
+        let original_xstate = XState::read(self.child_pid())?;
+        let original_pending_signal = self.pending_signal.take();
+        let original_breakpoint_hits = self.breakpoint_hits.borrow().len();
+
+        // function returns to the entry point of the program, it's not executed again
+        let return_address = self.base_address + self.image.entry_address;
+        // skip the red zone, stack must be 16 bytes aligned before the return address is pushed
+        let stack_pointer = ((original_regs.rsp - RED_ZONE_SIZE) & !0xf) - WORD_SIZE as u64;
+        self.write_memory(stack_pointer, &return_address.to_ne_bytes())?;
+        self.add_trap(return_address)?;
+        // breakpoints are ignored inside the called function
+        let lifted_breakpoints = self
+            .breakpoints
+            .borrow()
+            .values()
+            .filter(|breakpoint| breakpoint.enabled.get())
+            .map(|breakpoint| breakpoint.addr)
+            .collect::<Vec<_>>();
+        for addr in lifted_breakpoints.iter() {
+            self.disable_bp(&self.breakpoints.borrow()[addr])?;
+        }
+
+        let mut regs = original_regs;
+        let arg_registers = [&mut regs.rdi, &mut regs.rsi, &mut regs.rdx, &mut regs.rcx, &mut regs.r8, &mut regs.r9];
+        for (register, &arg) in arg_registers.into_iter().zip(args) {
+            *register = arg;
+        }
+        regs.rax = 0; // no vector registers are used by variadic functions
+        regs.rsp = stack_pointer;
+        regs.rip = func_address;
+        regs.orig_rax = u64::MAX; // interrupted syscall must not be restarted at the function
+        ptrace::setregs(self.child_pid(), regs)?;
+        log::trace!("call {} at {:#x}, return to {:#x}", func, func_address, return_address);
+
+        self.cont()?;
+        self.wait()?;
+        if self.get_state() == SessionState::Exited {
+            // flags are kept, so restart sets the breakpoints again. Breakpoint we were stopped at keeps its lifted flag
+            for addr in lifted_breakpoints.iter() {
+                if let Some(breakpoint) = self.breakpoints.borrow().get(addr) {
+                    breakpoint.enabled.set(true);
+                }
+            }
+            bail!(DebuggerError::ExitedDuringCall(func.to_string()));
+        }
+
+        let ip = self.get_ip()?;
+        let value = match ip == return_address {
+            true => self.read_return_value(return_type_id, return_class)?,
+            false => {
+                self.remove_trap(return_address)?;
+                None
+            }
+        };
+
+        for addr in lifted_breakpoints.iter() {
+            self.enable_bp(&self.breakpoints.borrow()[addr])?;
+        }
+        ptrace::setregs(self.child_pid(), original_regs)?;
+        original_xstate.write(self.child_pid())?;
+        self.pending_signal.set(original_pending_signal);
+        self.breakpoint_hits.borrow_mut().truncate(original_breakpoint_hits);
+        self.watchpoint_hit.take();
+        self.frames.borrow_mut().clear();
+
+        if ip != return_address {
+            bail!(DebuggerError::CallInterrupted(func.to_string()));
+        }
+
+        Ok(value)
+    }
+
+    fn run_to_return(&self, return_ip: u64) -> Result<()> {
         // there is posibility that we'll stop with bp <= start_bp (using some recursion), but we'll ignore this case for now
         self.add_trap(return_ip)?;
         self.cont()?;
-        self.wait()?;
+        self.wait()
+    }
 
-        // single step till we hit some line
+    /// single step till we hit some line
+    fn step_to_line(&self) -> Result<()> {
         while self.get_current_line()?.is_none() {
             self.single_step()?;
         }
@@ -229,6 +1358,36 @@ impl<R: gimli::Reader> DebugSession<R> {
         Ok(())
     }
 
+    /// must be called right after return, before registers are reused
+    fn read_return_value(&self, type_id: TypeId, return_class: ReturnClass) -> Result<Option<Value>> {
+        let regs = ptrace::getregs(self.child_pid())?;
+
+        let buf = match return_class {
+            ReturnClass::Void => return Ok(None),
+            ReturnClass::Memory => return Ok(Some(self.read_typed(regs.rax, type_id)?)),
+            ReturnClass::Registers(classes) => {
+                let fpregs = ptrace::getregset::<ptrace::regset::NT_PRFPREG>(self.child_pid())?;
+                let mut integer_registers = [regs.rax, regs.rdx].into_iter();
+                // low 8 bytes of xmm0 and xmm1
+                let mut sse_registers = (0..2).map(|i| fpregs.xmm_space[i * 4] as u64 | (fpregs.xmm_space[i * 4 + 1] as u64) << 32);
+
+                let mut buf = Vec::with_capacity(classes.len() * WORD_SIZE);
+                for class in classes {
+                    let value = match class {
+                        EightbyteClass::Integer => integer_registers.next(),
+                        EightbyteClass::Sse => sse_registers.next(),
+                    };
+                    buf.extend_from_slice(&value.ok_or(anyhow!("too many registers for return value"))?.to_ne_bytes());
+                }
+                buf.truncate(self.get_type_size(type_id)?);
+
+                buf.into()
+            }
+        };
+
+        Ok(Some(Value::new(type_id, buf)))
+    }
+
     fn rewind(&self) -> Result<()> {
         log::trace!("rewind");
 
@@ -236,85 +1395,283 @@ impl<R: gimli::Reader> DebugSession<R> {
         log::trace!("current ip {:#x}", regs.rip);
         regs.rip -= 1;
         ptrace::setregs(self.child_pid(), regs)?;
+        self.frames.borrow_mut().clear();
         log::trace!("new ip {:#x}", regs.rip);
 
         Ok(())
     }
 
     fn get_func_return_addr(&self, ctx: Context) -> Result<u64> {
-        let func_start = self.loc_finder.find_func_start(ctx.ip).ok_or(anyhow!("find func start"))?;
+        let mut caller_ctx = match self.unwind_context(&ctx)? {
+            Some(caller_ctx) => caller_ctx,
+            None => return self.guess_func_return_addr(ctx),
+        };
 
-        self.check_func_prologue(func_start)?;
+        // signal handler returns through trampoline to the interrupted code
+        while self.is_signal_trampoline(caller_ctx.ip()) {
+            log::trace!("return to signal trampoline at {:#x}", caller_ctx.ip());
+            caller_ctx = self.unwind_context(&caller_ctx)?.ok_or(anyhow!("unwind signal trampoline"))?;
+        }
 
-        let return_addr_location = if ctx.ip - func_start <= 4 {
-            ctx.sp
-        } else if ctx.ip - func_start <= 8 {
-            ctx.sp + WORD_SIZE as u64
-        } else {
-            ctx.bp + WORD_SIZE as u64
+        Ok(caller_ctx.ip())
+    }
+
+    /// find return address by function prologue for binaries without unwind info
+    fn guess_func_return_addr(&self, ctx: Context) -> Result<u64> {
+        let func_start = self.loc_finder.find_func_start(ctx.ip()).ok_or(anyhow!("find func start"))?;
+        let offset = ctx.ip() - func_start;
+        let code = self.read_address(func_start, MAX_FUNC_PROLOGUE_SIZE)?;
+
+        let return_addr_location = match FUNC_PROLOGUES.iter().find(|prologue| code.starts_with(prologue.bytes)) {
+            Some(prologue) if offset <= prologue.push_offset => ctx.sp(),
+            Some(prologue) if offset <= prologue.bytes.len() as u64 => ctx.sp() + WORD_SIZE as u64,
+            Some(_) => ctx.bp() + WORD_SIZE as u64,
+            // without frame pointer the stack is known only at the function entry
+            None if offset == 0 || (code.starts_with(&ENDBR64_BYTES) && offset == ENDBR64_BYTES.len() as u64) => ctx.sp(),
+            None => bail!("func prologue not found"),
         };
 
         let return_addr = self.read_address(return_addr_location, WORD_SIZE)?.get_u64_ne();
         Ok(return_addr)
     }
 
-    fn check_func_prologue(&self, func_start: u64) -> Result<()> {
-        let bytes = self.read_address(func_start, FUNC_PROLOGUE_MAGIC_BYTES.len())?;
-        if bytes.as_ref() != FUNC_PROLOGUE_MAGIC_BYTES {
-            bail!("func prologue not found");
+    /// canonical frame address identifies the frame, it doesn't change while the function runs. None without unwind info
+    fn get_cfa(&self, ctx: &Context) -> Result<Option<u64>> {
+        let cfa = match self.image.unwinder.unwind_cfa(ctx.pc() - self.base_address) {
+            Ok(cfa) => cfa,
+            Err(gimli::Error::NoUnwindInfoForAddress) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        match cfa {
+            gimli::CfaRule::RegisterAndOffset { register, offset } => {
+                let mut regs = ctx.regs;
+                Ok(Some((*Self::get_register_ref(&mut regs, register)? as i64 + offset) as u64))
+            }
+            gimli::CfaRule::Expression(_) => Ok(None),
         }
+    }
 
-        Ok(())
+    /// restore registers of the caller frame using call frame information, returns None for the outermost frame
+    fn unwind_context(&self, ctx: &Context) -> Result<Option<Context>> {
+        if self.is_signal_trampoline(ctx.ip()) {
+            return self.unwind_signal_trampoline(ctx).map(Some);
+        }
+
+        let frame_info = match self.image.unwinder.unwind_frame(ctx.pc() - self.base_address) {
+            Ok(frame_info) => frame_info,
+            Err(gimli::Error::NoUnwindInfoForAddress) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut regs = ctx.regs;
+        let cfa = match *frame_info.row.cfa() {
+            gimli::CfaRule::RegisterAndOffset { register, offset } => (*Self::get_register_ref(&mut regs, register)? as i64 + offset) as u64,
+            gimli::CfaRule::Expression(_) => bail!("unsupported cfa rule"),
+        };
+        log::trace!("unwind frame at {:#x}, cfa is {:#x}", ctx.ip(), cfa);
+
+        let mut caller_regs = ctx.regs;
+        for register in CALLEE_SAVED_REGISTERS.into_iter().chain([gimli::X86_64::RA]) {
+            let value = match frame_info.row.register(register) {
+                gimli::RegisterRule::Undefined | gimli::RegisterRule::SameValue => {
+                    if register == gimli::X86_64::RA {
+                        return Ok(None);
+                    }
+                    continue;
+                }
+                gimli::RegisterRule::Offset(offset) => self.read_address((cfa as i64 + offset) as u64, WORD_SIZE)?.get_u64_ne(),
+                gimli::RegisterRule::ValOffset(offset) => (cfa as i64 + offset) as u64,
+                gimli::RegisterRule::Register(other_register) => *Self::get_register_ref(&mut regs, other_register)?,
+                rule => bail!("unsupported register rule {:?}", rule),
+            };
+
+            match register {
+                gimli::X86_64::RA => caller_regs.rip = value,
+                _ => *Self::get_register_ref(&mut caller_regs, register)? = value,
+            }
+        }
+        caller_regs.rsp = cfa;
+
+        if caller_regs.rip == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Context {
+            regs: caller_regs,
+            // instruction is interrupted in the frame that called signal trampoline
+            is_return_address: !frame_info.is_signal_trampoline,
+        }))
+    }
+
+    fn is_signal_trampoline(&self, address: u64) -> bool {
+        self.read_address(address, SIGRETURN_TRAMPOLINE_BYTES.len())
+            .is_ok_and(|bytes| bytes.as_ref() == SIGRETURN_TRAMPOLINE_BYTES)
+    }
+
+    /// restore registers of the interrupted frame from the ucontext_t which kernel put on the stack before the signal handler call
+    fn unwind_signal_trampoline(&self, ctx: &Context) -> Result<Context> {
+        let gregs_address = ctx.sp() + (mem::offset_of!(libc::ucontext_t, uc_mcontext) + mem::offset_of!(libc::mcontext_t, gregs)) as u64;
+        let gregs_size = mem::offset_of!(libc::mcontext_t, fpregs) - mem::offset_of!(libc::mcontext_t, gregs);
+        let mut gregs = self.read_address(gregs_address, gregs_size)?;
+        let gregs = (0..gregs.len() / WORD_SIZE).map(|_| gregs.get_u64_ne()).collect::<Vec<_>>();
+        let greg = |index: libc::c_int| gregs[index as usize];
+        log::trace!("unwind signal trampoline at {:#x}, interrupted at {:#x}", ctx.ip(), greg(libc::REG_RIP));
+
+        let mut regs = ctx.regs;
+        regs.r8 = greg(libc::REG_R8);
+        regs.r9 = greg(libc::REG_R9);
+        regs.r10 = greg(libc::REG_R10);
+        regs.r11 = greg(libc::REG_R11);
+        regs.r12 = greg(libc::REG_R12);
+        regs.r13 = greg(libc::REG_R13);
+        regs.r14 = greg(libc::REG_R14);
+        regs.r15 = greg(libc::REG_R15);
+        regs.rdi = greg(libc::REG_RDI);
+        regs.rsi = greg(libc::REG_RSI);
+        regs.rbp = greg(libc::REG_RBP);
+        regs.rbx = greg(libc::REG_RBX);
+        regs.rdx = greg(libc::REG_RDX);
+        regs.rax = greg(libc::REG_RAX);
+        regs.rcx = greg(libc::REG_RCX);
+        regs.rsp = greg(libc::REG_RSP);
+        regs.rip = greg(libc::REG_RIP);
+        regs.eflags = greg(libc::REG_EFL);
+
+        Ok(Context {
+            regs,
+            is_return_address: false,
+        })
+    }
+
+    /// general purpose registers of the innermost frame
+    pub fn get_regs(&self) -> Result<libc::user_regs_struct> {
+        Ok(ptrace::getregs(self.child_pid())?)
+    }
+
+    pub fn get_xstate(&self) -> Result<XState> {
+        XState::read(self.child_pid())
     }
 
     /// get instruction pointer
-    fn get_ip(&self) -> Result<u64> {
+    pub fn get_ip(&self) -> Result<u64> {
         let regs = ptrace::getregs(self.child_pid())?;
         Ok(regs.rip)
     }
 
+    pub fn get_current_func(&self) -> Result<Option<Rc<str>>> {
+        let ip = self.get_ip()?;
+        Ok(self.loc_finder.find_func_by_address(ip))
+    }
+
     pub fn get_current_line(&self) -> Result<Option<Rc<str>>> {
         let ip = self.get_ip()?;
         let line = self.loc_finder.find_line(ip);
         Ok(line)
     }
 
-    pub fn add_breakpoint<'a, S>(&mut self, loc: S) -> Result<()>
+    pub fn add_breakpoint<'a, S>(&mut self, loc: S) -> Result<&Breakpoint>
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        let id = self.next_breakpoint_id;
+        let addr = self.insert_breakpoint(loc, id)?;
+        self.next_breakpoint_id += 1;
+
+        Ok(&self.breakpoints.get_mut()[&addr])
+    }
+
+    /// returns address of the new breakpoint
+    fn insert_breakpoint<'a, S>(&mut self, loc: S, id: usize) -> Result<u64>
     where
         S: Into<Cow<'a, str>>,
     {
         let loc = loc.into().into_owned();
-        let loc = self.prepare_breakpoint_loc(&loc)?;
-        let addr = self.loc_finder.find_loc(&loc)?.ok_or(DebuggerError::LocNotFound)?;
+        let (loc, addr) = self.resolve_loc(&loc)?;
 
         // can't use entry api here because of borrors
-        if self.breakpoints.contains_key(&addr) {
+        if self.breakpoints.get_mut().contains_key(&addr) {
             bail!(DebuggerError::BreakpointAlreadyExist)
         }
 
         log::trace!("set breakpoint at {:#x}", addr);
         let original_bytecode = ptrace::read(self.child_pid(), addr as ptrace::AddressType)?;
-        let breakpoint = Breakpoint::new(addr, original_bytecode, loc);
+        let breakpoint = Breakpoint::new(id, addr, original_bytecode, loc);
         self.enable_bp(&breakpoint)?;
+        self.breakpoints.get_mut().insert(addr, breakpoint);
+
+        Ok(addr)
+    }
+
+    /// breakpoint which prints the variable every time it's hit instead of stopping
+    pub fn add_logpoint<'a, S>(&mut self, loc: S, expr: &str) -> Result<&Breakpoint>
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        let addr = self.add_breakpoint(loc)?.addr;
+        let breakpoint = self.breakpoints.get_mut().get_mut(&addr).unwrap();
+        breakpoint.log = Some(expr.to_string());
+
+        Ok(breakpoint)
+    }
+
+    /// breakpoint removed after the first hit
+    pub fn add_temporary_breakpoint<'a, S>(&mut self, loc: S) -> Result<&Breakpoint>
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        let addr = self.add_breakpoint(loc)?.addr;
+        let breakpoint = self.breakpoints.get_mut().get_mut(&addr).unwrap();
+        breakpoint.temporary = true;
+
+        Ok(breakpoint)
+    }
 
-        self.breakpoints.insert(addr, breakpoint);
+    pub fn list_breakpoints(&self) -> Vec<Breakpoint> {
+        self.breakpoints.borrow().values().cloned().collect()
+    }
 
-        Ok(())
+    pub fn get_breakpoint(&self, loc: &str) -> Option<Ref<'_, Breakpoint>> {
+        Ref::filter_map(self.breakpoints.borrow(), |breakpoints| {
+            breakpoints.values().find(|&breakpoint| breakpoint.is_named(loc))
+        })
+        .ok()
+    }
+
+    /// breakpoint the program is stopped at
+    pub fn get_current_breakpoint(&self) -> Result<Option<Ref<'_, Breakpoint>>> {
+        let ip = self.get_ip()?;
+        Ok(Ref::filter_map(self.breakpoints.borrow(), |breakpoints| breakpoints.get(&ip)).ok())
     }
 
-    pub fn list_breakpoints(&self) -> impl ExactSizeIterator<Item = &Breakpoint> {
-        self.breakpoints.values()
+    pub fn set_breakpoint_commands(&mut self, loc: &str, commands: Vec<String>) -> Result<()> {
+        match self.breakpoints.get_mut().values_mut().find(|breakpoint| breakpoint.is_named(loc)) {
+            Some(breakpoint) => {
+                breakpoint.commands = commands;
+                Ok(())
+            }
+            None => Err(anyhow!(DebuggerError::BreakpointNotFound)),
+        }
     }
 
-    pub fn get_breakpoint(&self, loc: &str) -> Option<&Breakpoint> {
-        self.breakpoints.values().find(|&breakpoint| breakpoint.loc == loc)
+    pub fn set_breakpoint_condition(&mut self, loc: &str, condition: Option<String>) -> Result<()> {
+        match self.breakpoints.get_mut().values_mut().find(|breakpoint| breakpoint.is_named(loc)) {
+            Some(breakpoint) => {
+                breakpoint.condition = condition;
+                Ok(())
+            }
+            None => Err(anyhow!(DebuggerError::BreakpointNotFound)),
+        }
     }
 
     pub fn remove_breakpoint(&mut self, loc: &str) -> Result<()> {
         match self.get_breakpoint(loc).map(|breakpoint| breakpoint.addr) {
             Some(addr) => {
-                let breakpoint = self.breakpoints.remove(&addr);
-                self.disable_bp(&breakpoint.unwrap())
+                // disabled breakpoint has original bytecode in place already
+                match self.breakpoints.get_mut().remove(&addr) {
+                    Some(breakpoint) if breakpoint.enabled.get() => self.disable_bp(&breakpoint),
+                    _ => Ok(()),
+                }
             }
             None => Err(anyhow!(DebuggerError::BreakpointNotFound)),
         }
@@ -323,24 +1680,42 @@ impl<R: gimli::Reader> DebugSession<R> {
     pub fn clear_breakpoints(&mut self) -> Result<()> {
         log::trace!("clear breakpoints");
 
-        for breakpoint in self.breakpoints.values() {
+        for breakpoint in self.breakpoints.borrow().values().filter(|breakpoint| breakpoint.enabled.get()) {
             self.disable_bp(breakpoint)?;
         }
 
-        self.breakpoints.clear();
+        self.breakpoints.get_mut().clear();
 
         Ok(())
     }
 
+    /// finds address of file:line, line of current file, function or raw address
+    pub fn resolve_loc<'a>(&self, loc: &'a str) -> Result<(Cow<'a, str>, u64)> {
+        let loc = self.prepare_breakpoint_loc(loc)?;
+        let addr = self.loc_finder.find_loc(&loc)?.ok_or(DebuggerError::LocNotFound)?;
+
+        Ok((loc, addr))
+    }
+
     fn prepare_breakpoint_loc<'a>(&self, loc: &'a str) -> Result<Cow<'a, str>> {
         let loc = loc.trim();
 
-        match loc.parse::<u64>() {
+        // hex is an address, decimal is a line of current file
+        if loc.starts_with("0x") {
+            let address = parse_unsigned(loc).map_err(|_| DebuggerError::InvalidLocation)?;
+            return Ok(Cow::from(format!("{:#x}", address)));
+        }
+
+        if loc.starts_with('+') || loc.starts_with('-') {
+            return self.prepare_relative_loc(loc).map(Cow::from);
+        }
+
+        match parse_unsigned(loc) {
             Ok(_) => {
                 let ip = match self.get_state() {
                     SessionState::Started => None,
                     SessionState::Running => Some(self.get_ip()?),
-                    SessionState::Exited => panic!("can't get ip"),
+                    SessionState::Exited => bail!(DebuggerError::NotRunning),
                 };
 
                 let unit_name = self.loc_finder.find_unit(ip);
@@ -354,41 +1729,91 @@ impl<R: gimli::Reader> DebugSession<R> {
         }
     }
 
+    /// resolves +N and -N against the current line
+    fn prepare_relative_loc(&self, loc: &str) -> Result<String> {
+        let offset = parse_unsigned(&loc[1..]).map_err(|_| DebuggerError::InvalidLocation)?;
+        if self.get_state() != SessionState::Running {
+            bail!(DebuggerError::InvalidLocation);
+        }
+
+        let current_line = self.get_current_line()?.ok_or(DebuggerError::LocNotFound)?;
+        let (file, line) = current_line.rsplit_once(':').ok_or(anyhow!("invalid current line"))?;
+        let line = line.parse::<u64>()?;
+        let line = if loc.starts_with('+') {
+            line.checked_add(offset)
+        } else {
+            line.checked_sub(offset).filter(|&line| line > 0)
+        };
+
+        match line {
+            Some(line) => Ok(format!("{}:{}", file, line)),
+            None => bail!(DebuggerError::InvalidLocation),
+        }
+    }
+
     pub fn enable_breakpoint(&self, loc: &str) -> Result<()> {
         match self.get_breakpoint(loc) {
-            Some(breakpoint) => self.enable_bp(breakpoint),
+            Some(breakpoint) => self.enable_bp(&breakpoint),
             None => Err(anyhow!(DebuggerError::BreakpointNotFound)),
         }
     }
 
     fn enable_bp(&self, breakpoint: &Breakpoint) -> Result<()> {
-        let bytecode_with_trap = (breakpoint.original_bytecode & !0xff) | 0xcc;
+        // program could rewrite the code while the breakpoint was lifted, restoring saved byte would undo it.
+        // int3 there is a trap or program's own int3, not new code
+        if !breakpoint.enabled.get() {
+            let bytecode = ptrace::read(self.child_pid(), breakpoint.addr as ptrace::AddressType)? & 0xff;
+            if bytecode != INT3 && bytecode != breakpoint.original_bytecode.get() & 0xff {
+                log::warn!("code at {:#x} was modified, saving {:#x} as original", breakpoint.addr, bytecode);
+                breakpoint.original_bytecode.set(bytecode);
+            }
+        }
 
-        log::trace!(
-            "replace {:#x} with {:#x} at {:#x}",
-            breakpoint.original_bytecode,
-            bytecode_with_trap,
-            breakpoint.addr
-        );
-        ptrace::write(self.child_pid(), breakpoint.addr as ptrace::AddressType, bytecode_with_trap)?;
+        log::trace!("replace {:#x} with int3 at {:#x}", breakpoint.original_bytecode.get() & 0xff, breakpoint.addr);
+        Self::write_first_byte(self.child_pid(), breakpoint.addr, INT3)?;
 
         breakpoint.enabled.set(true);
+        breakpoint.lifted.set(false);
 
         Ok(())
     }
 
     pub fn disable_breakpoint(&self, loc: &str) -> Result<()> {
         match self.get_breakpoint(loc) {
-            Some(breakpoint) => self.disable_bp(breakpoint),
+            Some(breakpoint) => self.disable_bp(&breakpoint),
             None => Err(anyhow!(DebuggerError::BreakpointNotFound)),
         }
     }
 
     fn disable_bp(&self, breakpoint: &Breakpoint) -> Result<()> {
-        ptrace::write(self.child_pid(), breakpoint.addr as ptrace::AddressType, breakpoint.original_bytecode)?;
-        log::trace!("restored bytecode at {:#x} to {:#x}", breakpoint.addr, breakpoint.original_bytecode);
-
         breakpoint.enabled.set(false);
+        breakpoint.lifted.set(false);
+
+        // anything but int3 is either the original already or code written by the program after the breakpoint was set
+        let bytecode = ptrace::read(self.child_pid(), breakpoint.addr as ptrace::AddressType)? & 0xff;
+        if bytecode != INT3 {
+            if bytecode != breakpoint.original_bytecode.get() & 0xff {
+                log::warn!("int3 at {:#x} was overwritten with {:#x}, keeping it", breakpoint.addr, bytecode);
+            }
+            return Ok(());
+        }
+
+        Self::write_first_byte(self.child_pid(), breakpoint.addr, breakpoint.original_bytecode.get())?;
+        log::trace!(
+            "restored bytecode at {:#x} to {:#x}",
+            breakpoint.addr,
+            breakpoint.original_bytecode.get() & 0xff
+        );
+
+        Ok(())
+    }
+
+    /// takes int3 out until the program moves on, disabled breakpoint stays disabled
+    fn lift_bp(&self, breakpoint: &Breakpoint) -> Result<()> {
+        if breakpoint.enabled.get() {
+            self.disable_bp(breakpoint)?;
+            breakpoint.lifted.set(true);
+        }
 
         Ok(())
     }
@@ -400,10 +1825,9 @@ impl<R: gimli::Reader> DebugSession<R> {
                 log::trace!("set trap at {:#x}", addr);
 
                 let original_bytecode = ptrace::read(self.child_pid(), addr as ptrace::AddressType)?;
-                let bytecode_with_trap = (original_bytecode & !0xff) | 0xcc;
 
-                log::trace!("replace {:#x} with {:#x} at {:#x}", original_bytecode, bytecode_with_trap, addr);
-                ptrace::write(self.child_pid(), addr as ptrace::AddressType, bytecode_with_trap)?;
+                log::trace!("replace {:#x} with int3 at {:#x}", original_bytecode & 0xff, addr);
+                Self::write_first_byte(self.child_pid(), addr, INT3)?;
 
                 vacant_entry.insert(Trap::new(original_bytecode));
 
@@ -414,40 +1838,171 @@ impl<R: gimli::Reader> DebugSession<R> {
 
     fn remove_trap(&self, addr: u64) -> Result<()> {
         if let Some(trap) = self.traps.borrow_mut().remove(&addr) {
-            ptrace::write(self.child_pid(), addr as ptrace::AddressType, trap.original_bytecode)?;
-            log::trace!("restored bytecode at {:#x} to {:#x}", addr, trap.original_bytecode);
+            Self::write_first_byte(self.child_pid(), addr, trap.original_bytecode)?;
+            log::trace!("restored bytecode at {:#x} to {:#x}", addr, trap.original_bytecode & 0xff);
         }
 
         Ok(())
     }
 
+    /// int3 takes one byte, the rest of the word is read again because other traps could be set there
+    fn write_first_byte(pid: Pid, addr: u64, bytecode: i64) -> Result<()> {
+        let word = ptrace::read(pid, addr as ptrace::AddressType)?;
+        ptrace::write(pid, addr as ptrace::AddressType, (word & !0xff) | (bytecode & 0xff))?;
+        Ok(())
+    }
+
     pub fn get_vars(&self) -> Result<Vec<Var>> {
-        let ip = self.get_ip()?;
-        let current_func = self.loc_finder.find_func_by_address(ip).ok_or(anyhow!("get current func"))?;
+        let pc = self.get_frame_pc()?;
+        let current_func = self.loc_finder.find_func_by_address(pc).ok_or(DebuggerError::NoFrameDebugInfo)?;
+        let unit_name = self.loc_finder.find_unit(Some(pc));
+        let var_refs = self.loc_finder.get_vars(Some((current_func.as_ref(), pc)), unit_name.as_deref());
+
+        self.read_vars(&current_func, var_refs)
+    }
+
+    /// variables of the selected frame of one kind, sorted by name
+    pub fn get_vars_of_kind(&self, kind: VarKind) -> Result<Vec<Var>> {
+        let pc = self.get_frame_pc()?;
+        let current_func = self.loc_finder.find_func_by_address(pc).ok_or(DebuggerError::NoFrameDebugInfo)?;
+        let var_refs = match kind {
+            VarKind::Arg | VarKind::Local => {
+                let mut var_refs = self.loc_finder.get_func_vars(&current_func, pc);
+                var_refs.retain(|_, var_ref| var_ref.is_arg == (kind == VarKind::Arg));
+                var_refs
+            }
+            VarKind::Global => self.loc_finder.get_global_vars(self.loc_finder.find_unit(Some(pc)).as_deref()),
+        };
+
+        let mut vars = self.read_vars(&current_func, var_refs)?;
+        vars.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(vars)
+    }
+
+    /// optimized out variables are skipped
+    fn read_vars(&self, func: &str, var_refs: HashMap<Rc<str>, VarRef<R::Offset>>) -> Result<Vec<Var>> {
         let mut vars = Vec::new();
 
-        for (name, &var_ref) in self.loc_finder.get_vars(Some(current_func.as_ref())).iter() {
-            let value = self.get_value_by_var_ref(current_func.as_ref(), var_ref)?;
-            vars.push(Var::new(name.clone(), value));
+        for (name, var_ref) in var_refs {
+            let value = match self.get_value_by_var_ref(func, var_ref) {
+                Ok(value) => value,
+                Err(e) => match e.downcast_ref::<DebuggerError>() {
+                    // thread-local variable can't be read, the rest are still listed
+                    Some(DebuggerError::OptimizedOut | DebuggerError::UnsupportedThreadLocal) => continue,
+                    _ => return Err(e),
+                },
+            };
+            vars.push(Var::new(name, value));
         }
 
         Ok(vars)
     }
 
     pub fn get_var_loc(&self, path: &Path) -> Result<TypedValueLoc> {
-        let ip = self.get_ip()?;
-        let func = self.loc_finder.find_func_by_address(ip).ok_or(anyhow!("get current func"))?;
-        let var_ref = match self.loc_finder.get_var(path.name, Some(func.as_ref())) {
-            Some(var_ref) => var_ref,
-            None => bail!(DebuggerError::VarNotFound(String::from(path.name))),
+        let cast_type_id = path.cast.as_ref().map(|cast| self.get_cast_type(cast.type_name, cast.pointers)).transpose()?;
+        let mut loc = match (path.address, cast_type_id) {
+            // number is converted by the cast like any other value
+            (Some(address), Some(type_id)) => TypedValueLoc::new(ValueLoc::Value(address), type_id),
+            (Some(_), None) => bail!(DebuggerError::InvalidPath),
+            (None, _) => {
+                let pc = self.get_frame_pc()?;
+                let func = self.loc_finder.find_func_by_address(pc).ok_or(DebuggerError::NoFrameDebugInfo)?;
+                let unit_name = self.loc_finder.find_unit(Some(pc));
+                let var_ref = match self
+                    .loc_finder
+                    .get_var(path.name, path.scope, Some((func.as_ref(), pc)), unit_name.as_deref())?
+                {
+                    Some(var_ref) => var_ref,
+                    None => match path.scope {
+                        Some(scope) => bail!(DebuggerError::VarNotFound(format!("{}::{}", scope, path.name))),
+                        None => bail!(DebuggerError::VarNotFound(String::from(path.name))),
+                    },
+                };
+                let loc = self.get_value_loc_by_var_ref(&func, var_ref)?;
+                self.unwind_loc(loc, &path.postfix_operators)?
+            }
         };
-        let mut loc = self.get_value_loc_by_var_ref(&func, var_ref)?;
-        loc = self.unwind_loc(loc, &path.postfix_operators)?;
-        loc = self.apply_prefix_operators(loc, &path.prefix_operators)?;
+
+        // operators after the cast are applied to the casted value, ones before it to the cast result
+        let (outer_operators, inner_operators) = path
+            .prefix_operators
+            .split_at(path.cast.as_ref().map_or(path.prefix_operators.len(), |cast| cast.position));
+        loc = self.apply_prefix_operators(loc, inner_operators)?;
+        if let Some(type_id) = cast_type_id {
+            loc = self.apply_cast(loc, type_id)?;
+        }
+        loc = self.apply_prefix_operators(loc, outer_operators)?;
 
         Ok(loc)
     }
 
+    /// type named like in C (`int`, `struct node`, typedef name) with `pointers` levels of indirection
+    fn get_cast_type(&self, type_name: &str, pointers: usize) -> Result<TypeId> {
+        let mut type_id = self
+            .type_storage
+            .find_type(type_name)
+            .ok_or_else(|| DebuggerError::TypeNotFound(type_name.to_string()))?;
+        for _ in 0..pointers {
+            type_id = self.type_storage.get_pointer_type(type_id);
+        }
+
+        Ok(type_id)
+    }
+
+    /// converts integer, enum or pointer value to another of these types, truncating or extending it like C does.
+    /// Arrays decay to pointers, floats and aggregates can't be casted
+    fn apply_cast(&self, loc: TypedValueLoc, type_id: TypeId) -> Result<TypedValueLoc> {
+        let is_scalar = |typ: &Type<R>| match typ {
+            // value is carried in one word
+            Type::Base { encoding, size, .. } => !matches!(*encoding, gimli::DW_ATE_float | gimli::DW_ATE_complex_float) && *size as usize <= WORD_SIZE,
+            Type::Enum { .. } | Type::Pointer(_) | Type::String(_) | Type::Func(_) => true,
+            _ => false,
+        };
+        let target_type = self.type_storage.unwind_type(type_id)?;
+        if !is_scalar(&target_type) {
+            bail!(DebuggerError::InvalidCast);
+        }
+
+        let value = match self.type_storage.unwind_type(loc.type_id)? {
+            Type::Array { .. } => match loc.location {
+                ValueLoc::Address(address) => address,
+                _ => bail!(DebuggerError::NoAddress),
+            },
+            typ if is_scalar(&typ) => {
+                let is_signed = matches!(
+                    typ,
+                    Type::Base {
+                        encoding: gimli::DW_ATE_signed | gimli::DW_ATE_signed_char,
+                        ..
+                    } | Type::Enum {
+                        encoding: gimli::DW_ATE_signed,
+                        ..
+                    }
+                );
+                let buf = self.read_loc(&loc)?;
+                let mut bytes = [0; WORD_SIZE];
+                // sign extension
+                if is_signed && buf.last().is_some_and(|byte| byte & 0x80 != 0) {
+                    bytes = [0xff; WORD_SIZE];
+                }
+                bytes[..buf.len()].copy_from_slice(&buf);
+                u64::from_ne_bytes(bytes)
+            }
+            _ => bail!(DebuggerError::InvalidCast),
+        };
+        // any non zero value is true, truncating would lose high bits
+        let value = match target_type {
+            Type::Base {
+                encoding: gimli::DW_ATE_boolean,
+                ..
+            } => (value != 0) as u64,
+            _ => value,
+        };
+
+        Ok(TypedValueLoc::new(ValueLoc::Value(value), type_id))
+    }
+
     pub fn get_var(&self, path: &Path) -> Result<Var> {
         let loc = self.get_var_loc(path)?;
         let buf = self.read_loc(&loc)?;
@@ -457,19 +2012,113 @@ impl<R: gimli::Reader> DebugSession<R> {
         Ok(var)
     }
 
+    /// reads value of any type at the address, like `*(struct foo *)0x1234`
+    pub fn read_typed(&self, address: u64, type_id: TypeId) -> Result<Value> {
+        let size = self.get_type_size(type_id)?;
+        // reading unmapped memory fails with a vague io error
+        if !procmaps::is_readable(&self.get_memory_mappings()?, address, size as u64) {
+            bail!(DebuggerError::InaccessibleMemory(address));
+        }
+
+        let loc = TypedValueLoc::new(ValueLoc::Address(address), type_id);
+        let buf = self.read_loc(&loc)?;
+
+        Ok(Value::new(type_id, buf))
+    }
+
+    /// reads raw bytes at the address, like `x/16xb 0x1234`
+    pub fn read_bytes(&self, address: u64, size: usize) -> Result<Bytes> {
+        if !procmaps::is_readable(&self.get_memory_mappings()?, address, size as u64) {
+            bail!(DebuggerError::InaccessibleMemory(address));
+        }
+
+        self.read_address(address, size)
+    }
+
     fn get_value_loc_by_var_ref(&self, func: &str, var_ref: VarRef<R::Offset>) -> Result<TypedValueLoc> {
-        let unit_header = self.dwarf.debug_info.header_from_offset(var_ref.entry_ref.unit_offset)?;
-        let unit = self.dwarf.unit(unit_header)?;
+        let unit = self.get_unit(var_ref.entry_ref.unit_offset)?;
         let entry = unit.entry(var_ref.entry_ref.entry_offset)?;
-        let unit_ref = unit.unit_ref(&self.dwarf);
+        let unit_ref = unit.unit_ref(&self.image.dwarf);
 
         let location = entry.attr_value(gimli::DW_AT_location)?.ok_or(anyhow!("get location attr"))?;
-        let expr = location.exprloc_value().ok_or(anyhow!("get exprloc"))?;
+        let expr = match location.exprloc_value() {
+            Some(expr) => expr,
+            None => self.find_loclist_expr(unit_ref, location)?,
+        };
         let loc = self.evaluate(unit_ref, expr, func)?;
 
         Ok(TypedValueLoc::new(loc, var_ref.type_id))
     }
 
+    /// picks location expression from location list that covers pc of the selected frame
+    fn find_loclist_expr(&self, unit_ref: gimli::UnitRef<R>, location: gimli::AttributeValue<R>) -> Result<gimli::Expression<R>> {
+        self.find_loclist_expr_at(unit_ref, location, self.get_frame_pc()?)
+    }
+
+    fn find_loclist_expr_at(&self, unit_ref: gimli::UnitRef<R>, location: gimli::AttributeValue<R>, address: u64) -> Result<gimli::Expression<R>> {
+        let mut locations = unit_ref.attr_locations(location)?.ok_or(anyhow!("get location list"))?;
+        let pc = address - self.base_address;
+
+        while let Some(entry) = locations.next()? {
+            if entry.range.begin <= pc && pc < entry.range.end {
+                return Ok(entry.data);
+            }
+        }
+
+        bail!(DebuggerError::OptimizedOut)
+    }
+
+    /// variables visible at the address with description of their storage, sorted by name
+    pub fn get_scope(&self, address: u64) -> Result<Vec<(Rc<str>, String)>> {
+        let func = self.loc_finder.find_func_by_address(address);
+        let unit_name = self.loc_finder.find_unit(Some(address));
+        let mut scope = Vec::new();
+
+        for (name, var_ref) in self.loc_finder.get_vars(func.as_deref().map(|func| (func, address)), unit_name.as_deref()) {
+            scope.push((name, self.describe_var_location(var_ref, address)?));
+        }
+        scope.sort();
+
+        Ok(scope)
+    }
+
+    /// tells where variable is stored at the address, without evaluating location expression
+    fn describe_var_location(&self, var_ref: VarRef<R::Offset>, address: u64) -> Result<String> {
+        let unit = self.get_unit(var_ref.entry_ref.unit_offset)?;
+        let entry = unit.entry(var_ref.entry_ref.entry_offset)?;
+        let unit_ref = unit.unit_ref(&self.image.dwarf);
+
+        let location = match entry.attr_value(gimli::DW_AT_location)? {
+            Some(location) => location,
+            None => return Ok(String::from("optimized out")),
+        };
+        let expr = match location.exprloc_value() {
+            Some(expr) => expr,
+            None => match self.find_loclist_expr_at(unit_ref, location, address) {
+                Ok(expr) => expr,
+                Err(e) if matches!(e.downcast_ref::<DebuggerError>(), Some(DebuggerError::OptimizedOut)) => return Ok(String::from("optimized out")),
+                Err(e) => return Err(e),
+            },
+        };
+
+        let mut operations = expr.operations(unit_ref.encoding());
+        let (operation, next_operation) = (operations.next()?, operations.next()?);
+        let register_name = |register: gimli::Register| gimli::X86_64::register_name(register).unwrap_or("unknown");
+
+        let description = match (operation, next_operation) {
+            (Some(gimli::Operation::FrameOffset { offset }), None) => format!("frame base offset {}", offset),
+            (Some(gimli::Operation::Register { register }), None) => format!("register {}", register_name(register)),
+            (Some(gimli::Operation::RegisterOffset { register, offset, .. }), None) => format!("register {} offset {}", register_name(register), offset),
+            (Some(gimli::Operation::Address { address }), None) => format!("address {:#x}", self.base_address + address),
+            (Some(gimli::Operation::AddressIndex { index }), None) => format!("address {:#x}", self.base_address + unit_ref.address(index)?),
+            (Some(gimli::Operation::UnsignedConstant { value }), Some(gimli::Operation::StackValue)) => format!("constant {}", value),
+            (Some(gimli::Operation::SignedConstant { value }), Some(gimli::Operation::StackValue)) => format!("constant {}", value),
+            _ => String::from("complex DWARF expression"),
+        };
+
+        Ok(description)
+    }
+
     fn get_value_by_var_ref(&self, func: &str, var_ref: VarRef<R::Offset>) -> Result<Value> {
         let loc = self.get_value_loc_by_var_ref(func, var_ref)?;
         let buf = self.read_loc(&loc)?;
@@ -506,17 +2155,49 @@ impl<R: gimli::Reader> DebugSession<R> {
                     _ => Err(anyhow!(DebuggerError::InvalidPath)),
                 },
                 PostfixOperator::Index(index) => match self.type_storage.get(loc.type_id)? {
-                    Type::Array { subtype_id, count } => {
+                    Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) | Type::Typedef(_, subtype_id) => {
+                        self.unwind_loc(loc.with_type(subtype_id), postfix_operators)
+                    }
+                    Type::Array { subtype_id, count, bit_stride } => {
                         let count = self.get_array_count(count)?;
                         if index >= count {
                             bail!(DebuggerError::InvalidPath);
                         }
 
-                        let subtype_size = self.get_type_size(subtype_id)?;
-                        self.unwind_loc(
-                            TypedValueLoc::new(loc.location.with_offset(index * subtype_size)?, subtype_id),
-                            &postfix_operators[1..],
-                        )
+                        let offset = self.get_array_element_offset(subtype_id, bit_stride, index)?;
+                        self.unwind_loc(TypedValueLoc::new(loc.location.with_offset(offset)?, subtype_id), &postfix_operators[1..])
+                    }
+                    // pointer is indexed like in c, there is no count to check against
+                    Type::Pointer(subtype_id) | Type::String(subtype_id) => {
+                        let address = self.get_pointed_element_address(&loc, subtype_id, index)?;
+                        self.unwind_loc(TypedValueLoc::new(ValueLoc::Address(address), subtype_id), &postfix_operators[1..])
+                    }
+                    _ => Err(anyhow!(DebuggerError::InvalidPath)),
+                },
+                PostfixOperator::Slice(start, end) => match self.type_storage.get(loc.type_id)? {
+                    Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) | Type::Typedef(_, subtype_id) => {
+                        self.unwind_loc(loc.with_type(subtype_id), postfix_operators)
+                    }
+                    Type::Array { subtype_id, count, bit_stride } => {
+                        // flexible array members have no known count to check against
+                        let is_flexible = matches!(count, ArrayCount::Flexible);
+                        let count = self.get_array_count(count)?;
+                        if start > end || (!is_flexible && end > count) {
+                            bail!(DebuggerError::InvalidPath);
+                        }
+
+                        let offset = self.get_array_element_offset(subtype_id, bit_stride, start)?;
+                        let slice_type_id = self.type_storage.get_array_type(subtype_id, end - start, bit_stride);
+                        self.unwind_loc(TypedValueLoc::new(loc.location.with_offset(offset)?, slice_type_id), &postfix_operators[1..])
+                    }
+                    Type::Pointer(subtype_id) | Type::String(subtype_id) => {
+                        if start > end {
+                            bail!(DebuggerError::InvalidPath);
+                        }
+
+                        let address = self.get_pointed_element_address(&loc, subtype_id, start)?;
+                        let slice_type_id = self.type_storage.get_array_type(subtype_id, end - start, None);
+                        self.unwind_loc(TypedValueLoc::new(ValueLoc::Address(address), slice_type_id), &postfix_operators[1..])
                     }
                     _ => Err(anyhow!(DebuggerError::InvalidPath)),
                 },
@@ -525,6 +2206,23 @@ impl<R: gimli::Reader> DebugSession<R> {
         }
     }
 
+    /// address of the element `index` of the memory the pointer points to
+    fn get_pointed_element_address(&self, loc: &TypedValueLoc, subtype_id: TypeId, index: usize) -> Result<u64> {
+        if matches!(self.type_storage.get(subtype_id)?, Type::Void) {
+            bail!(DebuggerError::VoidDeref);
+        }
+        let ptr = self.read_loc(loc)?.get_u64_ne();
+        if ptr == 0 {
+            bail!(DebuggerError::NullDeref);
+        }
+
+        // index past the end of address space
+        index
+            .checked_mul(self.get_type_size(subtype_id)?)
+            .and_then(|offset| ptr.checked_add(offset as u64))
+            .ok_or(anyhow!(DebuggerError::InvalidPath))
+    }
+
     fn apply_prefix_operators(&self, loc: TypedValueLoc, operators: &[PrefixOperator]) -> Result<TypedValueLoc> {
         match operators.last() {
             Some(operator) => match operator {
@@ -533,7 +2231,8 @@ impl<R: gimli::Reader> DebugSession<R> {
                         let ref_type_id = self.type_storage.get_type_ref(loc.type_id);
                         self.apply_prefix_operators(TypedValueLoc::new(ValueLoc::Value(address), ref_type_id), &operators[..operators.len() - 1])
                     }
-                    _ => Err(anyhow!(DebuggerError::InvalidPath)),
+                    // variables in registers and computed values don't live in memory
+                    _ => Err(anyhow!(DebuggerError::NoAddress)),
                 },
                 PrefixOperator::Deref => match self.type_storage.get(loc.type_id)? {
                     Type::Pointer(subtype_id) => {
@@ -541,6 +2240,9 @@ impl<R: gimli::Reader> DebugSession<R> {
                         if ptr == 0 {
                             bail!(DebuggerError::InvalidPath);
                         }
+                        if matches!(self.type_storage.unwind_type(subtype_id)?, Type::Void) {
+                            bail!(DebuggerError::VoidDeref);
+                        }
 
                         self.apply_prefix_operators(TypedValueLoc::new(ValueLoc::Address(ptr), subtype_id), &operators[..operators.len() - 1])
                     }
@@ -554,11 +2256,17 @@ impl<R: gimli::Reader> DebugSession<R> {
         }
     }
 
-    fn get_var_name(path: &Path) -> Result<Rc<str>> {
+    pub fn get_var_name(path: &Path) -> Result<Rc<str>> {
         let mut name = String::new();
-        for prefix_operator in path.prefix_operators.iter() {
+        for (i, prefix_operator) in path.prefix_operators.iter().enumerate() {
+            if let Some(cast) = path.cast.as_ref().filter(|cast| cast.position == i) {
+                name.push_str(&cast.to_string());
+            }
             name.push(prefix_operator.into());
         }
+        if let Some(cast) = path.cast.as_ref().filter(|cast| cast.position == path.prefix_operators.len()) {
+            name.push_str(&cast.to_string());
+        }
 
         let last_field_operator_pos = path
             .postfix_operators
@@ -573,6 +2281,7 @@ impl<R: gimli::Reader> DebugSession<R> {
             match postfix_operator {
                 PostfixOperator::Field(field_name) => name.push_str(field_name),
                 PostfixOperator::Index(index) => name.push_str(&format!("[{}]", index)),
+                PostfixOperator::Slice(start, end) => name.push_str(&format!("[{}..{}]", start, end)),
             }
         }
 
@@ -581,7 +2290,9 @@ impl<R: gimli::Reader> DebugSession<R> {
 
     fn evaluate(&self, unit_ref: gimli::UnitRef<R>, expr: gimli::Expression<R>, func: &str) -> Result<ValueLoc> {
         let func_entry_ref = self.loc_finder.find_func(func).ok_or(anyhow!("no current func"))?;
-        let func_entry = unit_ref.entry(func_entry_ref.entry_offset)?;
+        // globals may come from another unit than the current function
+        let func_unit = self.get_unit(func_entry_ref.unit_offset)?;
+        let func_entry = func_unit.entry(func_entry_ref.entry_offset)?;
 
         let evaluation = self.exec(unit_ref, expr, &func_entry)?;
         let mut pieces = evaluation.result();
@@ -604,40 +2315,44 @@ impl<R: gimli::Reader> DebugSession<R> {
                 gimli::EvaluationResult::Complete => break,
                 gimli::EvaluationResult::RequiresFrameBase => {
                     let frame_base_attr = func_entry.attr_value(gimli::DW_AT_frame_base)?.ok_or(anyhow!("get frame base attr"))?;
-                    let fram_base_expr = frame_base_attr.exprloc_value().ok_or(anyhow!("get exprloc"))?; // todo loclists
-                    let frame_base_comleted_evaluation = self.exec(unit_ref, fram_base_expr, func_entry)?;
-                    let frame_base = frame_base_comleted_evaluation
-                        .value_result()
-                        .ok_or(anyhow!("get value result"))?
-                        .to_u64(!0u64)?;
+                    let fram_base_expr = match frame_base_attr.exprloc_value() {
+                        Some(expr) => expr,
+                        None => self.find_loclist_expr(unit_ref, frame_base_attr)?,
+                    };
+                    let frame_base = if fram_base_expr.0.to_slice()?.as_ref() == [gimli::DW_OP_call_frame_cfa.0] {
+                        // gcc and clang at -O0 use the cfa as frame base, so locals are fbreg offsets from it
+                        self.get_call_frame_cfa(unit_ref, func_entry)?
+                    } else {
+                        let frame_base_comleted_evaluation = self.exec(unit_ref, fram_base_expr, func_entry)?;
+                        self.get_evaluation_value(frame_base_comleted_evaluation)?
+                    };
                     log::trace!("frame base {:#x}", frame_base);
                     result = eval.resume_with_frame_base(frame_base)?;
                 }
                 gimli::EvaluationResult::RequiresCallFrameCfa => {
-                    let ip = self.get_ip()?;
-                    let cfa = self.unwinder.unwind_cfa(ip - self.base_address)?;
-
-                    let cfa_value = match cfa {
-                        gimli::CfaRule::RegisterAndOffset { register, offset } => {
-                            let register_value = self.get_register_value(register)?;
-                            let value = register_value as i64 + offset;
-                            value as u64
-                        }
-                        gimli::CfaRule::Expression(unwind_expression) => {
-                            let expression = self.unwinder.unwind_expression(&unwind_expression)?;
-                            let evaluation = self.exec(unit_ref, expression, func_entry)?;
-                            let value = evaluation.value_result().ok_or(anyhow!("get value result"))?;
-                            value.to_u64(!0u64)?
-                        }
-                    };
-
-                    log::trace!("cfa is {:#x}", cfa_value);
+                    let cfa_value = self.get_call_frame_cfa(unit_ref, func_entry)?;
                     result = eval.resume_with_call_frame_cfa(cfa_value)?;
                 }
+                gimli::EvaluationResult::RequiresRegister { register, base_type } => {
+                    // DW_OP_bregN, DW_OP_bregx
+                    if gimli::ReaderOffset::into_u64(base_type.0) != 0 {
+                        bail!("unsupported register base type");
+                    }
+                    let value = self.get_register_value(register)?;
+                    log::trace!("requires register {:?} = {:#x}", register, value);
+                    result = eval.resume_with_register(gimli::Value::Generic(value))?;
+                }
                 gimli::EvaluationResult::RequiresRelocatedAddress(address) => {
                     log::trace!("requires relocated address {:#x}", address);
                     result = eval.resume_with_relocated_address(self.base_address + address)?;
                 }
+                gimli::EvaluationResult::RequiresIndexedAddress { index, relocate } => {
+                    // DW_OP_addrx and DW_OP_constx, address is taken from .debug_addr at the unit addr base
+                    let address = unit_ref.address(index)?;
+                    log::trace!("requires indexed address {:?} = {:#x}", index, address);
+                    let address = if relocate { self.base_address + address } else { address };
+                    result = eval.resume_with_indexed_address(address)?;
+                }
                 gimli::EvaluationResult::RequiresMemory { address, size, .. } => {
                     log::trace!("requires memory {} bytes at {:#x}", size, address);
                     let mut buf = self.read_address(address, size as usize)?;
@@ -650,6 +2365,8 @@ impl<R: gimli::Reader> DebugSession<R> {
                     };
                     result = eval.resume_with_memory(value)?;
                 }
+                // DW_OP_form_tls_address, offset in the TLS block of the module
+                gimli::EvaluationResult::RequiresTls(_) => bail!(DebuggerError::UnsupportedThreadLocal),
                 _ => bail!("can't provide {:?}", result),
             }
         }
@@ -657,29 +2374,141 @@ impl<R: gimli::Reader> DebugSession<R> {
         Ok(eval)
     }
 
+    /// cfa of the selected frame, the value of the stack pointer before the call instruction
+    fn get_call_frame_cfa(&self, unit_ref: gimli::UnitRef<R>, func_entry: &gimli::DebuggingInformationEntry<R>) -> Result<u64> {
+        let pc = self.get_frame_pc()?;
+        let cfa = self.image.unwinder.unwind_cfa(pc - self.base_address)?;
+
+        let cfa_value = match cfa {
+            gimli::CfaRule::RegisterAndOffset { register, offset } => {
+                let register_value = self.get_register_value(register)?;
+                let value = register_value as i64 + offset;
+                value as u64
+            }
+            gimli::CfaRule::Expression(unwind_expression) => {
+                let expression = self.image.unwinder.unwind_expression(&unwind_expression)?;
+                let evaluation = self.exec(unit_ref, expression, func_entry)?;
+                self.get_evaluation_value(evaluation)?
+            }
+        };
+        log::trace!("cfa is {:#x}", cfa_value);
+
+        Ok(cfa_value)
+    }
+
+    /// value computed by an expression, expression like DW_OP_reg6 (rbp) gives a location, the value is the register content
+    fn get_evaluation_value(&self, evaluation: gimli::Evaluation<R>) -> Result<u64> {
+        if let Some(value) = evaluation.value_result() {
+            return Ok(value.to_u64(!0u64)?);
+        }
+
+        match evaluation.result().first().map(|piece| &piece.location) {
+            Some(gimli::Location::Register { register }) => self.get_register_value(*register),
+            Some(gimli::Location::Address { address }) => Ok(*address),
+            _ => bail!("unsupported expression value"),
+        }
+    }
+
     fn get_context(&self) -> Result<Context> {
         let regs = ptrace::getregs(self.child_pid())?;
         Ok(Context::new(regs))
     }
 
+    /// context of the selected frame
+    pub fn get_frame_context(&self) -> Result<Context> {
+        self.unwind_frames(self.selected_frame.get())
+    }
+
+    fn get_frame_pc(&self) -> Result<u64> {
+        Ok(self.get_frame_context()?.pc())
+    }
+
+    /// callers get only callee saved registers restored by unwinding
+    fn unwind_frames(&self, frame: usize) -> Result<Context> {
+        if self.frames.borrow().is_empty() {
+            self.frames.borrow_mut().push(self.get_context()?);
+        }
+        while self.frames.borrow().len() <= frame {
+            let ctx = self.frames.borrow().last().cloned().unwrap();
+            let caller_ctx = self.unwind_context(&ctx)?.ok_or(DebuggerError::FrameNotFound)?;
+            self.frames.borrow_mut().push(caller_ctx);
+        }
+
+        Ok(self.frames.borrow()[frame].clone())
+    }
+
+    /// selects the innermost frame and drops unwound frames
+    fn reset_frames(&self) {
+        self.selected_frame.set(0);
+        self.frames.borrow_mut().clear();
+    }
+
+    /// pcs of frames from the innermost one up to main, unwinding stops at code without debug info
+    pub fn backtrace(&self) -> Result<Vec<u64>> {
+        let mut ctx = self.get_context()?;
+        let mut frames = vec![ctx.pc()];
+
+        while !self.loc_finder.is_inside_main(ctx.pc()) {
+            ctx = match self.unwind_context(&ctx)? {
+                Some(caller_ctx) => caller_ctx,
+                None => break,
+            };
+            // signal trampoline has no debug info, but the interrupted code is above it
+            if self.loc_finder.find_func_by_address(ctx.pc()).is_none() && !self.is_signal_trampoline(ctx.ip()) {
+                break;
+            }
+            frames.push(ctx.pc());
+        }
+
+        Ok(frames)
+    }
+
+    pub fn get_selected_frame(&self) -> usize {
+        self.selected_frame.get()
+    }
+
+    /// variables are read in the selected frame until the program moves, returns pc of the frame
+    pub fn select_frame(&self, frame: usize) -> Result<u64> {
+        // callers of main and code without debug info aren't in backtrace, nothing can be read there
+        if frame >= self.backtrace()?.len() {
+            bail!(DebuggerError::FrameNotFound);
+        }
+        let pc = self.unwind_frames(frame)?.pc();
+        self.selected_frame.set(frame);
+
+        Ok(pc)
+    }
+
+    /// register of the selected frame
     fn get_register_value(&self, register: gimli::Register) -> Result<u64> {
-        let mut regs = ptrace::getregs(self.child_pid())?;
+        let mut regs = self.get_frame_context()?.regs;
         let value_ref = Self::get_register_ref(&mut regs, register)?;
 
         Ok(*value_ref)
     }
 
     fn set_register_value(&self, register: gimli::Register, value: u64) -> Result<()> {
+        // registers of callers are saved on the stack or not saved at all
+        if self.selected_frame.get() != 0 {
+            bail!(DebuggerError::NoAddress);
+        }
+
         let mut regs = ptrace::getregs(self.child_pid())?;
         let value_ref = Self::get_register_ref(&mut regs, register)?;
         *value_ref = value;
         ptrace::setregs(self.child_pid(), regs)?;
+        self.frames.borrow_mut().clear();
 
         Ok(())
     }
 
     fn get_register_ref(regs: &mut libc::user_regs_struct, register: gimli::Register) -> Result<&mut u64> {
-        let register_name = gimli::X86_64::register_name(register).ok_or(anyhow!("get {} register", register.0))?;
+        let unsupported = || DebuggerError::UnsupportedRegister {
+            name: gimli::X86_64::register_name(register).unwrap_or("unknown").to_string(),
+            number: register.0,
+            class: Self::get_register_class(register),
+        };
+        let register_name = gimli::X86_64::register_name(register).ok_or_else(unsupported)?;
 
         let value = match register_name {
             "rax" => &mut regs.rax,
@@ -698,41 +2527,86 @@ impl<R: gimli::Reader> DebugSession<R> {
             "r13" => &mut regs.r13,
             "r14" => &mut regs.r14,
             "r15" => &mut regs.r15,
-            _ => bail!("get {} register", register_name),
+            _ => bail!(unsupported()),
         };
 
         Ok(value)
     }
 
-    pub fn read_c_string(&self, addr: u64) -> Result<String> {
+    fn get_register_class(register: gimli::Register) -> &'static str {
+        match register.0 {
+            0..=15 => "integer",
+            16 => "pointer",                                          // return address, rip
+            17..=32 | 41..=48 | 64 | 67..=82 | 118..=125 => "vector", // xmm, mm, mxcsr and k registers
+            33..=40 | 65 | 66 => "x87",                               // st, fcw and fsw
+            49 => "flags",                                            // rflags
+            50..=55 | 58 | 59 | 62 | 63 => "segment",                 // es-gs, fs.base, gs.base, tr and ldtr
+            _ => "unknown",
+        }
+    }
+
+    /// reads null terminated string, but not more than max_len bytes. Returns bytes and whether the string was cut
+    pub fn read_c_string(&self, addr: u64, max_len: Option<usize>) -> Result<(Vec<u8>, bool)> {
         log::trace!("read c string at {:#x}", addr);
 
-        if addr == 0 {
-            return Ok(String::from("null")); // special case
-        }
+        self.read_string(addr, 1, max_len)
+    }
 
+    /// reads null terminated utf-16 or utf-32 string, but not more than max_len chars
+    pub fn read_wide_string(&self, addr: u64, char_size: usize, max_len: Option<usize>) -> Result<(String, bool)> {
+        log::trace!("read wide string at {:#x}", addr);
+
+        let (buf, truncated) = self.read_string(addr, char_size, max_len)?;
+        let s = match char_size {
+            2 => char::decode_utf16(buf.chunks_exact(2).map(|c| u16::from_ne_bytes([c[0], c[1]])))
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect(),
+            4 => buf
+                .chunks_exact(4)
+                .map(|c| char::from_u32(u32::from_ne_bytes([c[0], c[1], c[2], c[3]])).unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect(),
+            _ => bail!("unsupported char size"),
+        };
+
+        Ok((s, truncated))
+    }
+
+    /// reads chars of char_size bytes until null char
+    fn read_string(&self, addr: u64, char_size: usize, max_len: Option<usize>) -> Result<(Vec<u8>, bool)> {
         let mut buf = Vec::new();
         let mut read_buf = [0; READ_MEM_BUF_SIZE];
 
-        // todo maybe process_vm_readv
-        let mut procmem = fs::File::open(format!("/proc/{}/mem", self.child_pid()))?;
-        procmem.seek(io::SeekFrom::Start(addr))?;
-
         loop {
-            let n = procmem.read(&mut read_buf)?;
+            let n = self.read_memory_chunk(addr + buf.len() as u64, &mut read_buf)?;
+            if n == 0 {
+                bail!("can't read string");
+            }
+
+            // char could be split between reads, so search from the last whole char
+            let start = buf.len() - buf.len() % char_size;
+            buf.extend_from_slice(&read_buf[..n]);
+            let terminator = buf[start..]
+                .chunks_exact(char_size)
+                .position(|c| c.iter().all(|&b| b == 0))
+                .map(|pos| start + pos * char_size);
+            if let Some(len) = terminator {
+                buf.truncate(len);
+            }
 
-            match read_buf[..n].iter().position(|&b| b == 0) {
-                Some(pos) => {
-                    buf.extend_from_slice(&read_buf[..pos]);
-                    let s = String::from_utf8(buf)?;
-                    return Ok(s);
+            if let Some(max_len) = max_len {
+                if buf.len() > max_len * char_size {
+                    buf.truncate(max_len * char_size);
+                    return Ok((buf, true));
                 }
-                None => buf.extend_from_slice(&read_buf),
+            }
+
+            if terminator.is_some() {
+                return Ok((buf, false));
             }
         }
     }
 
-    fn read_loc(&self, loc: &TypedValueLoc) -> Result<Bytes> {
+    pub fn read_loc(&self, loc: &TypedValueLoc) -> Result<Bytes> {
         let size = self.get_type_size(loc.type_id)?;
         if size == 0 {
             return Ok(Bytes::new());
@@ -770,30 +2644,55 @@ impl<R: gimli::Reader> DebugSession<R> {
         Ok(buf.into())
     }
 
-    fn read_memory(&self, addr: u64, buf: &mut Vec<u8>) -> Result<()> {
-        // todo maybe process_vm_readv
-        let mut procmem = fs::File::open(format!("/proc/{}/mem", self.child_pid()))?;
-        procmem.seek(io::SeekFrom::Start(addr))?;
-        procmem.read_exact(buf.as_mut_slice())?;
+    fn read_memory(&self, addr: u64, buf: &mut [u8]) -> Result<()> {
+        let mut read = 0;
+        while read < buf.len() {
+            let n = self.read_memory_chunk(addr + read as u64, &mut buf[read..])?;
+            if n == 0 {
+                bail!(DebuggerError::InaccessibleMemory(addr + read as u64));
+            }
+            read += n;
+        }
 
         Ok(())
     }
 
+    /// reads in one syscall, could read less than asked at the end of a mapping
+    fn read_memory_chunk(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        let remote_iov = [RemoteIoVec {
+            base: addr as usize,
+            len: buf.len(),
+        }];
+
+        match uio::process_vm_readv(self.child_pid(), &mut [IoSliceMut::new(buf)], &remote_iov) {
+            Ok(n) => Ok(n),
+            // /proc/<pid>/mem ignores page protection, like ptrace does
+            Err(Errno::EFAULT | Errno::EPERM) => self.with_procmem(|procmem| procmem.read_at(buf, addr)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// opens /proc/<pid>/mem once for both reads and writes
+    fn with_procmem<T>(&self, f: impl FnOnce(&fs::File) -> io::Result<T>) -> Result<T> {
+        let mut procmem = self.procmem.borrow_mut();
+        let procmem = match procmem.as_ref() {
+            Some(procmem) => procmem,
+            None => procmem.insert(fs::OpenOptions::new().read(true).write(true).open(format!("/proc/{}/mem", self.child_pid()))?),
+        };
+
+        Ok(f(procmem)?)
+    }
+
     pub fn write_location(&self, location: ValueLoc, mut value: Bytes) -> Result<()> {
         log::trace!("write {:?} to {:?}", value, location);
 
         match location {
             ValueLoc::Register { register, offset } => {
-                if offset as usize + value.len() > WORD_SIZE {
-                    bail!(DebuggerError::InvalidValue);
-                }
-
-                let new_value = if value.len() == WORD_SIZE {
+                let new_value = if offset == 0 && value.len() == WORD_SIZE {
                     value.get_u64_ne()
                 } else {
-                    let mut buf = self.get_register_value(register)?.to_ne_bytes();
-                    buf[offset as usize..offset as usize + value.len()].copy_from_slice(&value);
-                    u64::from_ne_bytes(buf)
+                    // bytes of the register outside of the value must stay as they are
+                    location::replace_register_bytes(self.get_register_value(register)?, offset, &value)?
                 };
 
                 self.set_register_value(register, new_value)
@@ -804,9 +2703,9 @@ impl<R: gimli::Reader> DebugSession<R> {
     }
 
     fn write_memory(&self, addr: u64, buf: &[u8]) -> Result<()> {
-        let mut procmem = fs::OpenOptions::new().write(true).open(format!("/proc/{}/mem", self.child_pid()))?;
-        procmem.seek(io::SeekFrom::Start(addr))?;
-        procmem.write_all(buf)?;
+        self.with_procmem(|procmem| procmem.write_all_at(buf, addr))?;
+        // saved registers of callers could be overwritten
+        self.frames.borrow_mut().clear();
 
         Ok(())
     }
@@ -849,6 +2748,7 @@ impl<R: gimli::Reader> DebugSession<R> {
 
         ptrace::step(self.child_pid(), None)?;
         if let wait::WaitStatus::Exited(_, _) = wait::waitpid(self.child_pid(), None)? {
+            self.procmem.take();
             self.state.set(SessionState::Exited);
             bail!("child exited");
         }
@@ -879,26 +2779,113 @@ impl<R: gimli::Reader> DebugSession<R> {
             Type::Base { size, .. } | Type::Struct { size, .. } | Type::Enum { size, .. } | Type::Union { size, .. } => Ok(size as usize),
             Type::Const(subtype_id) | Type::Volatile(subtype_id) | Type::Atomic(subtype_id) | Type::Typedef(_, subtype_id) => self.get_type_size(subtype_id),
             Type::Pointer(_) | Type::String(_) | Type::Func(_) => Ok(WORD_SIZE),
-            Type::Array { subtype_id, count } => {
-                let subtype_size = self.get_type_size(subtype_id)?;
+            Type::Array { subtype_id, count, bit_stride } => {
+                let bit_stride = self.get_array_bit_stride(subtype_id, bit_stride)?;
                 let count = self.get_array_count(count)?;
-                Ok(subtype_size * count)
+                Ok((count * bit_stride).div_ceil(8))
+            }
+        }
+    }
+
+    /// bits between the starts of array elements
+    pub fn get_array_bit_stride(&self, subtype_id: TypeId, bit_stride: Option<u64>) -> Result<usize> {
+        match bit_stride {
+            Some(bit_stride) => Ok(bit_stride as usize),
+            None => Ok(self.get_type_size(subtype_id)? * 8),
+        }
+    }
+
+    /// elements of bit packed arrays don't start at byte boundary, so they have no address
+    fn get_array_element_offset(&self, subtype_id: TypeId, bit_stride: Option<u64>, index: usize) -> Result<usize> {
+        let bit_offset = index * self.get_array_bit_stride(subtype_id, bit_stride)?;
+        if !bit_offset.is_multiple_of(8) {
+            bail!(DebuggerError::NoAddress);
+        }
+
+        Ok(bit_offset / 8)
+    }
+
+    fn get_unit(&self, offset: gimli::DebugInfoOffset<R::Offset>) -> Result<Rc<gimli::Unit<R>>> {
+        if let Some(unit) = self.units.borrow().get(&offset) {
+            return Ok(unit.clone());
+        }
+
+        let unit_header = self.image.dwarf.debug_info.header_from_offset(offset)?;
+        let unit = Rc::new(self.image.dwarf.unit(unit_header)?);
+        self.units.borrow_mut().insert(offset, unit.clone());
+
+        Ok(unit)
+    }
+
+    /// array bound kept in a variable of the current function, it's an unsigned integer
+    fn read_bound_var(&self, unit_ref: gimli::UnitRef<R>, func: &str, offset: gimli::UnitOffset<R::Offset>) -> Result<usize> {
+        let entry = unit_ref.entry(offset)?;
+        if !matches!(entry.tag(), gimli::DW_TAG_variable | gimli::DW_TAG_formal_parameter) {
+            bail!("unsupported array bound {}", entry.tag());
+        }
+
+        let location = entry.attr_value(gimli::DW_AT_location)?.ok_or(DebuggerError::OptimizedOut)?;
+        let expr = match location.exprloc_value() {
+            Some(expr) => expr,
+            None => self.find_loclist_expr(unit_ref, location)?,
+        };
+        let size = Self::get_entry_type_size(unit_ref, &entry)?;
+        if !(1..=WORD_SIZE).contains(&size) {
+            bail!("unsupported array bound size {}", size);
+        }
+
+        let value = match self.evaluate(unit_ref, expr, func)? {
+            ValueLoc::Register { register, .. } => self.get_register_value(register)?,
+            ValueLoc::Address(address) => {
+                let mut buf = vec![0; size];
+                self.read_memory(address, &mut buf)?;
+                buf.resize(WORD_SIZE, 0);
+                u64::from_le_bytes(buf.try_into().unwrap())
+            }
+            ValueLoc::Value(value) => value,
+        };
+        let mask = match size {
+            WORD_SIZE => !0,
+            _ => (1u64 << (size * 8)) - 1,
+        };
+
+        Ok((value & mask) as usize)
+    }
+
+    /// byte size of the entry type, typedefs and qualifiers are followed
+    fn get_entry_type_size(unit_ref: gimli::UnitRef<R>, entry: &gimli::DebuggingInformationEntry<R>) -> Result<usize> {
+        let mut offset = match entry.attr_value(gimli::DW_AT_type)? {
+            Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+            _ => bail!("get type attr"),
+        };
+
+        loop {
+            let type_entry = unit_ref.entry(offset)?;
+            if let Some(size) = type_entry.attr_value(gimli::DW_AT_byte_size)?.and_then(|value| value.udata_value()) {
+                return Ok(size as usize);
             }
+            offset = match type_entry.attr_value(gimli::DW_AT_type)? {
+                Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+                _ => bail!("get type size"),
+            };
         }
     }
 
     fn get_vla_size(&self, entry_ref: EntryRef<R::Offset>) -> Result<usize> {
-        let unit_header = self.dwarf.debug_info.header_from_offset(entry_ref.unit_offset)?;
-        let unit = self.dwarf.unit(unit_header)?;
-        let unit_ref = unit.unit_ref(&self.dwarf);
+        let unit = self.get_unit(entry_ref.unit_offset)?;
+        let unit_ref = unit.unit_ref(&self.image.dwarf);
         let entry = unit_ref.entry(entry_ref.entry_offset)?;
 
-        let ip = self.get_ip()?;
-        let func = self.loc_finder.find_func_by_address(ip).ok_or(anyhow!("get current func"))?;
+        let pc = self.get_frame_pc()?;
+        let func = self.loc_finder.find_func_by_address(pc).ok_or(DebuggerError::NoFrameDebugInfo)?;
         let func_entry_ref = self.loc_finder.find_func(&func).ok_or(anyhow!("no current func"))?;
         let func_entry = unit_ref.entry(func_entry_ref.entry_offset)?;
 
         let exec_attr = |value: gimli::AttributeValue<R>| -> Result<usize> {
+            // bound could be stored in a variable, like __vla_expr0 of clang
+            if let gimli::AttributeValue::UnitRef(offset) = value {
+                return self.read_bound_var(unit_ref, &func, offset);
+            }
             let expr = value.exprloc_value().ok_or(anyhow!("get attr expr"))?;
             let evaluation = self.exec(unit_ref, expr, &func_entry)?;
             let value = evaluation.value_result().ok_or(anyhow!("get expr value result"))?;
@@ -920,3 +2907,26 @@ impl<R: gimli::Reader> DebugSession<R> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dwarf::Reader;
+
+    #[test]
+    fn test_get_register_class() {
+        let class = |register| DebugSession::<Reader>::get_register_class(register);
+
+        assert_eq!(class(gimli::X86_64::RAX), "integer");
+        assert_eq!(class(gimli::X86_64::R15), "integer");
+        assert_eq!(class(gimli::X86_64::RA), "pointer");
+        assert_eq!(class(gimli::X86_64::RFLAGS), "flags");
+        assert_eq!(class(gimli::X86_64::ES), "segment");
+        assert_eq!(class(gimli::X86_64::GS), "segment");
+        assert_eq!(class(gimli::X86_64::FS_BASE), "segment");
+        assert_eq!(class(gimli::X86_64::XMM0), "vector");
+        assert_eq!(class(gimli::X86_64::MXCSR), "vector");
+        assert_eq!(class(gimli::X86_64::ST0), "x87");
+        assert_eq!(class(gimli::Register(200)), "unknown");
+    }
+}
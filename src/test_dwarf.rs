@@ -0,0 +1,275 @@
+//! In-memory debug info for unit tests. It's written with `gimli::write` and read back,
+//! so parsing and lookups are tested without compiling a program and tracing it
+
+use std::rc::Rc;
+
+use gimli::leb128;
+use gimli::write::{self, Address, AttributeValue, LineProgram, LineString, UnitEntryId, UnitId};
+
+use crate::dwarf_parser::DwarfParser;
+use crate::loc_finder::LocFinder;
+use crate::types::TypeStorage;
+
+pub type Reader = gimli::EndianRcSlice<gimli::RunTimeEndian>;
+
+const ENCODING: gimli::Encoding = gimli::Encoding {
+    format: gimli::Format::Dwarf32,
+    version: 4,
+    address_size: 8,
+};
+
+pub const COMP_DIR: &str = "/src";
+
+pub struct DwarfBuilder {
+    dwarf: write::Dwarf,
+}
+
+impl DwarfBuilder {
+    pub fn new() -> Self {
+        Self { dwarf: write::Dwarf::new() }
+    }
+
+    /// compile unit for a single source file, compiled in `COMP_DIR`
+    pub fn add_unit(&mut self, name: &str, low_pc: u64, high_pc: u64) -> UnitId {
+        let mut line_program = LineProgram::new(
+            ENCODING,
+            gimli::LineEncoding::default(),
+            LineString::String(COMP_DIR.into()),
+            None,
+            LineString::String(name.into()),
+            None,
+        );
+        line_program.add_file(LineString::String(name.into()), line_program.default_directory(), None);
+
+        let unit_id = self.dwarf.units.add(write::Unit::new(ENCODING, line_program));
+        let unit = self.dwarf.units.get_mut(unit_id);
+        let root = unit.get_mut(unit.root());
+        root.set(gimli::DW_AT_name, string(name));
+        root.set(gimli::DW_AT_comp_dir, string(COMP_DIR));
+        root.set(gimli::DW_AT_low_pc, AttributeValue::Address(Address::Constant(low_pc)));
+        root.set(gimli::DW_AT_high_pc, AttributeValue::Udata(high_pc - low_pc));
+
+        unit_id
+    }
+
+    /// adds entry under the parent, or under the unit root if there is no parent
+    pub fn add_entry(&mut self, unit_id: UnitId, parent: Option<UnitEntryId>, tag: gimli::DwTag, attrs: &[(gimli::DwAt, AttributeValue)]) -> UnitEntryId {
+        let unit = self.dwarf.units.get_mut(unit_id);
+        let parent = parent.unwrap_or(unit.root());
+        let entry_id = unit.add(parent, tag);
+        let entry = unit.get_mut(entry_id);
+        for (attr, value) in attrs {
+            entry.set(*attr, value.clone());
+        }
+
+        entry_id
+    }
+
+    /// function with `[low_pc, high_pc)` range
+    pub fn add_func(&mut self, unit_id: UnitId, name: &str, low_pc: u64, high_pc: u64) -> UnitEntryId {
+        self.add_entry(
+            unit_id,
+            None,
+            gimli::DW_TAG_subprogram,
+            &[
+                (gimli::DW_AT_name, string(name)),
+                (gimli::DW_AT_low_pc, AttributeValue::Address(Address::Constant(low_pc))),
+                (gimli::DW_AT_high_pc, AttributeValue::Udata(high_pc - low_pc)),
+            ],
+        )
+    }
+
+    /// value of DW_AT_ranges covering `[low_pc, high_pc)` ranges
+    pub fn add_range_list(&mut self, unit_id: UnitId, ranges: &[(u64, u64)]) -> AttributeValue {
+        // addresses are absolute, not offsets from the unit low_pc
+        let base_address = write::Range::BaseAddress { address: Address::Constant(0) };
+        let ranges = [base_address]
+            .into_iter()
+            .chain(ranges.iter().map(|&(low_pc, high_pc)| write::Range::StartEnd {
+                begin: Address::Constant(low_pc),
+                end: Address::Constant(high_pc),
+            }))
+            .collect();
+        let range_list_id = self.dwarf.units.get_mut(unit_id).ranges.add(write::RangeList(ranges));
+        AttributeValue::RangeListRef(range_list_id)
+    }
+
+    /// global variable with the given type
+    pub fn add_var(&mut self, unit_id: UnitId, name: &str, type_entry_id: UnitEntryId) -> UnitEntryId {
+        self.add_entry(
+            unit_id,
+            None,
+            gimli::DW_TAG_variable,
+            &[(gimli::DW_AT_name, string(name)), (gimli::DW_AT_type, AttributeValue::UnitRef(type_entry_id))],
+        )
+    }
+
+    pub fn add_base_type(&mut self, unit_id: UnitId, name: &str, encoding: gimli::DwAte, size: u8) -> UnitEntryId {
+        self.add_entry(
+            unit_id,
+            None,
+            gimli::DW_TAG_base_type,
+            &[
+                (gimli::DW_AT_name, string(name)),
+                (gimli::DW_AT_encoding, AttributeValue::Encoding(encoding)),
+                (gimli::DW_AT_byte_size, AttributeValue::Data1(size)),
+            ],
+        )
+    }
+
+    /// sequence of line rows starting at `address`, rows are `(address offset, line)`
+    pub fn add_line_sequence(&mut self, unit_id: UnitId, address: u64, rows: &[(u64, u64)], size: u64) {
+        self.add_line_sequence_with_prologue_end(unit_id, address, rows, None, size);
+    }
+
+    /// the row at `prologue_end` address offset is marked as the end of function prologue
+    pub fn add_line_sequence_with_prologue_end(&mut self, unit_id: UnitId, address: u64, rows: &[(u64, u64)], prologue_end: Option<u64>, size: u64) {
+        // rows start with the first file of the unit
+        let line_program = &mut self.dwarf.units.get_mut(unit_id).line_program;
+
+        line_program.begin_sequence(Some(Address::Constant(address)));
+        for &(address_offset, line) in rows {
+            let row = line_program.row();
+            row.address_offset = address_offset;
+            row.line = line;
+            row.is_statement = true;
+            row.prologue_end = prologue_end == Some(address_offset);
+            line_program.generate_row();
+        }
+        line_program.end_sequence(size);
+    }
+
+    pub fn build(mut self) -> gimli::Dwarf<Reader> {
+        let mut sections = write::Sections::new(write::EndianVec::new(gimli::RunTimeEndian::Little));
+        self.dwarf.write(&mut sections).unwrap();
+
+        gimli::Dwarf::load(|id| -> gimli::Result<Reader> {
+            let data = sections.get(id).map(|section| section.slice()).unwrap_or_default();
+            Ok(Reader::new(Rc::from(data), gimli::RunTimeEndian::Little))
+        })
+        .unwrap()
+    }
+
+    pub fn parse(self) -> (LocFinder<Reader>, TypeStorage<Reader>) {
+        DwarfParser::parse(&self.build(), 0).unwrap()
+    }
+}
+
+pub fn string(value: &str) -> AttributeValue {
+    AttributeValue::String(value.into())
+}
+
+/// value of hand written entry attribute, the form is picked by the variant
+pub enum RawValue {
+    String(&'static str),
+    Data1(u8),
+    Data8(u64),
+    Addr(u64),
+    /// value is stored in the abbreviation, DWARF 5 only
+    ImplicitConst(i64),
+    /// pre-order index of the referenced entry in the unit, root is 0
+    Ref(usize),
+}
+
+impl RawValue {
+    fn form(&self) -> gimli::DwForm {
+        match self {
+            RawValue::String(_) => gimli::DW_FORM_string,
+            RawValue::Data1(_) => gimli::DW_FORM_data1,
+            RawValue::Data8(_) => gimli::DW_FORM_data8,
+            RawValue::Addr(_) => gimli::DW_FORM_addr,
+            RawValue::ImplicitConst(_) => gimli::DW_FORM_implicit_const,
+            RawValue::Ref(_) => gimli::DW_FORM_ref4,
+        }
+    }
+}
+
+pub struct RawEntry {
+    pub tag: gimli::DwTag,
+    pub attrs: Vec<(gimli::DwAt, RawValue)>,
+    pub children: Vec<RawEntry>,
+}
+
+/// DWARF 5 compile unit written byte by byte, for forms `gimli::write` doesn't produce (like DW_FORM_implicit_const).
+/// Every entry gets its own abbreviation, there is no line program
+pub fn build_raw_unit(root: &RawEntry) -> gimli::Dwarf<Reader> {
+    // unit header is unit_length, version, unit_type, address_size and debug_abbrev_offset
+    const HEADER_SIZE: usize = 12;
+
+    struct Writer<'a> {
+        ref_offsets: &'a [u32],
+        offsets: Vec<u32>,
+        info: Vec<u8>,
+        abbrev: Vec<u8>,
+    }
+
+    impl Writer<'_> {
+        fn write_entry(&mut self, entry: &RawEntry) {
+            self.offsets.push((HEADER_SIZE + self.info.len()) as u32);
+            let code = self.offsets.len() as u64;
+            leb128::write::unsigned(&mut self.abbrev, code).unwrap();
+            leb128::write::unsigned(&mut self.abbrev, entry.tag.0.into()).unwrap();
+            self.abbrev.push(u8::from(!entry.children.is_empty()));
+            leb128::write::unsigned(&mut self.info, code).unwrap();
+            for (attr, value) in &entry.attrs {
+                leb128::write::unsigned(&mut self.abbrev, attr.0.into()).unwrap();
+                leb128::write::unsigned(&mut self.abbrev, value.form().0.into()).unwrap();
+                match value {
+                    RawValue::String(value) => {
+                        self.info.extend_from_slice(value.as_bytes());
+                        self.info.push(0);
+                    }
+                    RawValue::Data1(value) => self.info.push(*value),
+                    RawValue::Data8(value) | RawValue::Addr(value) => self.info.extend_from_slice(&value.to_le_bytes()),
+                    RawValue::ImplicitConst(value) => {
+                        leb128::write::signed(&mut self.abbrev, *value).unwrap();
+                    }
+                    // referenced entry could be written later, its offset is known from the first pass
+                    RawValue::Ref(index) => self.info.extend_from_slice(&self.ref_offsets.get(*index).copied().unwrap_or(0).to_le_bytes()),
+                }
+            }
+            self.abbrev.extend_from_slice(&[0, 0]);
+
+            for child in &entry.children {
+                self.write_entry(child);
+            }
+            if !entry.children.is_empty() {
+                self.info.push(0);
+            }
+        }
+
+        fn write<'a>(ref_offsets: &'a [u32], root: &RawEntry) -> Writer<'a> {
+            let mut writer = Writer {
+                ref_offsets,
+                offsets: Vec::new(),
+                info: Vec::new(),
+                abbrev: Vec::new(),
+            };
+            writer.write_entry(root);
+            writer.abbrev.push(0);
+            writer
+        }
+    }
+
+    // references have fixed size, so the first pass finds entry offsets
+    let offsets = Writer::write(&[], root).offsets;
+    let Writer { mut info, abbrev, .. } = Writer::write(&offsets, root);
+
+    let mut section = Vec::new();
+    section.extend_from_slice(&((HEADER_SIZE - 4 + info.len()) as u32).to_le_bytes());
+    section.extend_from_slice(&5u16.to_le_bytes());
+    section.push(gimli::DW_UT_compile.0);
+    section.push(8);
+    section.extend_from_slice(&0u32.to_le_bytes());
+    section.append(&mut info);
+
+    gimli::Dwarf::load(|id| -> gimli::Result<Reader> {
+        let data = match id {
+            gimli::SectionId::DebugInfo => section.clone(),
+            gimli::SectionId::DebugAbbrev => abbrev.clone(),
+            _ => Vec::new(),
+        };
+        Ok(Reader::new(Rc::from(data), gimli::RunTimeEndian::Little))
+    })
+    .unwrap()
+}
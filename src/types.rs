@@ -32,6 +32,7 @@ pub enum Type<R: gimli::Reader> {
     Array {
         subtype_id: TypeId,
         count: ArrayCount<R>,
+        bit_stride: Option<u64>, // distance between elements of packed arrays, element size otherwise
     },
     Struct {
         name: Option<Rc<str>>,
@@ -54,6 +55,7 @@ pub enum Type<R: gimli::Reader> {
         name: Option<Rc<str>>,
         return_type_id: TypeId,
         args: Rc<Vec<TypeId>>,
+        variadic: bool,
     },
     Func(TypeId), // pointer to a function
 }
@@ -123,6 +125,88 @@ impl<R: gimli::Reader> TypeStorage<R> {
         }
     }
 
+    pub fn get_array_type(&self, subtype_id: TypeId, count: usize, bit_stride: Option<u64>) -> TypeId {
+        let mut types = self.types.borrow_mut();
+
+        types
+            .iter()
+            .position(|typ| match *typ {
+                Type::Array {
+                    subtype_id: array_subtype_id,
+                    count: ArrayCount::Static(array_count),
+                    bit_stride: array_bit_stride,
+                } => array_subtype_id == subtype_id && array_count == count && array_bit_stride == bit_stride,
+                _ => false,
+            })
+            .unwrap_or_else(|| {
+                types.push(Type::Array {
+                    subtype_id,
+                    count: ArrayCount::Static(count),
+                    bit_stride,
+                });
+                types.len() - 1
+            })
+    }
+
+    /// finds type by C name like `int`, `struct node` or typedef name, definitions are preferred over declarations
+    pub fn find_type(&self, name: &str) -> Option<TypeId> {
+        if name == "void" {
+            return Some(VOID_TYPE_ID);
+        }
+
+        let (keyword, name) = match name.split_once(' ') {
+            Some((keyword @ ("struct" | "union" | "enum"), name)) => (Some(keyword), name.trim_start()),
+            _ => (None, name),
+        };
+
+        let types = self.types.borrow();
+        let mut declaration = None;
+        for (type_id, typ) in types.iter().enumerate() {
+            let (type_name, is_declaration) = match (keyword, typ) {
+                (None, Type::Base { name, .. } | Type::Typedef(name, _)) => (name, false),
+                (Some("struct"), Type::Struct { name: Some(name), fields, .. }) => (name, fields.is_empty()),
+                (Some("union"), Type::Union { name: Some(name), fields, .. }) => (name, fields.is_empty()),
+                (
+                    Some("enum"),
+                    Type::Enum {
+                        name: Some(name), variants, ..
+                    },
+                ) => (name, variants.is_empty()),
+                _ => continue,
+            };
+            if type_name.as_ref() != name {
+                continue;
+            }
+            if !is_declaration {
+                return Some(type_id);
+            }
+            declaration.get_or_insert(type_id);
+        }
+        drop(types);
+
+        // casts can use integer types the program doesn't have
+        declaration.or_else(|| {
+            let (encoding, size) = get_c_integer_type(name)?;
+            let mut types = self.types.borrow_mut();
+            types.push(Type::Base {
+                name: Rc::from(name),
+                encoding,
+                size,
+            });
+            Some(types.len() - 1)
+        })
+    }
+
+    /// pointer to the type, pointers found in debug info are reused so `char *` is a string
+    pub fn get_pointer_type(&self, type_id: TypeId) -> TypeId {
+        let position = self.types.borrow().iter().position(|typ| match *typ {
+            Type::Pointer(subtype_id) | Type::String(subtype_id) | Type::Func(subtype_id) => subtype_id == type_id,
+            _ => false,
+        });
+
+        position.unwrap_or_else(|| self.get_type_ref(type_id))
+    }
+
     pub fn get_type_ref(&self, type_id: TypeId) -> TypeId {
         let mut types = self.types.borrow_mut();
 
@@ -138,3 +222,114 @@ impl<R: gimli::Reader> TypeStorage<R> {
             })
     }
 }
+
+/// encoding and size of C integer type like `unsigned long` or `short int` on x86_64
+fn get_c_integer_type(name: &str) -> Option<(gimli::DwAte, u16)> {
+    if matches!(name, "bool" | "_Bool") {
+        return Some((gimli::DW_ATE_boolean, 1));
+    }
+
+    let (mut signedness, mut size, mut longs, mut has_int) = (None, None, 0, false);
+    for word in name.split_whitespace() {
+        match word {
+            "signed" | "unsigned" if signedness.is_none() => signedness = Some(word),
+            "char" | "short" if size.is_none() => size = Some(word),
+            "long" if longs < 2 => longs += 1,
+            "int" if !has_int => has_int = true,
+            _ => return None,
+        }
+    }
+
+    let size = match (size, longs, has_int) {
+        (Some("char"), 0, false) => 1,
+        (Some("short"), 0, _) => 2,
+        (None, 0, _) if has_int || signedness.is_some() => 4,
+        (None, 1 | 2, _) => 8,
+        _ => return None,
+    };
+    let encoding = match (signedness, size) {
+        (Some("unsigned"), 1) => gimli::DW_ATE_unsigned_char,
+        (Some("unsigned"), _) => gimli::DW_ATE_unsigned,
+        (_, 1) => gimli::DW_ATE_signed_char,
+        _ => gimli::DW_ATE_signed,
+    };
+
+    Some((encoding, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Reader = gimli::EndianSlice<'static, gimli::LittleEndian>;
+
+    #[test]
+    fn test_find_type() {
+        let mut type_storage = TypeStorage::<Reader>::new();
+        let int_type_id = type_storage.add(Type::Base {
+            name: Rc::from("int"),
+            encoding: gimli::DW_ATE_signed,
+            size: 4,
+        });
+        let unsigned_type_id = type_storage.add(Type::Base {
+            name: Rc::from("unsigned int"),
+            encoding: gimli::DW_ATE_unsigned,
+            size: 4,
+        });
+        let declaration_type_id = type_storage.add(Type::Struct {
+            name: Some(Rc::from("node")),
+            size: 0,
+            fields: Rc::new(Vec::new()),
+        });
+        let struct_type_id = type_storage.add(Type::Struct {
+            name: Some(Rc::from("node")),
+            size: 4,
+            fields: Rc::new(vec![Field {
+                name: Rc::from("value"),
+                type_id: int_type_id,
+                offset: 0,
+            }]),
+        });
+        let typedef_type_id = type_storage.add(Type::Typedef(Rc::from("node_t"), struct_type_id));
+        let pointer_type_id = type_storage.add(Type::Pointer(struct_type_id));
+
+        assert_eq!(type_storage.find_type("void"), Some(VOID_TYPE_ID));
+        assert_eq!(type_storage.find_type("int"), Some(int_type_id));
+        assert_eq!(type_storage.find_type("unsigned int"), Some(unsigned_type_id));
+        assert_eq!(type_storage.find_type("struct node"), Some(struct_type_id));
+        assert_eq!(type_storage.find_type("node_t"), Some(typedef_type_id));
+        assert_eq!(type_storage.find_type("node"), None);
+        assert_eq!(type_storage.find_type("union node"), None);
+        assert_ne!(declaration_type_id, struct_type_id);
+
+        let long_type_id = type_storage.find_type("unsigned long").unwrap();
+        assert!(matches!(
+            type_storage.get(long_type_id).unwrap(),
+            Type::Base {
+                encoding: gimli::DW_ATE_unsigned,
+                size: 8,
+                ..
+            }
+        ));
+        assert_eq!(type_storage.find_type("unsigned long"), Some(long_type_id));
+
+        assert_eq!(type_storage.get_pointer_type(struct_type_id), pointer_type_id);
+        let int_pointer_type_id = type_storage.get_pointer_type(int_type_id);
+        assert!(matches!(type_storage.get(int_pointer_type_id).unwrap(), Type::Pointer(type_id) if type_id == int_type_id));
+    }
+
+    #[test]
+    fn test_get_c_integer_type() {
+        assert_eq!(get_c_integer_type("char"), Some((gimli::DW_ATE_signed_char, 1)));
+        assert_eq!(get_c_integer_type("unsigned char"), Some((gimli::DW_ATE_unsigned_char, 1)));
+        assert_eq!(get_c_integer_type("short"), Some((gimli::DW_ATE_signed, 2)));
+        assert_eq!(get_c_integer_type("unsigned"), Some((gimli::DW_ATE_unsigned, 4)));
+        assert_eq!(get_c_integer_type("long unsigned int"), Some((gimli::DW_ATE_unsigned, 8)));
+        assert_eq!(get_c_integer_type("long long"), Some((gimli::DW_ATE_signed, 8)));
+        assert_eq!(get_c_integer_type("_Bool"), Some((gimli::DW_ATE_boolean, 1)));
+        assert_eq!(get_c_integer_type("long char"), None);
+        assert_eq!(get_c_integer_type("unsigned signed int"), None);
+        assert_eq!(get_c_integer_type("long long long"), None);
+        assert_eq!(get_c_integer_type("float"), None);
+    }
+}
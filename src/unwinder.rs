@@ -7,6 +7,11 @@ pub enum UnwindFrame<R: gimli::Reader> {
     EhFrame(gimli::EhFrame<R>, Option<gimli::ParsedEhFrameHdr<R>>),
 }
 
+pub struct FrameInfo<R: gimli::Reader> {
+    pub row: gimli::UnwindTableRow<R::Offset>,
+    pub is_signal_trampoline: bool,
+}
+
 pub struct Unwinder<R: gimli::Reader> {
     unwind_frame: UnwindFrame<R>,
     ctx: RefCell<gimli::UnwindContext<R::Offset>>,
@@ -23,23 +28,34 @@ impl<R: gimli::Reader> Unwinder<R> {
     }
 
     pub fn unwind_cfa(&self, relative_address: u64) -> gimli::Result<gimli::CfaRule<R::Offset>> {
+        self.unwind_frame(relative_address).map(|frame_info| frame_info.row.cfa().clone())
+    }
+
+    pub fn unwind_frame(&self, relative_address: u64) -> gimli::Result<FrameInfo<R>> {
+        let fde = self.find_fde(relative_address)?;
         let mut ctx = self.ctx.borrow_mut();
 
-        let unwind_info = match &self.unwind_frame {
-            UnwindFrame::DebugFrame(debug_frame) => {
-                debug_frame.unwind_info_for_address(&self.bases, &mut ctx, relative_address, gimli::DebugFrame::cie_from_offset)
-            }
+        let row = match &self.unwind_frame {
+            UnwindFrame::DebugFrame(debug_frame) => fde.unwind_info_for_address(debug_frame, &self.bases, &mut ctx, relative_address),
+            UnwindFrame::EhFrame(eh_frame, _) => fde.unwind_info_for_address(eh_frame, &self.bases, &mut ctx, relative_address),
+        }?;
+
+        Ok(FrameInfo {
+            row: row.clone(),
+            is_signal_trampoline: fde.cie().is_signal_trampoline(),
+        })
+    }
+
+    fn find_fde(&self, relative_address: u64) -> gimli::Result<gimli::FrameDescriptionEntry<R>> {
+        match &self.unwind_frame {
+            UnwindFrame::DebugFrame(debug_frame) => debug_frame.fde_for_address(&self.bases, relative_address, gimli::DebugFrame::cie_from_offset),
             UnwindFrame::EhFrame(eh_frame, parsed_eh_frame_hdr) => {
                 match parsed_eh_frame_hdr.as_ref().and_then(|parsed_eh_frame_hdr| parsed_eh_frame_hdr.table()) {
-                    Some(eh_hdr_table) => {
-                        eh_hdr_table.unwind_info_for_address(eh_frame, &self.bases, &mut ctx, relative_address, gimli::EhFrame::cie_from_offset)
-                    }
-                    None => eh_frame.unwind_info_for_address(&self.bases, &mut ctx, relative_address, gimli::EhFrame::cie_from_offset),
+                    Some(eh_hdr_table) => eh_hdr_table.fde_for_address(eh_frame, &self.bases, relative_address, gimli::EhFrame::cie_from_offset),
+                    None => eh_frame.fde_for_address(&self.bases, relative_address, gimli::EhFrame::cie_from_offset),
                 }
             }
-        }?;
-
-        Ok(unwind_info.cfa().clone())
+        }
     }
 
     pub fn unwind_expression(&self, unwind_expression: &gimli::UnwindExpression<R::Offset>) -> gimli::Result<gimli::Expression<R>> {
@@ -18,6 +18,30 @@ impl<T: PartialOrd> AVLTree<T> {
         AVLTree { root: None }
     }
 
+    /// builds balanced tree in O(n), values must be sorted and without duplicates
+    pub fn from_sorted(values: Vec<T>) -> Self {
+        debug_assert!(values.windows(2).all(|pair| pair[0] < pair[1]), "values must be sorted");
+
+        let len = values.len();
+        AVLTree {
+            root: Self::build_from_sorted(&mut values.into_iter(), len),
+        }
+    }
+
+    /// takes `len` values from the iterator in order, middle one becomes the root, so subtree heights differ at most by one
+    fn build_from_sorted(values: &mut impl Iterator<Item = T>, len: usize) -> Option<Box<Node<T>>> {
+        if len == 0 {
+            return None;
+        }
+
+        let left = Self::build_from_sorted(values, len / 2);
+        let value = values.next()?;
+        let right = Self::build_from_sorted(values, len - len / 2 - 1);
+        let height = 1 + std::cmp::max(Self::height(&left), Self::height(&right));
+
+        Some(Box::new(Node { value, height, left, right }))
+    }
+
     pub fn insert(&mut self, value: T) {
         self.root = Self::insert_into(self.root.take(), value);
     }
@@ -74,8 +98,8 @@ impl<T: PartialOrd> AVLTree<T> {
     fn rotate_left(mut node: Box<Node<T>>) -> Box<Node<T>> {
         let mut new_root = node.right.take().unwrap();
         node.right = new_root.left.take();
+        node.height = 1 + std::cmp::max(Self::height(&node.left), Self::height(&node.right));
         new_root.left = Some(node);
-        new_root.left.as_mut().unwrap().height = 1 + std::cmp::max(Self::height(&new_root.left), Self::height(&new_root.right));
         new_root.height = 1 + std::cmp::max(Self::height(&new_root.left), Self::height(&new_root.right));
         new_root
     }
@@ -83,8 +107,8 @@ impl<T: PartialOrd> AVLTree<T> {
     fn rotate_right(mut node: Box<Node<T>>) -> Box<Node<T>> {
         let mut new_root = node.left.take().unwrap();
         node.left = new_root.right.take();
+        node.height = 1 + std::cmp::max(Self::height(&node.left), Self::height(&node.right));
         new_root.right = Some(node);
-        new_root.right.as_mut().unwrap().height = 1 + std::cmp::max(Self::height(&new_root.left), Self::height(&new_root.right));
         new_root.height = 1 + std::cmp::max(Self::height(&new_root.left), Self::height(&new_root.right));
         new_root
     }
@@ -283,6 +307,41 @@ mod tests {
         let expected: Vec<_> = (1..=1000).collect();
 
         assert_eq!(result, expected);
+        assert_balanced(&tree.root);
+    }
+
+    fn assert_balanced<T>(node: &Option<Box<Node<T>>>) -> i32 {
+        match node {
+            Some(n) => {
+                let left_height = assert_balanced(&n.left);
+                let right_height = assert_balanced(&n.right);
+                assert!((left_height - right_height).abs() <= 1);
+                assert_eq!(n.height, 1 + left_height.max(right_height));
+                n.height
+            }
+            None => 0,
+        }
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        let tree = AVLTree::<i32>::from_sorted(Vec::new());
+        assert!(tree.root.is_none());
+
+        for len in [1, 2, 3, 7, 8, 1000] {
+            let tree = AVLTree::from_sorted((1..=len).collect());
+            let result: Vec<_> = tree.iter().copied().collect();
+            assert_eq!(result, (1..=len).collect::<Vec<_>>());
+            let height = assert_balanced(&tree.root);
+            // complete tree is as low as possible
+            assert_eq!(height, (len as f64 + 1.0).log2().ceil() as i32);
+        }
+
+        let mut tree = AVLTree::from_sorted(vec![10, 20, 30]);
+        tree.insert(40);
+        tree.insert(50);
+        assert_balanced(&tree.root);
+        assert_eq!(tree.get_by(|x| 50.cmp(x)), Some(&50));
     }
 
     #[test]
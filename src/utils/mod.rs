@@ -1,3 +1,4 @@
 mod avl;
+pub mod number_parser;
 pub mod ranges;
 pub mod string_parser;
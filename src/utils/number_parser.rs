@@ -0,0 +1,43 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseError {
+    #[error("negative number")]
+    NegativeNumber,
+    #[error("invalid number")]
+    InvalidNumber,
+}
+
+/// parses unsigned decimal or 0x-prefixed hex number
+pub fn parse_unsigned(input: &str) -> Result<u64, ParseError> {
+    if input.starts_with('-') {
+        return Err(ParseError::NegativeNumber);
+    }
+
+    match input.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => input.parse::<u64>(),
+    }
+    .map_err(|_| ParseError::InvalidNumber)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unsigned() {
+        assert_eq!(parse_unsigned("0"), Ok(0));
+        assert_eq!(parse_unsigned("123"), Ok(123));
+        assert_eq!(parse_unsigned("0x10"), Ok(16));
+        assert_eq!(parse_unsigned("0xdeadBEEF"), Ok(0xdeadbeef));
+        assert_eq!(parse_unsigned("0xffffffffffffffff"), Ok(u64::MAX));
+        assert_eq!(parse_unsigned("-1"), Err(ParseError::NegativeNumber));
+        assert_eq!(parse_unsigned("-0x10"), Err(ParseError::NegativeNumber));
+        assert_eq!(parse_unsigned(""), Err(ParseError::InvalidNumber));
+        assert_eq!(parse_unsigned("0x"), Err(ParseError::InvalidNumber));
+        assert_eq!(parse_unsigned("0x1g"), Err(ParseError::InvalidNumber));
+        assert_eq!(parse_unsigned("0x10000000000000000"), Err(ParseError::InvalidNumber));
+        assert_eq!(parse_unsigned("foo"), Err(ParseError::InvalidNumber));
+    }
+}
@@ -39,6 +39,24 @@ impl<T> Ranges<T> {
         Self { tree: AVLTree::new() }
     }
 
+    /// builds ranges at once, faster than adding them one by one. Like `add`, overlapping range is dropped,
+    /// out of overlapping ranges the one starting first is kept
+    pub fn bulk_load(mut ranges: Vec<(u64, u64, T)>) -> Self {
+        ranges.sort_by_key(|&(start, _, _)| start); // stable, so duplicates keep the order they were added in
+
+        let mut sorted: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for (start, end, value) in ranges {
+            if sorted.last().is_some_and(|last| start <= last.end) {
+                continue;
+            }
+            sorted.push(Range { start, end, value });
+        }
+
+        Self {
+            tree: AVLTree::from_sorted(sorted),
+        }
+    }
+
     pub fn add(&mut self, start: u64, end: u64, value: T) {
         self.tree.insert(Range { start, end, value });
     }
@@ -90,4 +108,18 @@ mod tests {
         assert_eq!(ranges.find_range(15), Some((10, 20)));
         assert_eq!(ranges.find_range(0), None);
     }
+
+    #[test]
+    fn test_bulk_load() {
+        let ranges = Ranges::bulk_load(vec![(60, 90, "baz"), (10, 20, "foo"), (30, 50, "bar"), (10, 20, "dup"), (40, 55, "overlap")]);
+
+        assert_eq!(ranges.find_value(10), Some(&"foo"));
+        assert_eq!(ranges.find_value(40), Some(&"bar"));
+        assert_eq!(ranges.find_value(55), None);
+        assert_eq!(ranges.find_value(90), Some(&"baz"));
+        assert_eq!(ranges.find_range(25), None);
+
+        let ranges = Ranges::<&str>::bulk_load(Vec::new());
+        assert_eq!(ranges.find_value(10), None);
+    }
 }
@@ -0,0 +1,83 @@
+use std::cell::RefCell;
+
+use bytes::Bytes;
+
+use crate::types::TypeId;
+use crate::var::Value;
+
+/// debug address registers dr0-dr3
+pub const WATCHPOINT_SLOTS: usize = 4;
+pub const DR6: usize = 6;
+pub const DR7: usize = 7;
+/// dr6 bit set when the trap comes from single stepping
+pub const DR6_SINGLE_STEP: u64 = 1 << 14;
+
+/// variable memory watched for writes with a debug register
+#[derive(Debug)]
+pub struct Watchpoint {
+    pub id: usize,
+    pub slot: usize, // debug address register holding the address
+    pub expr: String,
+    pub address: u64,
+    pub type_id: TypeId,
+    pub value: RefCell<Bytes>, // value seen on the last stop, writes of the same value are ignored
+}
+
+impl Watchpoint {
+    pub fn new<S: Into<String>>(id: usize, slot: usize, expr: S, address: u64, value: Value) -> Self {
+        Self {
+            id,
+            slot,
+            expr: expr.into(),
+            address,
+            type_id: value.type_id,
+            value: RefCell::new(value.buf),
+        }
+    }
+
+    /// dr7 bits enabling the slot to break on writes of `size` bytes, debug registers watch only 1, 2, 4 or 8 aligned bytes
+    pub fn get_dr7_bits(slot: usize, address: u64, size: usize) -> Option<u64> {
+        let len = match size {
+            1 => 0b00,
+            2 => 0b01,
+            4 => 0b11,
+            8 => 0b10,
+            _ => return None,
+        };
+        if !address.is_multiple_of(size as u64) {
+            return None;
+        }
+        let rw = 0b01; // data writes
+
+        Some((1 << (slot * 2)) | ((rw | (len << 2)) << (16 + slot * 4)))
+    }
+
+    /// dr7 bits of the slot, cleared when the watchpoint is removed
+    pub fn get_dr7_mask(slot: usize) -> u64 {
+        (0b11 << (slot * 2)) | (0b1111 << (16 + slot * 4))
+    }
+}
+
+/// watchpoint which stopped the program
+#[derive(Debug, Clone)]
+pub struct WatchpointHit {
+    pub id: usize,
+    pub expr: String,
+    pub old_value: Value,
+    pub new_value: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_dr7_bits() {
+        assert_eq!(Watchpoint::get_dr7_bits(0, 0x1000, 4), Some(0b1101 << 16 | 0b01));
+        assert_eq!(Watchpoint::get_dr7_bits(1, 0x1000, 8), Some(0b1001 << 20 | 0b0100));
+        assert_eq!(Watchpoint::get_dr7_bits(3, 0x1001, 1), Some(0b0001 << 28 | 0b0100_0000));
+        assert_eq!(Watchpoint::get_dr7_bits(0, 0x1002, 4), None); // unaligned
+        assert_eq!(Watchpoint::get_dr7_bits(0, 0x1000, 16), None);
+        assert_eq!(Watchpoint::get_dr7_bits(2, 0x1000, 2).unwrap() & !Watchpoint::get_dr7_mask(2), 0);
+    }
+}
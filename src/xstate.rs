@@ -0,0 +1,231 @@
+use anyhow::Result;
+use nix::errno::Errno;
+use nix::unistd::Pid;
+
+/// `PTRACE_GETREGSET` note type of the xsave area, nix knows only general purpose and fpu register sets
+const NT_X86_XSTATE: libc::c_int = 0x202;
+/// xsave area without avx-512, used when cpuid doesn't tell the size
+const XSTATE_SIZE: usize = 4096;
+/// legacy fxsave region, it's what `NT_PRFPREG` returns
+const FXSAVE_SIZE: usize = 512;
+
+const FCW_OFFSET: usize = 0;
+const FSW_OFFSET: usize = 2;
+const FTW_OFFSET: usize = 4;
+const FOP_OFFSET: usize = 6;
+const FIP_OFFSET: usize = 8;
+const FDP_OFFSET: usize = 16;
+const MXCSR_OFFSET: usize = 24;
+const ST_OFFSET: usize = 32;
+const XMM_OFFSET: usize = 160;
+const XSTATE_BV_OFFSET: usize = 512;
+const YMM_HI_OFFSET: usize = 576; // upper halves of ymm in the standard (not compacted) format
+const AVX_STATE_BIT: u64 = 1 << 2;
+
+pub const ST_REGISTERS_COUNT: usize = 8;
+pub const XMM_REGISTERS_COUNT: usize = 16;
+
+/// x87, sse and avx state of the child
+#[derive(Debug)]
+pub struct XState {
+    kind: libc::c_int, // register set the state was read from, it's written back to the same one
+    data: Vec<u8>,
+}
+
+impl XState {
+    /// falls back to fxsave region when kernel or cpu has no xsave
+    pub fn read(pid: Pid) -> Result<Self> {
+        match Self::get_regset(pid, NT_X86_XSTATE, Self::xstate_size()) {
+            Ok(data) => Ok(Self::new(NT_X86_XSTATE, data)),
+            Err(e) => {
+                log::trace!("can't read xstate: {}", e);
+                Ok(Self::new(libc::NT_PRFPREG, Self::get_regset(pid, libc::NT_PRFPREG, FXSAVE_SIZE)?))
+            }
+        }
+    }
+
+    /// kernel writes back only the whole xsave area, so amx tiles must fit too
+    fn xstate_size() -> usize {
+        // leaf 0xd tells the size of xsave area for all supported components
+        if std::arch::x86_64::__cpuid(0).eax < 0xd {
+            return XSTATE_SIZE;
+        }
+        match std::arch::x86_64::__cpuid_count(0xd, 0).ecx as usize {
+            0 => XSTATE_SIZE,
+            size => size,
+        }
+    }
+
+    fn new(kind: libc::c_int, data: Vec<u8>) -> Self {
+        Self { kind, data }
+    }
+
+    /// restores the state saved by `read`
+    pub fn write(&self, pid: Pid) -> Result<()> {
+        let mut iov = libc::iovec {
+            iov_base: self.data.as_ptr() as *mut libc::c_void,
+            iov_len: self.data.len(),
+        };
+        let res = unsafe { libc::ptrace(libc::PTRACE_SETREGSET, pid.as_raw(), self.kind, &mut iov as *mut libc::iovec) };
+        Errno::result(res)?;
+
+        Ok(())
+    }
+
+    fn get_regset(pid: Pid, kind: libc::c_int, size: usize) -> Result<Vec<u8>> {
+        let mut data = vec![0u8; size];
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr().cast(),
+            iov_len: data.len(),
+        };
+        // kernel sets iov_len to the size of the register set
+        let res = unsafe { libc::ptrace(libc::PTRACE_GETREGSET, pid.as_raw(), kind, &mut iov as *mut libc::iovec) };
+        Errno::result(res)?;
+        data.truncate(iov.iov_len);
+
+        Ok(data)
+    }
+
+    pub fn fcw(&self) -> u16 {
+        self.read_u16(FCW_OFFSET)
+    }
+
+    pub fn fsw(&self) -> u16 {
+        self.read_u16(FSW_OFFSET)
+    }
+
+    /// abridged tag word, bit per register, set bit means register is not empty
+    pub fn ftw(&self) -> u8 {
+        self.data[FTW_OFFSET]
+    }
+
+    pub fn fop(&self) -> u16 {
+        self.read_u16(FOP_OFFSET)
+    }
+
+    pub fn fip(&self) -> u64 {
+        self.read_u64(FIP_OFFSET)
+    }
+
+    pub fn fdp(&self) -> u64 {
+        self.read_u64(FDP_OFFSET)
+    }
+
+    pub fn mxcsr(&self) -> u32 {
+        u32::from_le_bytes(self.data[MXCSR_OFFSET..MXCSR_OFFSET + 4].try_into().unwrap())
+    }
+
+    /// 80 bit x87 register, st0 is the top of the stack
+    pub fn st(&self, index: usize) -> &[u8] {
+        let offset = ST_OFFSET + index * 16;
+        &self.data[offset..offset + 10]
+    }
+
+    /// mmx registers are low 64 bits of x87 registers
+    pub fn mm(&self, index: usize) -> u64 {
+        self.read_u64(ST_OFFSET + index * 16)
+    }
+
+    pub fn xmm(&self, index: usize) -> &[u8] {
+        let offset = XMM_OFFSET + index * 16;
+        &self.data[offset..offset + 16]
+    }
+
+    /// None if there is no avx state, upper halves in init state are zeros
+    pub fn ymm(&self, index: usize) -> Option<[u8; 32]> {
+        let offset = YMM_HI_OFFSET + index * 16;
+        if self.data.len() < offset + 16 {
+            return None;
+        }
+
+        let mut ymm = [0u8; 32];
+        ymm[..16].copy_from_slice(self.xmm(index));
+        if self.read_u64(XSTATE_BV_OFFSET) & AVX_STATE_BIT != 0 {
+            ymm[16..].copy_from_slice(&self.data[offset..offset + 16]);
+        }
+
+        Some(ymm)
+    }
+
+    fn read_u16(&self, offset: usize) -> u16 {
+        u16::from_le_bytes(self.data[offset..offset + 2].try_into().unwrap())
+    }
+
+    fn read_u64(&self, offset: usize) -> u64 {
+        u64::from_le_bytes(self.data[offset..offset + 8].try_into().unwrap())
+    }
+}
+
+/// converts x87 extended precision value, precision beyond f64 is lost
+pub fn f80_to_f64(bytes: &[u8]) -> f64 {
+    let mantissa = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+    let sign_exponent = u16::from_le_bytes(bytes[8..10].try_into().unwrap());
+    let sign = if sign_exponent & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (sign_exponent & 0x7fff) as i32;
+
+    let value = match exponent {
+        0 if mantissa == 0 => 0.0,
+        0x7fff if mantissa << 1 == 0 => f64::INFINITY,
+        0x7fff => f64::NAN,
+        // denormals have exponent of 1, mantissa has explicit integer bit
+        _ => mantissa as f64 / 2f64.powi(63) * 2f64.powi(exponent.max(1) - 16383),
+    };
+
+    sign * value
+}
+
+/// vector register as a number, bytes are little endian
+pub fn format_vector(bytes: &[u8]) -> String {
+    let digits = bytes.iter().rev().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    format!("0x{}", digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f80(value: u128) -> Vec<u8> {
+        value.to_le_bytes()[..10].to_vec()
+    }
+
+    #[test]
+    fn test_f80_to_f64() {
+        assert_eq!(f80_to_f64(&f80(0)), 0.0);
+        assert_eq!(f80_to_f64(&f80(0x3fff_8000_0000_0000_0000)), 1.0);
+        assert_eq!(f80_to_f64(&f80(0xc000_c000_0000_0000_0000)), -3.0);
+        assert_eq!(f80_to_f64(&f80(0x3ffe_8000_0000_0000_0000)), 0.5);
+        assert_eq!(f80_to_f64(&f80(0x7fff_8000_0000_0000_0000)), f64::INFINITY);
+        assert!(f80_to_f64(&f80(0x7fff_c000_0000_0000_0000)).is_nan());
+    }
+
+    #[test]
+    fn test_xstate_layout() {
+        let mut data = vec![0u8; 1024];
+        data[FCW_OFFSET..FCW_OFFSET + 2].copy_from_slice(&0x37fu16.to_le_bytes());
+        data[MXCSR_OFFSET..MXCSR_OFFSET + 4].copy_from_slice(&0x1f80u32.to_le_bytes());
+        data[ST_OFFSET + 16..ST_OFFSET + 26].copy_from_slice(&f80(0x3fff_8000_0000_0000_0001));
+        data[XMM_OFFSET + 16] = 0xab;
+        data[YMM_HI_OFFSET + 16] = 0xcd;
+
+        let xstate = XState::new(NT_X86_XSTATE, data.clone());
+        assert_eq!(xstate.fcw(), 0x37f);
+        assert_eq!(xstate.mxcsr(), 0x1f80);
+        assert_eq!(xstate.mm(1), 0x8000_0000_0000_0001);
+        assert_eq!(f80_to_f64(xstate.st(1)), 1.0);
+        assert_eq!(xstate.xmm(1)[0], 0xab);
+        // avx state isn't saved, upper half is in init state
+        assert_eq!(xstate.ymm(1).unwrap()[16], 0);
+
+        data[XSTATE_BV_OFFSET] = AVX_STATE_BIT as u8;
+        let xstate = XState::new(NT_X86_XSTATE, data);
+        assert_eq!(xstate.ymm(1).unwrap()[16], 0xcd);
+
+        let xstate = XState::new(libc::NT_PRFPREG, vec![0u8; FXSAVE_SIZE]);
+        assert!(xstate.ymm(0).is_none());
+    }
+
+    #[test]
+    fn test_format_vector() {
+        assert_eq!(format_vector(&[0x01, 0x02, 0x00, 0xff]), "0xff000201");
+    }
+}
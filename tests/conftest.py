@@ -14,7 +14,10 @@ def check_debugger():
 
 @pytest.fixture
 def debugger(tmp_path_factory):
-    def _debugger(code: str, steps: list[Step], filename: str = ""):
+    # keep debugger dotfiles away from user's home, shared between runs in one test
+    home_path = tmp_path_factory.mktemp("home")
+
+    def _debugger(code: str, steps: list[Step], filename: str = "", cflags: list[str] = [], compiler: str = "gcc"):
         tmp_path = tmp_path_factory.mktemp("source")
         original_dir = os.getcwd()
         if not filename:
@@ -30,7 +33,7 @@ def debugger(tmp_path_factory):
                 f.write(code)
 
             # compile code
-            args = ["gcc", "-g", "-O0", "-Wall", src_name, "-o", exec_name]
+            args = [compiler, "-g", "-O0", "-Wall", *cflags, src_name, "-o", exec_name]
             subprocess.run(args, stdout=subprocess.PIPE, stderr=subprocess.STDOUT, check=True, text=True)
         except subprocess.CalledProcessError as e:
             pytest.fail(e.stdout)
@@ -38,7 +41,7 @@ def debugger(tmp_path_factory):
             os.chdir(original_dir)
 
         # run debugger
-        with subprocess.Popen(["target/debug/dbg", exec_path], stdin=subprocess.PIPE, stdout=subprocess.PIPE, stderr=subprocess.STDOUT, text=True) as child:
+        with subprocess.Popen(["target/debug/dbg", exec_path], stdin=subprocess.PIPE, stdout=subprocess.PIPE, stderr=subprocess.STDOUT, text=True, env={**os.environ, "HOME": str(home_path)}) as child:
             for step in steps:
                 child.stdin.write(step.command + "\n")
                 child.stdin.flush()
@@ -0,0 +1,56 @@
+from tests import Step
+
+
+def test_alias(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 5;
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("alias", "no aliases"),
+            Step("alias bp = break", "alias set"),
+            Step("alias pp=print", "alias set"),
+            Step("alias", ["bp = break", "pp = print"]),
+            Step("bp 6", "breakpoint set"),
+            Step("r"),
+            Step("pp x", "int x = 5"),
+            Step("unalias pp", "alias removed"),
+            Step("unalias pp", "alias not found"),
+            Step("c", "5"),
+            Step("q"),
+        ]
+    )
+
+
+def test_alias_persistence(debugger):
+    code = """#include <stdio.h>
+
+int main()
+{
+    printf("hello\\n");
+    return 0;
+}
+"""
+    debugger(
+        code=code,
+        steps=[
+            Step("alias go = run", "alias set"),
+            Step("alias rr = run", "alias set"),
+            Step("unalias rr", "alias removed"),
+            Step("q"),
+        ]
+    )
+    debugger(
+        code=code,
+        steps=[
+            Step("alias", "go = run"),
+            Step("go", "hello"),
+            Step("q"),
+        ]
+    )
@@ -1,3 +1,6 @@
+import os
+import subprocess
+
 from tests import Step
 
 
@@ -23,7 +26,7 @@ int main()
             Step("enable t.c:5", "breakpoint enabled"),
             Step("clear"),
             Step("r"),
-            Step("stop", "invalid command"),  # assert program completed
+            Step("stop", "program not running"),  # assert program completed
             Step("q"),
         ],
         filename="t"
@@ -45,7 +48,7 @@ int main()
             Step("b 5", "breakpoint set"),
             Step("disable t.c:5", "breakpoint disabled"),
             Step("r"),
-            Step("stop", "invalid command"),  # assert program completed
+            Step("stop", "program not running"),  # assert program completed
             Step("q"),
         ],
         filename="t"
@@ -75,6 +78,45 @@ int main()
     )
 
 
+def test_disable_breakpoint_at_stop(debugger, tmp_path):
+    path = tmp_path / "breakpoints"
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    return sum;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("b 9", "breakpoint set"),
+            Step("r"),
+            Step("p i", "int i = 0"),
+            # breakpoint the program is stopped at is not set again on continue
+            Step("disable t.c:7", "breakpoint disabled"),
+            Step(f"save-breakpoints {path}", "2 breakpoints saved"),
+            Step("c"),
+            Step("p sum", "int sum = 3"),
+            Step("restart", "program restarted"),
+            Step("r"),
+            Step("loc", "t.c:9"),
+            Step("disable t.c:9", "breakpoint disabled"),
+            Step("rune", "program exited with code 3"),
+            Step("restart", "program restarted"),
+            Step("r"),
+            Step("stop", "program not running"),  # assert program completed
+            Step("q"),
+        ],
+        filename="t"
+    )
+    assert path.read_text() == "break t.c:7\ndisable t.c:7\nbreak t.c:9\n"
+
+
 def test_breakpoint_by_func_name(debugger):
     debugger(
         code="""#include <stdio.h>
@@ -93,3 +135,644 @@ int main()
         ],
         filename="t"
     )
+
+
+def test_breakpoint_at_stmt_row(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+__attribute__((noinline)) int sum(int *a, int n)
+{
+    int s = 0;
+    for (int i = 0; i < n; i++)
+        s += a[i] * 3;
+    return s;
+}
+
+int main()
+{
+    int a[4] = {1, 2, 3, 4};
+    printf("%d\\n", sum(a, 4));
+    return 0;
+}
+""",
+        steps=[
+            # line 14 starts with a non-statement row, before the array is stored
+            Step("b 14", "breakpoint set"),
+            Step("r"),
+            Step("p a", "int[4] a = [1, 2, 3, 4]"),
+            Step("c", "30"),
+            Step("q"),
+        ],
+        cflags=["-O2", "-fno-reorder-functions"]
+    )
+
+
+def test_breakpoint_number_formats(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <stdlib.h>
+
+int foo(int x)
+{
+    return x * 2;
+}
+
+int main()
+{
+    printf("%d\\n", foo(5));
+    exit(0);
+}
+""",
+        steps=[
+            Step("b -5", "invalid location"),
+            Step("b 0x1", "loc not found"),
+            Step("b 0xzz", "invalid location"),
+            Step("b 0x800000", "breakpoint set"),
+            Step("b 0x800000", "breakpoint already exist"),
+            Step("l", "0x800000"),
+            Step("r"),
+            Step("c", "10"),
+            Step("q"),
+        ],
+        filename="t",
+        # place foo at a known address
+        cflags=["-no-pie", "-nostartfiles", "-e", "main", "-Wl,--section-start=.text=0x800000"]
+    )
+
+
+def test_skip_unparseable_unit(debugger, tmp_path):
+    # references are not supported, so the whole c++ unit is skipped
+    lib_path = tmp_path / "lib.o"
+    (tmp_path / "lib.cpp").write_text("""int x = 1;
+int &r = x;
+
+extern "C" int foo()
+{
+    return r;
+}
+""")
+    subprocess.run(["g++", "-g", "-O0", "-c", str(tmp_path / "lib.cpp"), "-o", str(lib_path)], check=True)
+
+    debugger(
+        code="""int foo();
+
+int main()
+{
+    int a = foo();
+    return a;
+}
+""",
+        steps=[
+            Step("b foo", "loc not found"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("p a", "int a = 1"),
+            Step("q"),
+        ],
+        cflags=[str(lib_path)]
+    )
+
+
+def test_relative_breakpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    x++;
+    x++;
+    x++;
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b +1", "invalid location"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("b +2", "breakpoint set"),
+            Step("b -1", "breakpoint set"),
+            Step("b -10", "invalid location"),
+            Step("b +x", "invalid location"),
+            Step("l", ["t.c:6", "t.c:8", "t.c:5"]),
+            Step("c"),
+            Step("loc", "t.c:8"),
+            Step("p x", "int x = 3"),
+            Step("c", "4"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_breakpoint_commands(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int total = 0;
+    for (int i = 0; i < 3; i++) {
+        total += i;
+    }
+    return total;
+}
+""",
+        steps=[
+            Step("commands t.c:7", "breakpoint not found"),
+            Step("b 7", "breakpoint set"),
+            Step("b 9", "breakpoint set"),
+            Step("commands t.c:7", "type commands"),
+            Step("p total"),
+            Step("foo", "invalid command"),
+            Step("commands t.c:9", "invalid command"),
+            Step("c"),
+            Step("end", "commands set"),
+            Step("r", ["int total = 0", "int total = 0", "int total = 1"]),
+            Step("p total", "int total = 3"),  # stopped at the breakpoint without commands
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_breakpoint_commands_without_continue(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 0;
+    x = 1;
+    return x;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("commands t.c:6", "type commands"),
+            Step("p x"),
+            Step("p y"),
+            Step("loc"),
+            Step("end", "commands set"),
+            Step("r", ["int x = 0", "y not found"]),  # failed command stops the list
+            Step("loc", "t.c:6"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_breakpoint_on_int3(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    __asm__("int3");
+    return 0;
+}
+""",
+        steps=[
+            Step("b 5", ["breakpoint set", "warning: code at"]),
+            Step("b 6", "breakpoint set", "warning"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_remove_disabled_breakpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 0;
+    x += 1;
+    x += 2;
+    printf("x = %d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("disable t.c:6", "breakpoint disabled"),
+            Step("rm t.c:6", "breakpoint removed"),
+            Step("l", "no breakpoints"),
+            Step("r", "x = 3"),
+            Step("stop", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_remove_enabled_breakpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 0;
+    for (int i = 0; i < 3; i++) {
+        x += i;
+    }
+    printf("x = %d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 5", "breakpoint set"),
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("rm t.c:7", "breakpoint removed"),  # enabled and not hit yet
+            Step("c", "x = 3"),
+            Step("stop", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_remove_hit_breakpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 0;
+    for (int i = 0; i < 3; i++) {
+        x += i;
+    }
+    printf("x = %d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("rm t.c:7", "breakpoint removed"),  # stopped at it, so it's lifted
+            Step("c", "x = 3"),
+            Step("stop", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_step_over_breakpoint_on_one_byte_instruction(tmp_path):
+    (tmp_path / "t.c").write_text("""#include <stdio.h>
+
+int helper(int x)
+{
+    return x * 2;
+}
+
+int main()
+{
+    printf("%d\\n", helper(5));
+    return 0;
+}
+""")
+    exec_path = tmp_path / "t"
+    # function starts with one byte push rbp without endbr64
+    subprocess.run(["gcc", "-g", "-O0", "-Wall", "-no-pie", "-fcf-protection=none", str(tmp_path / "t.c"), "-o", str(exec_path)], check=True)
+    symbols = subprocess.run(["nm", str(exec_path)], capture_output=True, text=True, check=True).stdout
+    helper_address = next(line.split()[0] for line in symbols.splitlines() if line.endswith(" helper"))
+
+    # continue single steps over push rbp which stops right after it, as int3 there would
+    result = subprocess.run(
+        ["target/debug/dbg", str(exec_path)],
+        input="b 0x{}\nb 11\nr\nc\nc\n".format(helper_address),
+        capture_output=True,
+        text=True,
+        timeout=10,
+        env={**os.environ, "HOME": str(tmp_path)},
+    )
+    assert result.returncode == 0
+    assert result.stdout.splitlines() == ["breakpoint set", "breakpoint set", "10"]
+    assert result.stderr == ""
+
+
+def test_breakpoint_in_loop(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    return sum;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("p sum", "int sum = 0"),
+            Step("c"),
+            Step("p sum", "int sum = 0"),
+            Step("c"),
+            Step("p sum", "int sum = 1"),
+            Step("c"),
+            Step("stop", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_conditional_breakpoint(debugger):
+    debugger(
+        code="""#include <stdbool.h>
+
+int main()
+{
+    int sum = 0;
+    int i;
+    bool last = false;
+    for (i = 0; i < 1000; i++) {
+        last = i == 999;
+        sum += i;
+    }
+    return sum > 0 ? 0 : 1;
+}
+""",
+        steps=[
+            Step("b 10 if i == 500", "breakpoint set"),
+            Step("l", "t.c:10 if i == 500"),
+            Step("b 12 if nope > 0", "breakpoint set"),
+            Step("r"),
+            Step("p i", "int i = 500"),
+            Step("rm t.c:10", "breakpoint removed"),
+            Step("b 10 if last == true", "breakpoint set"),
+            Step("c"),
+            Step("p i", "int i = 999"),
+            Step("c"),
+            Step("loc", "t.c:12"),
+            Step("p sum", "int sum = 499500"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_temporary_breakpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    return sum;
+}
+""",
+        steps=[
+            Step("tb 7", "temporary breakpoint set"),
+            Step("l", "t.c:7 (temporary)"),
+            Step("r"),
+            Step("p sum", "int sum = 0"),
+            Step("l", "no breakpoints"),
+            Step("tbreak t.c:9", "temporary breakpoint set"),
+            Step("c"),
+            Step("loc", "t.c:9"),
+            Step("c"),
+            Step("stop", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_tbreak_after_kill(debugger):
+    debugger(
+        code="""int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    return sum;
+}
+""",
+        steps=[
+            Step("tb 3", "temporary breakpoint set"),
+            Step("tb 7", "temporary breakpoint set"),
+            Step("r"),
+            Step("kill", "program killed"),
+            # breakpoint is still temporary in the new run
+            Step("l", "t.c:7 (temporary)"),
+            Step("r"),
+            Step("p sum", "int sum = 3"),
+            Step("l", "no breakpoints"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_logpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    printf("%d\\n", sum);
+    return 0;
+}
+""",
+        steps=[
+            Step("logp t.c:7 sum", "logpoint set"),
+            Step("logpoint t.c:9 foo", "logpoint set"),
+            Step("l", ["t.c:7 (log sum)", "t.c:9 (log foo)"]),
+            Step("r", ["int sum = 0", "int sum = 0", "int sum = 1", "can't print foo", "3"]),
+            Step("stop", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_step_over_breakpoint_interrupted_by_signal(debugger):
+    # ud2 under the breakpoint raises SIGILL before the instruction is stepped over
+    debugger(
+        code="""#include <setjmp.h>
+#include <signal.h>
+
+sigjmp_buf env;
+
+void handler(int sig)
+{
+    siglongjmp(env, 1);
+}
+
+int main()
+{
+    volatile int count = 0;
+    signal(SIGILL, handler);
+    sigsetjmp(env, 1);
+    if (count++ < 3) {
+        __builtin_trap();
+    }
+    return 0;
+}
+""",
+        steps=[
+            Step("b 17", "breakpoint set"),
+            Step("r"),
+            Step("p count", "volatile int count = 1"),
+            Step("c"),
+            Step("p count", "volatile int count = 2"),
+            Step("c"),
+            Step("p count", "volatile int count = 3"),
+            Step("c"),
+            Step("stop", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_breakpoint_ids(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 0;
+    x = 1;
+    x = 2;
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 5", "breakpoint set"),
+            Step("b 6", "breakpoint set"),
+            Step("b 7", "breakpoint set"),
+            Step("l", ["1: t.c:5", "2: t.c:6", "3: t.c:7"]),
+            Step("rm 2", "breakpoint removed"),
+            Step("rm 2", "breakpoint not found"),
+            Step("disable 1", "breakpoint disabled"),
+            Step("r"),
+            Step("p x", "int x = 1"),
+            Step("enable 1", "breakpoint enabled"),
+            # ids are kept when the program is started again
+            Step("kill", "program killed"),
+            Step("b 8", "breakpoint set"),
+            Step("l", ["1: t.c:5", "3: t.c:7", "4: t.c:8"]),
+            Step("rm t.c:7", "breakpoint removed"),
+            Step("r"),
+            Step("loc", "t.c:5"),
+            Step("c"),
+            Step("p x", "int x = 2"),
+            Step("c", "2"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_save_breakpoints(debugger, tmp_path):
+    code = """#include <stdio.h>
+
+int main()
+{
+    int x = 0;
+    x = 1;
+    x = 2;
+    printf("%d\\n", x);
+    return 0;
+}
+"""
+    path = tmp_path / "breakpoints"
+    debugger(
+        code=code,
+        steps=[
+            Step("b 5", "breakpoint set"),
+            Step("b 7", "breakpoint set"),
+            Step("disable t.c:7", "breakpoint disabled"),
+            Step("r"),
+            # breakpoint the program is stopped at stays enabled
+            Step(f"save-breakpoints {path}", "2 breakpoints saved"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+    assert path.read_text() == "break t.c:5\nbreak t.c:7\ndisable t.c:7\n"
+
+    path.write_text("break t.c:100\ndisable t.c:100\nbreak t.c:5\nbreak t.c:7\ndisable t.c:7\n")
+    debugger(
+        code=code,
+        steps=[
+            Step(f"source-breakpoints {path}", ["warning: breakpoint t.c:100 skipped: loc not found", "2 breakpoints set"]),
+            Step("l", ["1: t.c:5", "2: t.c:7"]),
+            Step("source-breakpoints /nonexistent", "No such file or directory"),
+            Step("r"),
+            Step("loc", "t.c:5"),
+            Step("c", "2"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_save_breakpoints_with_settings(debugger, tmp_path):
+    code = """#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 4; i++) {
+        sum += i;
+    }
+    sum++;
+    sum++;
+    printf("%d\\n", sum);
+    return 0;
+}
+"""
+    path = tmp_path / "breakpoints"
+    debugger(
+        code=code,
+        steps=[
+            Step("b 7 if i == 2", "breakpoint set"),
+            Step("tb 9", "temporary breakpoint set"),
+            Step("logp 10 sum", "logpoint set"),
+            Step("b 11", "breakpoint set"),
+            Step("commands 4", "type commands one per line"),
+            Step("p sum"),
+            Step("end", "commands set"),
+            Step(f"save-breakpoints {path}", "4 breakpoints saved"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+    assert path.read_text() == "break t.c:7 if i == 2\ntbreak t.c:9\nlogpoint t.c:10 sum\nbreak t.c:11\ncommands t.c:11\np sum\nend\n"
+
+    debugger(
+        code=code,
+        steps=[
+            Step(f"source-breakpoints {path}", "4 breakpoints set"),
+            Step("l", ["1: t.c:7 if i == 2", "2: t.c:9 (temporary)", "3: t.c:10 (log sum)", "4: t.c:11"]),
+            Step("r"),
+            Step("p i", "int i = 2"),
+            Step("c"),
+            Step("loc", "t.c:9"),
+            Step("c", ["int sum = 7", "int sum = 8"]),
+            Step("l", ["1: t.c:7 if i == 2", "3: t.c:10 (log sum)", "4: t.c:11"]),
+            Step("c", "8"),
+            Step("q"),
+        ],
+        filename="t"
+    )
@@ -1,3 +1,8 @@
+import os
+import subprocess
+
+import pytest
+
 from tests import Step
 
 
@@ -22,6 +27,33 @@ int main()
     )
 
 
+def test_command_in_wrong_state(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    printf("x = %d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("step-in", "program not running"),
+            Step("p x", "program not running"),
+            Step("set x = 2", "program not running"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("p x", "int x = 1"),
+            Step("c", "x = 1"),
+            Step("step-in", "program not running"),
+            Step("p x", "program not running"),
+            Step("b 5", "invalid command"),
+            Step("q"),
+        ]
+    )
+
+
 def test_quit_started_program(debugger):
     debugger(
         code="""#include <stdio.h>
@@ -54,3 +86,1299 @@ int main()
             Step("q"),
         ]
     )
+
+
+def test_signal_handler(debugger):
+    debugger(
+        code="""#include <signal.h>
+#include <stdio.h>
+
+volatile sig_atomic_t hits = 0;
+
+void handler(int sig)
+{
+    hits++;
+}
+
+int main()
+{
+    signal(SIGUSR1, handler);
+    raise(SIGUSR1);
+    printf("hits = %d\\n", hits);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 8", "breakpoint set"),
+            Step("r", "program received signal SIGUSR1"),
+            Step("c"),
+            Step("loc", "t.c:8"),
+            Step("p hits", "hits = 0"),
+            Step("step-out"),
+            Step("loc", "t.c:15"),
+            Step("c", "hits = 1"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_kill_keeps_breakpoints(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    x++;
+    printf("x = %d\\n", x);
+    x++;
+    printf("x = %d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("b 8", "breakpoint set"),
+            Step("r"),
+            Step("kill", "program killed"),
+            Step("r"),
+            Step("p x", "int x = 1"),
+            Step("c"),
+            Step("p x", "int x = 2"),
+            Step("c", ["x = 2", "x = 3"]),
+            Step("q"),
+        ]
+    )
+
+
+def test_kill(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    x++;
+    printf("x = %d\\n", x);
+    x++;
+    printf("x = %d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("b 8", "breakpoint set"),
+            Step("disable hello.c:8", "breakpoint disabled"),
+            Step("r"),
+            Step("set x = 10"),
+            Step("kill", "program killed"),
+            Step("l", ["hello.c:6", "hello.c:8"]),
+            Step("r"),
+            Step("p x", "int x = 1"),
+            Step("c", ["x = 2", "x = 3"]),
+            Step("q"),
+        ],
+        filename="hello"
+    )
+
+
+def test_run_to_exit(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    return sum;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("p sum", "int sum = 0"),
+            Step("rune", "program exited with code 3"),
+            Step("stop", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_run_to_exit_crash(debugger):
+    debugger(
+        code="""#include <stddef.h>
+
+int main()
+{
+    int *p = NULL;
+    int n = 0;
+    for (int i = 0; i < 3; i++) {
+        n += i;
+    }
+    return *p + n;
+}
+""",
+        steps=[
+            Step("b 8", "breakpoint set"),
+            Step("run-to-exit", "program terminated by signal SIGSEGV"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_reverse_continue(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int square(int x)
+{
+    return x * x;
+}
+
+int main()
+{
+    int total = 0;
+    for (int i = 0; i < 3; i++) {
+        total += square(i);
+    }
+    printf("%d\\n", total);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 5", "breakpoint set"),
+            Step("b 14", "breakpoint set"),
+            Step("r"),
+            Step("rc", "no previous breakpoint hit"),
+            Step("c"),
+            Step("c"),
+            Step("p x", "int x = 2"),
+            Step("rc"),
+            Step("p x", "int x = 1"),
+            Step("reverse-continue"),
+            Step("p x", "int x = 0"),
+            Step("c"),
+            Step("n"),
+            Step("rc"),  # stepped away from the hit, so it's the one we go back to
+            Step("p x", "int x = 1"),
+            Step("c"),
+            Step("c"),
+            Step("p total", "int total = 5"),
+            Step("rm t.c:5", "breakpoint removed"),
+            Step("rc", "program took another path while replaying"),
+            Step("loc", "t.c:14"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_disable_randomization(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int counter = 1;
+
+int main()
+{
+    printf("%d\\n", counter);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            # pie is loaded at the default base without randomization
+            Step("p &counter", "int* &counter = 0x55555555"),
+            Step("set disable-randomization off"),
+            Step("p &counter", "int* &counter = 0x55555555"),  # applied on the next launch
+            Step("kill", "program killed"),
+            Step("r"),
+            Step("p &counter", "int* &counter = 0x", "0x55555555"),
+            Step("set disable-randomization maybe", "parser error"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_main_function(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <unistd.h>
+
+int helper(int x)
+{
+    return x * 2;
+}
+
+void my_start(void)
+{
+    int y = helper(5);
+    printf("%d\\n", y);
+    fflush(stdout);
+    _exit(0);
+}
+""",
+        steps=[
+            Step("b 11", "breakpoint set"),
+            Step("b helper", "breakpoint set"),
+            Step("r"),
+            # there is no main, function with the entry point is used
+            Step("finish", "not meaningful in the outermost frame"),
+            Step("c"),
+            Step("set main-function nope", "function nope not found"),
+            Step("set main-function helper"),
+            Step("finish", "not meaningful in the outermost frame"),
+            Step("set main-function my_start"),
+            Step("finish", "int helper() = 10"),
+            Step("q"),
+        ],
+        cflags=["-nostartfiles", "-e", "my_start"],
+        filename="t"
+    )
+
+
+def test_info_line_table(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    printf("x = %d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("info line-table main", ["hello.c:4 (prologue)", "hello.c:5", "hello.c:6", "hello.c:7", "hello.c:8 (epilogue)"]),
+            Step("info line-table foo", "loc not found"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("info line-table", ["hello.c:4 (prologue)", "hello.c:5", "hello.c:6", "hello.c:7", "hello.c:8 (epilogue)"]),
+            Step("c", "x = 1"),
+            Step("q"),
+        ],
+        filename="hello"
+    )
+
+
+def test_checkpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <stdlib.h>
+
+int counter = 0;
+
+int main()
+{
+    int x = 1;
+    int *p = malloc(sizeof(int));
+    *p = 1;
+    counter += 10;
+    x += 5;
+    *p += 1;
+    printf("%d %d %d\\n", x, counter, *p);
+    free(p);
+    return 0;
+}
+""",
+        steps=[
+            Step("restart-checkpoint 1", "program not running"),
+            Step("b 11", "breakpoint set"),
+            Step("r"),
+            Step("checkpoint", "checkpoint 1 saved"),
+            Step("step"),
+            Step("step"),
+            Step("step"),
+            Step("loc", "t.c:14"),
+            Step("p x", "int x = 6"),
+            Step("p counter", "int counter = 10"),
+            Step("p *p", "int *p = 2"),
+            Step("restart-checkpoint 2", "checkpoint not found"),
+            Step("restart-checkpoint 1", "checkpoint 1 restored"),
+            Step("loc", "t.c:11"),
+            Step("p x", "int x = 1"),
+            Step("p counter", "int counter = 0"),
+            Step("p *p", "int *p = 1"),
+            Step("set x = 100"),
+            Step("c", "105 10 2"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_inferiors(debugger, tmp_path):
+    # second program is started from the debugger
+    other_path = tmp_path / "other"
+    (tmp_path / "other.c").write_text("""#include <stdio.h>
+
+int main()
+{
+    int y = 2;
+    printf("other %d\\n", y);
+    return 0;
+}
+""")
+    subprocess.run(["gcc", "-g", "-O0", "-Wall", str(tmp_path / "other.c"), "-o", str(other_path)], check=True)
+
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    printf("main %d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("add-inferior /nonexistent", "invalid program"),
+            Step(f"add-inferior {other_path}", "inferior 2 added"),
+            Step("info inferiors", ["* 1 ", "  2 "]),
+            Step("inferior 3", "inferior not found"),
+            Step("inferior 2", "switched to inferior 2"),
+            Step("info inferiors", ["  1 ", "* 2 "]),
+            Step("l", "no breakpoints"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("p y", "int y = 2"),
+            Step("inferior 1", "switched to inferior 1"),
+            Step("p x", "int x = 1"),
+            Step("c", "main 1"),
+            Step("info inferiors", ["exited", "running"]),
+            Step("inferior 2", "switched to inferior 2"),
+            Step("c", "other 2"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_finish(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+typedef struct {
+    int x;
+    int y;
+} Point;
+
+typedef struct {
+    long id;
+    double weight;
+} Item;
+
+typedef struct {
+    double re;
+    double im;
+} Complex;
+
+typedef struct {
+    int values[10];
+} Big;
+
+int twice(int x)
+{
+    return x * 2;
+}
+
+double half(double x)
+{
+    return x / 2;
+}
+
+Point make_point(int x, int y)
+{
+    Point p = {x, y};
+    return p;
+}
+
+Item make_item(void)
+{
+    Item item = {7, 2.5};
+    return item;
+}
+
+Complex make_complex(void)
+{
+    Complex c = {1.5, -3};
+    return c;
+}
+
+Big make_big(void)
+{
+    Big big = {{1, 2, 3, 4, 5, 6, 7, 8, 9, 10}};
+    return big;
+}
+
+void nothing(void)
+{
+}
+
+int main()
+{
+    int a = twice(5);
+    double b = half(3);
+    Point p = make_point(3, 4);
+    Item item = make_item();
+    Complex c = make_complex();
+    Big big = make_big();
+    nothing();
+    printf("%d %f %d %ld %f %d\\n", a, b, p.y, item.id, c.im, big.values[9]);
+    return 0;
+}
+""",
+        steps=[
+            Step("b twice", "breakpoint set"),
+            Step("b half", "breakpoint set"),
+            Step("b make_point", "breakpoint set"),
+            Step("b make_item", "breakpoint set"),
+            Step("b make_complex", "breakpoint set"),
+            Step("b make_big", "breakpoint set"),
+            Step("b nothing", "breakpoint set"),
+            Step("r"),
+            Step("finish", "int twice() = 10"),
+            Step("c"),
+            Step("finish", "double half() = 1.5"),
+            Step("c"),
+            Step("finish", "Point make_point() = { x = 3, y = 4 }"),
+            Step("c"),
+            Step("finish", "Item make_item() = { id = 7, weight = 2.5 }"),
+            Step("c"),
+            Step("finish", "Complex make_complex() = { re = 1.5, im = -3 }"),
+            Step("c"),
+            Step("finish", "Big make_big() = { values = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10] }"),
+            Step("c"),
+            Step("finish"),
+            Step("loc", "t.c:69"),
+            Step("finish", "not meaningful in the outermost frame"),
+            Step("c", "10 1.500000 4 7 -3.000000 10"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_finish_long_double(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+long double third(long double x)
+{
+    return x / 3;
+}
+
+int main()
+{
+    printf("%Lf\\n", third(3));
+    return 0;
+}
+""",
+        steps=[
+            Step("b third", "breakpoint set"),
+            Step("r"),
+            Step("finish", "functions returning long double are not supported"),
+            Step("bt", ["#0 third", "#1 main"]),
+            Step("c", "1.000000"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_info_scope(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int counter = 5;
+
+int add(int a, int b)
+{
+    int sum = a + b;
+    return sum;
+}
+
+int main()
+{
+    int x = add(1, 2);
+    printf("%d %d\\n", x, counter);
+    return 0;
+}
+""",
+        steps=[
+            Step("info scope", "invalid location"),
+            Step("info scope nope", "loc not found"),
+            Step("info scope add", ["a: frame base offset -", "b: frame base offset -", "counter: address 0x", "sum: frame base offset -"]),
+            Step("info scope t.c:14", ["counter: address 0x", "x: frame base offset -"]),
+            Step("b add", "breakpoint set"),
+            Step("r"),
+            Step("info scope", ["a: frame base offset -", "b: frame base offset -", "counter: address 0x", "sum: frame base offset -"]),
+            Step("c", "3 5"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_advance(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int square(int x)
+{
+    int y = x * x;
+    return y;
+}
+
+int main()
+{
+    int a = square(2);
+    int b = square(3);
+    printf("%d %d\\n", a, b);
+    return 0;
+}
+""",
+        steps=[
+            Step("advance 6", "program not running"),
+            Step("b main", "breakpoint set"),
+            Step("r"),
+            Step("advance nope", "loc not found"),
+            Step("advance 6"),
+            Step("loc", "t.c:6"),
+            Step("p x", "int x = 2"),
+            Step("advance 13"),
+            Step("loc", "t.c:12"),
+            Step("advance 13"),
+            Step("loc", "t.c:13"),
+            Step("p b", "int b = 9"),
+            Step("l", "main"),
+            Step("c", "4 9"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_until(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int square(int x)
+{
+    int y = x * x;
+    return y;
+}
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 5; i++) {
+        sum += i;
+    }
+    int a = square(sum);
+    printf("%d %d\\n", sum, a);
+    if (a < 0) {
+        printf("negative\\n");
+    }
+    return 0;
+}
+""",
+        steps=[
+            Step("until 15", "program not running"),
+            Step("b 11", "breakpoint set"),
+            Step("r"),
+            Step("n"),
+            Step("n"),
+            Step("loc", "t.c:13"),
+            # the rest of the loop is run
+            Step("until 15"),
+            Step("loc", "t.c:15"),
+            Step("p sum", "int sum = 10"),
+            Step("b 5", "breakpoint set"),
+            Step("c"),
+            # line of other function runs out of square
+            Step("u 16"),
+            Step("loc", "t.c:16"),
+            Step("p a", "int a = 100"),
+            # never reached line leaves no trap behind
+            Step("until 18", "10 100"),
+            Step("loc", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_stop_context(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    x++;
+    x++;
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("set stop-context x", "invalid value"),
+            Step("set stop-context 1"),
+            Step("b 6", "breakpoint set"),
+            Step("b 8", "breakpoint set"),
+            Step("r", ["t.c:6", "6\t    x++;"]),
+            Step("set listsize 3"),
+            Step("c", ["t.c:8", "7\t    x++;", "8\t    printf", "9\t    return 0;"]),
+            Step("set stop-context 0"),
+            Step("c", "3"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_stop_context_substitute_path(debugger, tmp_path):
+    # sources are looked up in the compilation directory, which is remapped to a missing one
+    source_root = tmp_path.parent
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    x++;
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("set stop-context 1"),
+            Step("b 5", "breakpoint set"),
+            Step("b 7", "breakpoint set"),
+            Step("r", "t.c:5"),
+            Step(f"set substitute-path /build {source_root}"),
+            Step("c", ["t.c:7", "7\t    printf"]),
+            Step("c", "2"),
+            Step("q"),
+        ],
+        filename="t",
+        cflags=[f"-fdebug-prefix-map={source_root}=/build"]
+    )
+
+
+def test_info_all_registers(debugger):
+    general = ["rax", "rbx", "rcx", "rdx", "rsi", "rdi", "rbp", "rsp", *["r{}".format(i) for i in range(8, 16)], "rip", "eflags"]
+    segment = ["cs 0x33", "ss 0x2b", "ds", "es", "fs", "gs", "fs_base", "gs_base"]
+    x87 = ["st{} ".format(i) for i in range(7)] + ["st7 4.5 (raw 0x40019000000000000000)", "fctrl 0x37f", "fstat", "ftag", "fop", "fioff", "fooff"]
+    sse = ["mxcsr 0x1f80", *["mm{} 0x".format(i) for i in range(8)], "xmm0 0x0000000000000000401c000000000000"] + ["xmm{} 0x".format(i) for i in range(1, 16)]
+    registers = [name if " " in name else name + " 0x" for name in general + segment + x87 + sse]
+    with open("/proc/cpuinfo") as f:
+        if " avx " in f.read():
+            registers += ["ymm0 0x000000000000000000000000000000000000000000000000401c000000000000"] + ["ymm{} 0x".format(i) for i in range(1, 16)]
+
+    debugger(
+        code="""int main()
+{
+    double x = 3.5;
+    long double l = 1.5L;
+    x *= 2;
+    l *= 3;
+    return (int)x + (int)l;
+}
+""",
+        steps=[
+            Step("info all-registers", "program not running"),
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            # last x87 value stays in the physical register after it's popped
+            Step("info all-registers", registers),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_info_registers(debugger):
+    debugger(
+        code="""int foo(int x)
+{
+    return x * 2;
+}
+
+int main()
+{
+    return foo(3);
+}
+""",
+        steps=[
+            Step("info registers", "program not running"),
+            Step("b 3", "breakpoint set"),
+            Step("r"),
+            Step("info registers", [
+                "rax 0x",
+                "rbx 0x",
+                "rcx 0x",
+                "rdx 0x",
+                "rsi 0x",
+                "rdi 0x",
+                "rbp 0x",
+                "rsp 0x",
+                "r8 0x",
+                "r9 0x",
+                "r10 0x",
+                "r11 0x",
+                "r12 0x",
+                "r13 0x",
+                "r14 0x",
+                "r15 0x",
+                "rip 0x",
+                "eflags 0x",
+            ]),
+            Step("frame 1", "#1 main at t.c:8"),
+            Step("info registers", "rax 0x"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_info_proc_mappings(debugger):
+    debugger(
+        code="""#include <stdlib.h>
+
+int main()
+{
+    char *buf = malloc(16);
+    free(buf);
+    return 0;
+}
+""",
+        steps=[
+            Step("info proc mappings", "program not running"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("info proc mappings", ["start              end                size       offset     perms pathname", "0x0        r--p  /"]),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_frame(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int add(int a, int b)
+{
+    int sum = a + b;
+    return sum;
+}
+
+int compute(int x)
+{
+    int local = x * 2;
+    int res = add(local, local + 1);
+    return res;
+}
+
+int main()
+{
+    int n = 5;
+    int first = compute(n);
+    int second = compute(n + 1);
+    printf("%d %d\\n", first, second);
+    return 0;
+}
+""",
+        steps=[
+            Step("frame 1", "program not running"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("p sum", "int sum = 21"),
+            Step("frame 1", "#1 compute at t.c:12"),
+            Step("p local", "int local = 10"),
+            Step("p x", "int x = 5"),
+            Step("p sum", "sum not found"),
+            Step("set local = 100"),
+            Step("p local", "int local = 100"),
+            Step("frame 2", "#2 main at t.c:19"),
+            Step("p n", "int n = 5"),
+            Step("frame 100", "frame not found"),
+            Step("p n", "int n = 5"),
+            Step("frame 0", "#0 add at t.c:6"),
+            Step("p sum", "int sum = 21"),
+            # selection is reset when the program moves
+            Step("frame 1", "#1 compute at t.c:12"),
+            Step("c"),
+            Step("p sum", "int sum = 25"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_backtrace(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int factorial(int n)
+{
+    if (n <= 1) {
+        return 1;
+    }
+    return n * factorial(n - 1);
+}
+
+int compute(int x)
+{
+    return factorial(x) + 1;
+}
+
+int main()
+{
+    int res = compute(3);
+    printf("%d\\n", res);
+    return 0;
+}
+""",
+        steps=[
+            Step("bt", "program not running"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("bt", [
+                "#0 factorial at t.c:6",
+                "#1 factorial at t.c:8",
+                "#2 factorial at t.c:8",
+                "#3 compute at t.c:13",
+                "#4 main at t.c:18",
+            ]),
+            Step("frame 3", "#3 compute at t.c:13"),
+            Step("p x", "int x = 3"),
+            Step("rm t.c:6", "breakpoint removed"),
+            Step("b 18", "breakpoint set"),
+            Step("kill", "program killed"),
+            Step("r"),
+            Step("backtrace", "#0 main at t.c:18"),
+            Step("loc", "t.c:18"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_backtrace_in_signal_handler(debugger):
+    debugger(
+        code="""#include <signal.h>
+#include <stddef.h>
+#include <unistd.h>
+
+void handler(int sig)
+{
+    _exit(sig);
+}
+
+int crash(int *p)
+{
+    return *p + 1;
+}
+
+int main()
+{
+    signal(SIGSEGV, handler);
+    return crash(NULL);
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r", "program received signal SIGSEGV"),
+            Step("c"),
+            # handler is called by the signal trampoline, interrupted frames are above it
+            Step("bt", [
+                "#0 handler at t.c:7",
+                "#1 ?? at ??",
+                "#2 crash at t.c:12",
+                "#3 main at t.c:18",
+            ]),
+            # trampoline has no variables
+            Step("frame 1", "#1 ?? at ??"),
+            Step("p p", "no debug info for frame"),
+            Step("info locals", "no debug info for frame"),
+            Step("frame 2", "#2 crash at t.c:12"),
+            Step("p p", "int* p = null"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_up_down(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int factorial(int n)
+{
+    if (n <= 1) {
+        return 1;
+    }
+    return n * factorial(n - 1);
+}
+
+int compute(int x)
+{
+    return factorial(x) + 1;
+}
+
+int main()
+{
+    int res = compute(3);
+    printf("%d\\n", res);
+    return 0;
+}
+""",
+        steps=[
+            Step("up", "program not running"),
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("up", "#1 factorial at t.c:8"),
+            Step("p n", "int n = 2"),
+            Step("up 2", "#3 compute at t.c:13"),
+            Step("p x", "int x = 3"),
+            Step("up 10", "frame not found"),
+            Step("p x", "int x = 3"),
+            Step("down 3", "#0 factorial at t.c:6"),
+            Step("p n", "int n = 1"),
+            Step("down", "innermost frame selected"),
+            Step("up 3", "#3 compute at t.c:13"),
+            Step("n"),
+            Step("p n", "int n = 2"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_up_past_main(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <stdlib.h>
+
+int cmp(const void *a, const void *b)
+{
+    return *(const int *)a - *(const int *)b;
+}
+
+int main()
+{
+    int a[] = {2, 1};
+    qsort(a, 2, sizeof(int), cmp);
+    printf("%d\\n", a[0]);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            # qsort has no debug info, backtrace stops before it
+            Step("up", "frame not found"),
+            Step("p a", "const void* a = 0x"),
+            Step("rm t.c:6", "breakpoint removed"),
+            Step("b 13", "breakpoint set"),
+            Step("c"),
+            # caller of main is not shown by backtrace
+            Step("up", "frame not found"),
+            Step("p", "int[2] a = [1, 2]"),
+            Step("info locals", "int[2] a = [1, 2]"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_piped_commands(tmp_path):
+    (tmp_path / "t.c").write_text("""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    printf("x = %d\\n", x);
+    return 0;
+}
+""")
+    exec_path = tmp_path / "t"
+    subprocess.run(["gcc", "-g", "-O0", "-Wall", str(tmp_path / "t.c"), "-o", str(exec_path)], check=True)
+
+    # no prompts for non-interactive input, end of input quits
+    result = subprocess.run(
+        ["target/debug/dbg", str(exec_path)],
+        input="b 6\nr\np x\n",
+        capture_output=True,
+        text=True,
+        timeout=10,
+        env={**os.environ, "HOME": str(tmp_path)},
+    )
+    assert result.returncode == 0
+    assert result.stdout.splitlines() == ["breakpoint set", "int x = 1"]
+    assert not (tmp_path / ".dbg_history").exists()
+
+
+def test_stdin_redirect(tmp_path):
+    (tmp_path / "t.c").write_text("""#include <stdio.h>
+
+int main()
+{
+    int x = 0;
+    scanf("%d", &x);
+    printf("x = %d\\n", x * 2);
+    return 0;
+}
+""")
+    exec_path = tmp_path / "t"
+    subprocess.run(["gcc", "-g", "-O0", "-Wall", str(tmp_path / "t.c"), "-o", str(exec_path)], check=True)
+    (tmp_path / "input.txt").write_text("21\n")
+
+    # file is read again after restart
+    result = subprocess.run(
+        ["target/debug/dbg", "--stdin", str(tmp_path / "input.txt"), str(exec_path)],
+        input="b 7\nr\np x\nrestart\nr\np x\nc\n",
+        capture_output=True,
+        text=True,
+        timeout=10,
+        env={**os.environ, "HOME": str(tmp_path)},
+    )
+    assert result.returncode == 0
+    assert result.stdout.splitlines() == ["breakpoint set", "int x = 21", "program restarted", "int x = 21", "x = 42"]
+
+    result = subprocess.run(
+        ["target/debug/dbg", "--stdin", str(tmp_path / "missing.txt"), str(exec_path)],
+        input="",
+        capture_output=True,
+        text=True,
+        timeout=10,
+        env={**os.environ, "HOME": str(tmp_path)},
+    )
+    assert result.returncode != 0
+    assert "can't access" in result.stderr
+
+
+def test_env(tmp_path):
+    (tmp_path / "t.c").write_text("""#include <stdlib.h>
+
+int main()
+{
+    char *foo = getenv("FOO");
+    char *home = getenv("HOME");
+    return foo == home;
+}
+""")
+    exec_path = tmp_path / "t"
+    subprocess.run(["gcc", "-g", "-O0", "-Wall", str(tmp_path / "t.c"), "-o", str(exec_path)], check=True)
+
+    def run(options):
+        return subprocess.run(
+            ["target/debug/dbg", *options, str(exec_path)],
+            input="b 7\nr\np foo\np home\n",
+            capture_output=True,
+            text=True,
+            timeout=10,
+            env={**os.environ, "HOME": str(tmp_path)},
+        )
+
+    result = run(["--env", "FOO=bar=baz"])
+    assert result.returncode == 0
+    assert result.stdout.splitlines() == ["breakpoint set", 'char* foo = "bar=baz"', 'char* home = "{}"'.format(tmp_path)]
+
+    result = run(["--env", "FOO=bar", "--clear-env"])
+    assert result.returncode == 0
+    assert result.stdout.splitlines() == ["breakpoint set", 'char* foo = "bar"', "char* home = null"]
+
+    result = run(["--env", "FOO"])
+    assert result.returncode != 0
+
+
+def test_detach(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    printf("sum %d\\n", sum);
+    return 0;
+}
+""",
+        steps=[
+            Step("detach", "program not running"),
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("watch sum", "watchpoint 1 set"),
+            # int3 or watchpoint left in the program would kill it before it prints
+            Step("detach", ["program detached", "sum 3"]),
+            Step("c", "program not running"),
+            Step("q"),
+        ]
+    )
+
+
+def test_restart(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    return 0;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("tb 9", "temporary breakpoint set"),
+            Step("r"),
+            Step("restart", "program restarted"),
+            Step("l", ["1: t.c:7", "2: t.c:9 (temporary)"]),
+            Step("disable 1", "breakpoint disabled"),
+            Step("r"),
+            Step("loc", "t.c:9"),
+            Step("c"),
+            Step("loc", "program not running"),
+            Step("rerun", "program restarted"),
+            Step("l", "1: t.c:7"),
+            Step("enable 1", "breakpoint enabled"),
+            Step("r"),
+            Step("loc", "t.c:7"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_call(debugger):
+    debugger(
+        code="""#include <stdlib.h>
+
+int counter = 0;
+
+int add(int a, int b)
+{
+    counter++;
+    return a + b;
+}
+
+long neg(long x)
+{
+    return -x;
+}
+
+void bump(int *p)
+{
+    *p += 10;
+}
+
+int crash(int *p)
+{
+    return *p;
+}
+
+void quit(int code)
+{
+    exit(code);
+}
+
+int main()
+{
+    int x = 5;
+    double d = 1.5;
+    bump(&x);
+    return add(x, (int)d) + (int)neg(1);
+}
+
+long double third(int x)
+{
+    return x / 3.0L;
+}
+""",
+        steps=[
+            Step("call add(1, 2)", "program not running"),
+            Step("b 35", "breakpoint set"),
+            Step("b add", "breakpoint set"),
+            Step("r"),
+            Step("call add(1, 2)", "int add(1, 2) = 3"),
+            Step("call add(x, -7)", "int add(x, -7) = -2"),
+            Step("p counter", "int counter = 2"),
+            Step("call neg(-3)", "long int neg(-3) = 3"),
+            Step("call bump(&x)"),
+            Step("p x", "int x = 15"),
+            Step("call add(d, 1)", "only integer and pointer arguments are supported"),
+            Step("call add(1, 2, 3, 4, 5, 6, 7)", "at most 6 arguments are supported"),
+            Step("call third(3)", "functions returning long double are not supported"),
+            Step("call nope()", "function nope not found"),
+            Step("call crash(0)", "call of crash was interrupted"),
+            Step("loc", "t.c:35"),
+            Step("c"),
+            Step("bt", ["#0 add", "#1 main"]),
+            Step("call quit(3)", "program exited during the call of quit"),
+            Step("restart", "program restarted"),
+            Step("r"),
+            Step("loc", "t.c:35"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def cpu_has_avx() -> bool:
+    with open("/proc/cpuinfo") as f:
+        return " avx " in f.read()
+
+
+@pytest.mark.skipif(not cpu_has_avx(), reason="cpu has no avx")
+def test_call_keeps_registers(tmp_path):
+    (tmp_path / "t.c").write_text("""#include <immintrin.h>
+
+int scale(int a)
+{
+    double f = a * 2.5;
+    return (int)f;
+}
+
+int main()
+{
+    __m256d v = _mm256_set1_pd(1.5);
+    v = _mm256_mul_pd(v, v);
+    return scale((int)v[3]);
+}
+""")
+    exec_path = tmp_path / "t"
+    subprocess.run(["gcc", "-g", "-O0", "-Wall", "-mavx", str(tmp_path / "t.c"), "-o", str(exec_path)], check=True)
+
+    # vex encoded sse code of the called function clears upper halves of ymm registers, all of them are put back
+    result = subprocess.run(
+        ["target/debug/dbg", str(exec_path)],
+        input="b 13\nr\ninfo all-registers\ncall scale(3)\ninfo all-registers\n",
+        capture_output=True,
+        text=True,
+        timeout=10,
+        env={**os.environ, "HOME": str(tmp_path)},
+    )
+    assert result.returncode == 0
+    lines = result.stdout.splitlines()
+    assert lines[0] == "breakpoint set"
+    call_line = lines.index("int scale(3) = 7")
+    before, after = lines[1:call_line], lines[call_line + 1:]
+    assert "ymm0 0x4002000000000000400200000000000040020000000000004002000000000000" in before
+    assert before == after
@@ -0,0 +1,37 @@
+from tests import Step
+
+
+def test_display(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    x++;
+    x++;
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("info display", "no displays"),
+            Step("display x"),
+            Step("info display", "1: x (not evaluated)"),
+            Step("b 6", "breakpoint set"),
+            Step("b 8", "breakpoint set"),
+            Step("r", "1: int x = 1"),
+            Step("display y", "2: y = <error: y not found>"),
+            Step("display &x", "3: int* &x = 0x"),
+            Step("info display", ["1: x (ok)", "2: y (failed)", "3: &x (ok)"]),
+            Step("undisplay 2", "display removed"),
+            Step("undisplay 5", "display not found"),
+            Step("undisplay 3", "display removed"),
+            Step("c", "1: int x = 3"),
+            Step("display", "1: int x = 3"),
+            Step("c", "3"),
+            Step("display x", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
@@ -0,0 +1,151 @@
+from tests import Step
+
+FORK_CODE = """#include <stdio.h>
+#include <unistd.h>
+#include <sys/wait.h>
+
+int main()
+{
+    pid_t pid = fork();
+    if (pid == 0) {
+        printf("child\\n");
+        return 0;
+    }
+    waitpid(pid, NULL, 0);
+    printf("parent\\n");
+    return 0;
+}
+"""
+
+
+def test_follow_fork_parent(debugger):
+    debugger(
+        code=FORK_CODE,
+        steps=[
+            Step("b 9", "breakpoint set"),
+            Step("b 13", "breakpoint set"),
+            Step("r", ["child", "program received signal SIGCHLD"]),
+            Step("c"),
+            Step("loc", "t.c:13"),
+            Step("c", "parent"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_follow_fork_child(debugger):
+    debugger(
+        code=FORK_CODE,
+        steps=[
+            Step("set follow-fork-mode fork", "invalid value"),
+            Step("set follow-fork-mode child"),
+            Step("b 9", "breakpoint set"),
+            Step("b 13", "breakpoint set"),
+            Step("r"),
+            Step("loc", "t.c:9"),
+            Step("p pid", "pid_t pid = 0"),
+            Step("c", ["child", "parent"]),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_follow_vfork_child(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <unistd.h>
+#include <sys/wait.h>
+
+void work(int n)
+{
+    printf("parent %d\\n", n);
+}
+
+int main(int argc, char *argv[])
+{
+    if (argc > 1) {
+        printf("exec %s\\n", argv[1]);
+        return 0;
+    }
+    pid_t pid = vfork();
+    if (pid == 0) {
+        execl("/proc/self/exe", "t", "again", NULL);
+        _exit(1);
+    }
+    waitpid(pid, NULL, 0);
+    work(2);
+    return 0;
+}
+""",
+        steps=[
+            Step("set follow-fork-mode child"),
+            Step("b work", "breakpoint set"),
+            Step("r", "executing new program"),
+            # parent shared memory with int3 until exec, it doesn't stop at work without the debugger
+            Step("c", ["exec again", "parent 2"]),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_step_in_fork(debugger):
+    # parent is stepped past the fork, instead of running to waitpid
+    debugger(
+        code="""#include <unistd.h>
+#include <sys/wait.h>
+
+int main()
+{
+    pid_t pid = fork();
+    if (pid == 0) {
+        sleep(1);
+        return 0;
+    }
+    waitpid(pid, NULL, 0);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("step-in"),
+            Step("loc", "t.c:7"),
+            Step("c", "program received signal SIGCHLD"),
+            Step("c"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_follow_exec(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <unistd.h>
+
+int main(int argc, char *argv[])
+{
+    if (argc > 1) {
+        printf("exec %s\\n", argv[1]);
+        return 0;
+    }
+    execl("/proc/self/exe", "t", "again", NULL);
+    return 1;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r", "executing new program"),
+            Step("loc", "t.c:7"),
+            Step("p argc", "int argc = 2"),
+            Step("kill", "program killed"),
+            Step("l", "t.c:7"),
+            Step("r", "executing new program"),
+            Step("c", "exec again"),
+            Step("q"),
+        ],
+        filename="t"
+    )
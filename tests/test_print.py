@@ -1,3 +1,8 @@
+import shutil
+import subprocess
+
+import pytest
+
 from tests import Step
 
 
@@ -90,6 +95,45 @@ int main()
     )
 
 
+def test_print_scalar_members(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <stdbool.h>
+
+enum Color { RED, GREEN, BLUE };
+
+struct Flags {
+    char c;
+    bool flag;
+    enum Color color;
+};
+
+int main()
+{
+    char c = 'x';
+    bool flag = true;
+    enum Color color = BLUE;
+    struct Flags flags = {'x', true, BLUE};
+    struct Flags all[2] = {{'a', false, RED}, {'b', true, GREEN}};
+    printf("%c %d %d %c %c\\n", c, flag, color, flags.c, all[1].c);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 20", "breakpoint set"),
+            Step("r"),
+            Step("p c", "char c = 120"),
+            Step("p flag", "bool flag = true"),
+            Step("p color", "enum Color color = BLUE"),
+            Step("p flags", "Flags flags = { c = 120, flag = true, color = BLUE }"),
+            Step("p all", "Flags[2] all = [{ c = 97, flag = false, color = RED }, { c = 98, flag = true, color = GREEN }]"),
+            Step("p/x flags", "Flags flags = { c = 0x78, flag = true, color = 0x2 }"),
+            Step("c"),
+            Step("q"),
+        ]
+    )
+
+
 def test_print_node(debugger):
     debugger(
         code="""#include <stdio.h>
@@ -264,3 +308,1022 @@ int main()
             Step("q"),
         ]
     )
+
+
+@pytest.mark.parametrize("cflags", [["-no-pie"], ["-pie"]])
+def test_global(debugger, cflags):
+    debugger(
+        code="""#include <stdio.h>
+
+int counter = 42;
+static const char *name = "counter";
+
+int main()
+{
+    counter++;
+    printf("%s = %d\\n", name, counter);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 9", "breakpoint set"),
+            Step("r"),
+            Step("p counter", "int counter = 43"),
+            Step("p name", 'const char* name = "counter"'),
+            Step("c", "counter = 43"),
+            Step("q"),
+        ],
+        cflags=cflags
+    )
+
+
+def test_print_raw(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+struct Point {
+    int x;
+    char name[16];
+};
+
+int main()
+{
+    int i = 123;
+    struct Point p = {-1, "raw bytes"};
+    printf("%d %s\\n", i, p.name);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 12", "breakpoint set"),
+            Step("r"),
+            Step("p/r i", ["int i (4 bytes)", ": 7b 00 00 00 "]),
+            Step("print/r p", ["Point p (20 bytes)", ": ff ff ff ff 72 61 77 20 62 79 74 65 73 00 00 00  |....raw bytes...|", ": 00 00 00 00 "]),
+            # value without address is dumped without prefix
+            Step("p/r (short)123", ["short (short)123 (2 bytes)", "7b 00 "], not_expected_output="0x"),
+            Step("p/r", "invalid path"),
+            Step("c"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_frame_base_cfa(debugger):
+    # gcc sets DW_AT_frame_base to DW_OP_call_frame_cfa, over-aligned local makes the frame realign the stack
+    debugger(
+        code="""#include <stdio.h>
+
+int compute(int n)
+{
+    int aligned __attribute__((aligned(64))) = n * 2;
+    printf("%d\\n", aligned);
+    return aligned;
+}
+
+int main()
+{
+    int local = 7;
+    int r = compute(local);
+    return r == 14 ? 0 : 1;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("p aligned", "int aligned = 14"),
+            Step("p n", "int n = 7"),
+            Step("info scope", ["aligned: frame base offset", "n: frame base offset"]),
+            Step("frame 1", "#1 main at"),
+            Step("p local", "int local = 7"),
+            Step("c"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_cast(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+struct node {
+    int value;
+    struct node *next;
+};
+
+int main()
+{
+    struct node second = {2, NULL};
+    struct node first = {1, &second};
+    void *opaque = &first;
+    int x = 300;
+    int neg = -2;
+    int arr[3] = {7, 8, 9};
+    __int128 big = 5;
+    _Complex double z = 1.0;
+    printf("%p %d %d %d %ld %f\\n", opaque, x, neg, arr[0], (long)big, __real__ z);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 19", "breakpoint set"),
+            Step("r"),
+            Step("p *(struct node *)opaque", "node *(struct node *)opaque = { value = 1, next = 0x"),
+            Step("p *(struct node *)first.next", "node *(struct node *)next = { value = 2, next = null }"),
+            Step("p (char)x", "char (char)x = 44"),
+            Step("p (bool)x", "bool (bool)x = true"),
+            Step("p (long)neg", "long (long)neg = -2"),
+            Step("p/x (unsigned int)neg", "unsigned int (unsigned int)neg = 0xfffffffe"),
+            Step("p *(int *)arr", "int *(int *)arr = 7"),
+            Step("p *(int*)&x", "int *(int *)&x = 300"),
+            Step("p (struct foo *)opaque", "type struct foo not found"),
+            Step("p (struct node)opaque", "invalid cast"),
+            Step("p (long)big", "invalid cast"),
+            Step("p (long)z", "invalid cast"),
+            Step("p &(int)x", "value has no address"),
+            Step("c"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_address(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+struct Point {
+    int x;
+    int y;
+};
+
+int main()
+{
+    struct Point p = {1, 2};
+    int arr[4] = {1, 2, 3, 4};
+    register int r = 5;
+    printf("%d %d %d\\n", p.y, arr[2], r);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 13", "breakpoint set"),
+            Step("r"),
+            Step("p &p", "Point* &p = 0x"),
+            Step("p &p.y", "int* &y = 0x"),
+            Step("p &arr[2]", "int* &arr[2] = 0x"),
+            Step("p *&arr[2]", "int *&arr[2] = 3"),
+            Step("p r", "int r = 5"),
+            Step("p &r", "value has no address"),
+            Step("c"),
+            Step("q"),
+        ]
+    )
+
+
+def test_register_param(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+__attribute__((noinline)) int square(int x)
+{
+    return x * x;
+}
+
+int main()
+{
+    printf("%d\\n", square(7));
+    return 0;
+}
+""",
+        steps=[
+            Step("b square", "breakpoint set"),
+            Step("r"),
+            Step("p x", "int x = 7"),
+            Step("c", "49"),
+            Step("q"),
+        ],
+        cflags=["-O1"]
+    )
+
+
+def test_print_hex(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <stdint.h>
+
+enum Sign { NEG = -1, POS = 1 };
+
+int main()
+{
+    int8_t a = -1;
+    int16_t b = -2;
+    int32_t c = -3;
+    int64_t d = -4;
+    uint32_t u = 255;
+    enum Sign s = NEG;
+    int *p = (int *)0x10;
+    printf("%d %d %d %ld %u %d %p\\n", a, b, c, d, u, s, p);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 15", "breakpoint set"),
+            Step("r"),
+            Step("p/x a", "int8_t a = 0xff"),
+            Step("p/x b", "int16_t b = 0xfffe"),
+            Step("p/x c", "int32_t c = 0xfffffffd"),
+            Step("p/x d", "int64_t d = 0xfffffffffffffffc"),
+            Step("p/x u", "uint32_t u = 0xff"),
+            Step("p/x s", "enum Sign s = 0xffffffff"),
+            Step("p/d c", "int32_t c = -3"),
+            Step("p/d p", "int* p = 16"),
+            Step("p p", "int* p = 0x10"),
+            Step("c"),
+            Step("q"),
+        ]
+    )
+
+
+@pytest.mark.parametrize("cflags", [["-gdwarf-2"], ["-gdwarf-4"], ["-gdwarf-5"]])
+def test_dwarf_versions(debugger, cflags):
+    debugger(
+        code="""#include <stdio.h>
+
+enum Big { SMALL = 1, HUGE = 0xffffffffffffffffULL };
+
+struct Point {
+    int x;
+    int y;
+    union {
+        int i;
+        float f;
+    } u;
+};
+
+int main()
+{
+    struct Point p = {1, 2, {3}};
+    int arr[3] = {4, 5, 6};
+    enum Big b = HUGE;
+    printf("%d %d %d %d %d\\n", p.x, p.y, p.u.i, arr[1], b == HUGE);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 19", "breakpoint set"),
+            Step("r"),
+            Step("p p.y", "int y = 2"),
+            Step("p p.u.i", "int i = 3"),
+            Step("p arr", "int[3] arr = [4, 5, 6]"),
+            Step("p b", "enum Big b = HUGE"),
+            Step("c", "1 2 3 5 1"),
+            Step("q"),
+        ],
+        cflags=cflags
+    )
+
+
+def test_print_slice(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int arr[8] = {1, 2, 3, 4, 5, 6, 7, 8};
+    int m[2][3] = {{1, 2, 3}, {4, 5, 6}};
+    printf("%d %d\\n", arr[0], m[0][0]);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("p arr[2..5]", "int[3] arr[2..5] = [3, 4, 5]"),
+            Step("p arr[0..8]", "int[8] arr[0..8] = [1, 2, 3, 4, 5, 6, 7, 8]"),
+            Step("p arr[3..3]", "int[0] arr[3..3] = []"),
+            Step("p arr[2..5][1]", "int arr[2..5][1] = 4"),
+            Step("p m[1][1..3]", "int[2] m[1][1..3] = [5, 6]"),
+            Step("p arr[5..9]", "invalid path"),
+            Step("p arr[5..2]", "invalid path"),
+            Step("c"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_string_settings(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    const char *s = "hello\\tworld";
+    const char *empty = "";
+    const char *nil = NULL;
+    printf("%s%s%p\\n", s, empty, nil);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 8", "breakpoint set"),
+            Step("r"),
+            Step("p s", 'const char* s = "hello\\tworld"'),
+            Step("p empty", 'const char* empty = ""'),
+            Step("p nil", "const char* nil = null"),
+            Step("set print string-length 5"),
+            Step("p s", 'const char* s = "hello"...'),
+            Step("p empty", 'const char* empty = ""'),
+            Step("set print string-length 11"),
+            Step("p s", 'const char* s = "hello\\tworld"', '...'),
+            Step("set print string-length 0"),
+            Step("set print string-escape off"),
+            Step("p s", 'const char* s = "hello\tworld"'),
+            Step("set print string-length -1", "invalid value"),
+            Step("set print string-escape maybe", "invalid value"),
+            Step("set print colors on", "invalid command"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_wide_string(debugger):
+    debugger(
+        code="""#include <stdio.h>
+#include <wchar.h>
+#include <uchar.h>
+
+int main()
+{
+    const wchar_t *w = L"wide ключ";
+    const char16_t *u16 = u"utf-16 \\U0001F600";
+    const char32_t *u32 = U"utf-32";
+    wchar_t *nil = NULL;
+    printf("%ls %p %p %p\\n", w, u16, u32, nil);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 11", "breakpoint set"),
+            Step("r"),
+            Step("p w", 'const wchar_t* w = "wide ключ"'),
+            Step("p u16", 'const char16_t* u16 = "utf-16 😀"'),
+            Step("p u32", 'const char32_t* u32 = "utf-32"'),
+            Step("p nil", "wchar_t* nil = null"),
+            Step("set print string-length 4"),
+            Step("p w", 'const wchar_t* w = "wide"...'),
+            Step("set w = \"x\"", "invalid value"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_unsupported_register(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+__attribute__((noinline)) double sum(const double *a, int n)
+{
+    double s = 0;
+    for (int i = 0; i < n; i++) {
+        s += a[i];
+    }
+    return s;
+}
+
+int main()
+{
+    double a[] = {1, 2, 3};
+    printf("%f\\n", sum(a, 3));
+    return 0;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("p s", "unsupported vector register xmm0 (dwarf 17), supported registers: rax, rdx"),
+            Step("q"),
+        ],
+        cflags=["-O1"]
+    )
+
+
+def test_print_incomplete_pointers(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+struct Opaque;
+
+int log_message(const char *fmt, ...)
+{
+    return 0;
+}
+
+int main()
+{
+    int i = 1;
+    void *p = &i;
+    struct Opaque *o = (struct Opaque *)&i;
+    int (*f)(const char *, ...) = log_message;
+    printf("%p %p %p\\n", p, (void *)o, (void *)f);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 17", "breakpoint set"),
+            Step("r"),
+            Step("p p", "void* p = 0x"),
+            Step("p *p", "can't dereference void pointer"),
+            Step("p o", "Opaque* o = 0x"),
+            Step("p *o", "Opaque *o = {}"),
+            Step("p f", "int (const char*, ...) f = log_message"),
+            Step("p/x f", "int (const char*, ...) f = 0x"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_qualified_global(debugger, tmp_path):
+    lib_path = tmp_path / "lib.o"
+    (tmp_path / "lib.c").write_text("""static int counter = 2;
+static int shared = 5;
+
+int lib_counter(void)
+{
+    return counter + shared;
+}
+""")
+    subprocess.run(["gcc", "-g", "-O0", "-c", str(tmp_path / "lib.c"), "-o", str(lib_path)], check=True)
+    ns_path = tmp_path / "ns.o"
+    (tmp_path / "ns.cpp").write_text("""namespace ns { int counter = 3; }
+namespace { int hidden = 4; }
+static int shared = 6;
+
+extern "C" int ns_counter()
+{
+    return ns::counter + hidden + shared;
+}
+""")
+    subprocess.run(["g++", "-g", "-O0", "-c", str(tmp_path / "ns.cpp"), "-o", str(ns_path)], check=True)
+
+    debugger(
+        code="""#include <stdio.h>
+
+int lib_counter(void);
+int ns_counter(void);
+
+static int counter = 1;
+
+int main()
+{
+    printf("%d %d %d\\n", counter, lib_counter(), ns_counter());
+    return 0;
+}
+""",
+        steps=[
+            Step("b 10", "breakpoint set"),
+            Step("b lib_counter", "breakpoint set"),
+            Step("r"),
+            Step("p counter", "int counter = 1"),
+            Step("p t.c::counter", "int counter = 1"),
+            Step("p lib.c::counter", "int counter = 2"),
+            Step("p ns::counter", "int counter = 3"),
+            Step("p ns.cpp::ns::counter", "int counter = 3"),
+            Step("p hidden", "int hidden = 4"),
+            Step("p shared", "shared is ambiguous, qualify it with a unit"),
+            Step("p ns.cpp::shared", "int shared = 6"),
+            Step("p nope.c::counter", "nope.c::counter not found"),
+            Step("c"),
+            Step("p counter", "int counter = 2"),
+            Step("p shared", "int shared = 5"),
+            Step("c", "1 7 13"),
+            Step("q"),
+        ],
+        filename="t",
+        cflags=[str(lib_path), str(ns_path)]
+    )
+
+
+def test_info_args_and_locals(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int counter = 3;
+
+int sum(int a, int b)
+{
+    int result = a + b;
+    return result + counter;
+}
+
+int main()
+{
+    printf("%d\\n", sum(1, 2));
+    return 0;
+}
+""",
+        steps=[
+            Step("info args", "program not running"),
+            Step("b 8", "breakpoint set"),
+            Step("r"),
+            Step("info args", ["int a = 1", "int b = 2"], not_expected_output="result"),
+            # arguments are not listed as locals, extra line would be read by the next step
+            Step("info locals", "int result = 3", not_expected_output="int a"),
+            Step("info locals", "int result = 3", not_expected_output="int b"),
+            Step("info globals", "int counter = 3"),
+            Step("up", "#1 main"),
+            Step("info args", "no arguments"),
+            Step("info locals", "no locals"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_block_scope(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    int sum = 0;
+    for (int i = 0; i < 2; i++) {
+        int x = 10 + i;
+        sum += x;
+    }
+    printf("%d\\n", sum + x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("b 9", "breakpoint set"),
+            Step("b 11", "breakpoint set"),
+            Step("r"),
+            Step("p i", "i not found"),
+            Step("c"),
+            Step("p i", "int i = 0"),
+            Step("p x", "int x = 10"),
+            Step("info locals", ["int i = 0", "int sum = 0", "int x = 10"]),
+            Step("c"),
+            Step("p x", "int x = 11"),
+            Step("c"),
+            Step("p x", "int x = 1"),
+            Step("p i", "i not found"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_shadowed_var(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int x = 0;
+
+int main()
+{
+    int x = 1;
+    {
+        int x = 2;
+        {
+            int x = 3;
+            printf("%d\\n", x);
+        }
+        printf("%d\\n", x);
+    }
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 12", "breakpoint set"),
+            Step("b 14", "breakpoint set"),
+            Step("b 16", "breakpoint set"),
+            Step("r"),
+            Step("p x", "int x = 3"),
+            Step("c"),
+            Step("p x", "int x = 2"),
+            Step("c"),
+            Step("p x", "int x = 1"),
+            Step("info globals", "int x = 0"),
+            Step("q"),
+        ]
+    )
+
+
+def test_ptype(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+typedef struct {
+    char c;
+    int x;
+    char tail;
+} Foo;
+
+union Value {
+    char c;
+    double d;
+};
+
+int main()
+{
+    Foo foo = {1, 2, 3};
+    union Value value = {.d = 1.5};
+    int *p = &foo.x;
+    return foo.x + value.c + *p;
+}
+""",
+        steps=[
+            Step("ptype foo", "program not running"),
+            Step("b 19", "breakpoint set"),
+            Step("r"),
+            Step("ptype foo", ["type = struct {", "char c;", "int x;", "char tail;", "}"]),
+            Step("ptype/o foo", [
+                "type = struct {",
+                "/* offset: 0, size: 1 */ char c;",
+                "/* XXX 3-byte hole */",
+                "/* offset: 4, size: 4 */ int x;",
+                "/* offset: 8, size: 1 */ char tail;",
+                "/* XXX 3-byte padding */",
+                "} /* size: 12 */",
+            ]),
+            Step("ptype/o value", [
+                "type = union Value {",
+                "/* offset: 0, size: 1 */ char c;",
+                "/* offset: 0, size: 8 */ double d;",
+                "} /* size: 8 */",
+            ]),
+            Step("ptype p", "type = int*"),
+            Step("ptype *p", "type = int"),
+            Step("q"),
+        ]
+    )
+
+
+def test_ptype_enum(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+enum Color {
+    RED,
+    GREEN = 5,
+    BLUE = -1,
+};
+
+int main()
+{
+    enum Color color = GREEN;
+    return color;
+}
+""",
+        steps=[
+            Step("b 12", "breakpoint set"),
+            Step("r"),
+            Step("ptype color", ["type = enum Color {", "RED = 0,", "GREEN = 5,", "BLUE = -1,", "}"]),
+            Step("q"),
+        ]
+    )
+
+
+def test_whatis(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+typedef struct {
+    int x;
+} Foo;
+
+struct node {
+    struct node *next;
+};
+
+int main()
+{
+    Foo foo = {1};
+    Foo *p = &foo;
+    struct node node = {NULL};
+    struct { int y; } anon = {2};
+    return foo.x + p->x + anon.y + (node.next != NULL);
+}
+""",
+        steps=[
+            Step("b 17", "breakpoint set"),
+            Step("r"),
+            Step("whatis foo", "type = Foo"),
+            Step("whatis p", "type = Foo*"),
+            Step("whatis *p", "type = Foo"),
+            Step("whatis node", "type = node"),
+            Step("whatis node.next", "type = node*"),
+            Step("whatis anon", "type = struct {...}"),
+            Step("whatis foo.x", "type = int"),
+            Step("q"),
+        ]
+    )
+
+
+@pytest.mark.skipif(shutil.which("clang") is None, reason="clang is not installed")
+def test_print_global_dwarf5(debugger):
+    # clang addresses globals by index into .debug_addr (DW_OP_addrx)
+    debugger(
+        code="""#include <stdio.h>
+
+int counter = 5;
+static const char *name = "foo";
+
+int main()
+{
+    counter++;
+    printf("%s %d\\n", name, counter);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 9", "breakpoint set"),
+            Step("r"),
+            Step("p counter", "int counter = 6"),
+            Step("p name", 'const char* name = "foo"'),
+            Step("q"),
+        ],
+        cflags=["-gdwarf-5"],
+        compiler="clang"
+    )
+
+
+def test_print_thread_local(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+__thread int tv = 5;
+int g = 1;
+
+int main()
+{
+    tv++;
+    printf("%d %d\\n", tv, g);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 9", "breakpoint set"),
+            Step("r"),
+            Step("p tv", "thread-local variables are not supported"),
+            Step("p g", "int g = 1"),
+            # thread-local variable is skipped, like optimized out ones
+            Step("p", "int g = 1"),
+            Step("c", "6 1"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+@pytest.mark.skipif(shutil.which("clang") is None, reason="clang is not installed")
+def test_print_struct_dwarf5_clang(debugger):
+    # clang shares member locations and sizes between entries with DW_FORM_implicit_const
+    debugger(
+        code="""#include <stdio.h>
+
+struct point {
+    int x;
+    int y;
+    long z;
+};
+
+int main()
+{
+    struct point p = {1, 2, 3};
+    printf("%d\\n", p.x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 12", "breakpoint set"),
+            Step("r"),
+            Step("p p", "point p = { x = 1, y = 2, z = 3 }"),
+            Step("p p.z", "z = 3"),
+            Step("q"),
+        ],
+        cflags=["-gdwarf-5"],
+        compiler="clang"
+    )
+
+
+@pytest.mark.skipif(shutil.which("clang") is None, reason="clang is not installed")
+def test_print_vla_clang(debugger):
+    # clang refers to artificial variable holding vla length (DW_AT_count is a reference)
+    debugger(
+        code="""int main()
+{
+    int n = 3;
+    int a[n];
+    a[0] = 7;
+    a[1] = 8;
+    a[2] = 9;
+    return a[0];
+}
+""",
+        steps=[
+            Step("b 8", "breakpoint set"),
+            Step("r"),
+            Step("p a", "int[] a = [7, 8, 9]"),
+            Step("p a[1]", "int a[1] = 8"),
+            Step("q"),
+        ],
+        compiler="clang"
+    )
+
+
+def test_summary_format(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+struct MyVec
+{
+    int len;
+    int cap;
+};
+
+typedef struct
+{
+    int x;
+    int y;
+} Point;
+
+int main()
+{
+    struct MyVec v = {3, 4};
+    Point p = {1, 2};
+    struct MyVec vs[2] = {{1, 2}, {5, 6}};
+    printf("%d %d %d\\n", v.len, p.x, vs[0].len);
+    return 0;
+}
+""",
+        steps=[
+            Step('set summary-format MyVec "{len}/{cap} items"'),
+            Step("b 20", "breakpoint set"),
+            Step("r"),
+            Step("p v", "MyVec v = 3/4 items"),
+            Step("p vs", "MyVec[2] vs = [1/2 items, 5/6 items]"),
+            # anonymous struct is found by typedef name
+            Step('set summary-format Point "({x}, {y}) {{z}} {z}"'),
+            Step("p p", "Point p = (1, 2) {z} <no field z>"),
+            Step('set summary-format Point ""'),
+            Step("p p", "Point p = { x = 1, y = 2 }"),
+            Step('set summary-format Point "{x"', "invalid format"),
+            Step("q"),
+        ]
+    )
+
+
+def test_print_utf_chars(debugger, tmp_path):
+    chars_path = tmp_path / "chars.o"
+    (tmp_path / "chars.cpp").write_text("""char8_t u8c = u8'a';
+char16_t u16c = u'b';
+char32_t u32c = U'\\u00e9';
+
+extern "C" int chars_sum()
+{
+    return u8c + u16c + u32c;
+}
+""")
+    subprocess.run(["g++", "-std=c++20", "-g", "-O0", "-c", str(tmp_path / "chars.cpp"), "-o", str(chars_path)], check=True)
+
+    debugger(
+        code="""#include <stdio.h>
+
+int chars_sum(void);
+
+int main()
+{
+    double _Complex z = 1.0;
+    printf("%d %f\\n", chars_sum(), __real__ z);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 8", "breakpoint set"),
+            Step("r"),
+            Step("p u8c", "char8_t u8c = 97"),
+            Step("p u16c", "char16_t u16c = 98"),
+            Step("p u32c", "char32_t u32c = 233"),
+            Step("set u16c = 99"),
+            Step("p/x u16c", "char16_t u16c = 0x63"),
+            Step("p z", "unsupported encoding DW_ATE_complex_float"),
+            Step("c", "429 1.000000"),
+            Step("q"),
+        ],
+        cflags=[str(chars_path)],
+        filename="t"
+    )
+
+
+def test_examine(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int a[10] = {1, 2, 3, 4, 5, 6, 7, 8, 9, -10};
+    char s[] = "Hi\\n";
+    short h = -2;
+    printf("%d %s %d\\n", a[0], s, h);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 8", "breakpoint set"),
+            Step("r"),
+            Step("x/10d a", [": 1 2 3 4 5 6 7 8", ": 9 -10"]),
+            Step("x a[1]", ": 0x00000002"),
+            Step("x/2xg &a", ": 0x0000000200000001 0x0000000400000003"),
+            Step("x/4c s", ": 'H' 'i' '\\n' '\\x00'"),
+            Step("x/4xb s", ": 0x48 0x69 0x0a 0x00"),
+            Step("x/uh h", ": 65534"),
+            Step("x/dh h", ": -2"),
+            Step("x 0", "can't access memory at 0x0"),
+            Step("x/18446744073709551615xg a", "invalid format"),
+            Step("c"),
+            Step("q"),
+        ]
+    )
+
+
+def test_disassemble(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int square(int x)
+{
+    return x * x;
+}
+
+long double half(long double x)
+{
+    return x / 2;
+}
+
+int main()
+{
+    printf("%d %Lf\\n", square(3), half(3));
+    return 0;
+}
+""",
+        steps=[
+            Step("b 5", "breakpoint set"),
+            Step("r"),
+            # int3 of the breakpoint is not shown
+            Step("disassemble", [
+                "<+0>: push rbp",
+                "<+1>: mov rbp, rsp",
+                "<+4>: mov dword ptr [rbp-0x4], edi",
+                "=> 0x",
+                "<+10>: imul eax, eax",
+                "<+13>: pop rbp",
+                "<+14>: ret",
+            ], not_expected_output="int3"),
+            Step("disassemble foo", "function foo not found"),
+            # x87 code of long double
+            Step("disas half", [
+                "<+0>: push rbp",
+                "<+1>: mov rbp, rsp",
+                "<+4>: fld st, tbyte ptr [rbp+0x10]",
+                "<+7>: fld st, tbyte ptr [0x",
+                "<+13>: fdivp",
+                "<+15>: pop rbp",
+                "<+16>: ret",
+            ], not_expected_output="(bad)"),
+            Step("disas main", [
+                "<+0>: push rbp",
+                "<+1>: mov rbp, rsp",
+                "<+4>: sub rsp, 0x10",
+                "<+8>: fld st, tbyte ptr [0x",
+            ]),
+            Step("c"),
+            Step("q"),
+        ],
+        cflags=["-fcf-protection=none"]
+    )
+
+
+def test_print_protected_memory(debugger):
+    debugger(
+        code="""#include <string.h>
+#include <sys/mman.h>
+
+int main()
+{
+    char *page = mmap(0, 4096, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+    strcpy(page, "hidden");
+    mprotect(page, 4096, PROT_NONE);
+    return page == 0;
+}
+""",
+        steps=[
+            Step("b 9", "breakpoint set"),
+            Step("r"),
+            # program itself can't read the page, but debugger can
+            Step("p page", 'char* page = "hidden"'),
+            Step("p page[5]", "char page[5] = 110"),
+            Step("q"),
+        ]
+    )
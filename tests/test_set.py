@@ -24,11 +24,28 @@ int main()
             Step("p i", "int i = 234"),
             Step("set f 9.81"),
             Step("p f", "float f = 9.81"),
+            Step("set f 5"),
+            Step("p f", "float f = 5"),
+            Step("set f -2.5e3"),
+            Step("p f", "float f = -2500"),
+            Step("set f 0x10"),
+            Step("p f", "float f = 16"),
+            Step("set f -inf"),
+            Step("p f", "float f = -inf"),
+            Step("set f nan"),
+            Step("p f", "float f = NaN"),
+            Step("set f abc", "invalid value"),
+            Step("set f 9.81"),
             Step("set b false"),
             Step("p b", "bool b = false"),
             Step("set b true"),
             Step("p b", "bool b = true"),
             Step("set b 123", "invalid value"),
+            Step("set b 0"),
+            Step("p b", "bool b = false"),
+            Step("set b 1"),
+            Step("p b", "bool b = true"),
+            Step("set b 2", "invalid value"),
             Step("set *p = 345"),
             Step("p *p", "int *p = 345"),
             Step("p i", "int i = 345"),
@@ -42,6 +59,54 @@ int main()
     )
 
 
+def test_set_array_element(debugger):
+    debugger(
+        code="""typedef int Row[3];
+
+struct S {
+    const int vals[2];
+    Row rows[2];
+};
+
+int main()
+{
+    int arr[5] = {0};
+    int grid[2][3] = {{0}};
+    Row row = {1, 2, 3};
+    int *p = arr;
+    char buf[4] = "abc";
+    struct S st = {{1, 2}, {{0}}};
+    void *v = arr;
+    int *np = 0;
+    return arr[0] + grid[0][0] + row[0] + p[0] + buf[0] + st.vals[0] + (v != 0) + (np != 0);
+}
+""",
+        steps=[
+            Step("b 19", "breakpoint set"),
+            Step("r"),
+            Step("set arr[3] 42"),
+            Step("p arr[3]", "int arr[3] = 42"),
+            Step("set arr[5] 1", "invalid path"),
+            Step("set grid[1][2] 7"),
+            Step("p grid[1]", "int[3] grid[1] = [0, 0, 7]"),
+            Step("set row[1] 9"),
+            Step("p row[0..2]", "int[2] row[0..2] = [1, 9]"),
+            Step("set p[1] 5"),
+            Step("p p[1]", "int p[1] = 5"),
+            Step("p p[0..4]", "int[4] p[0..4] = [0, 5, 0, 42]"),
+            Step("set p[4611686018427387904] 1", "invalid path"),
+            Step("set np[1] 1", "can't dereference null pointer"),
+            Step("set buf[1] 120"),
+            Step("p buf", "char[4] buf = [97, 120, 99, 0]"),
+            Step("set st.rows[1][2] 4"),
+            Step("p st.rows[1][2]", "int rows[1][2] = 4"),
+            Step("p st.vals[1]", "const int vals[1] = 2"),
+            Step("p v[0]", "can't dereference void pointer"),
+            Step("q"),
+        ]
+    )
+
+
 def test_set_field(debugger):
     debugger(
         code="""#include <stdio.h>
@@ -70,6 +135,47 @@ int main()
     )
 
 
+def test_set_nested_field(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+struct Inner {
+    char tag;
+    int count;
+    double ratio;
+};
+
+typedef struct {
+    long id;
+    struct Inner inner;
+    const struct Inner cinner;
+} Outer;
+
+int main()
+{
+    Outer o = {1, {'a', 2, 0.5}, {'b', 3, 1.5}};
+    Outer *p = &o;
+    printf("%d %d\\n", o.inner.count, p->cinner.count);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 19", "breakpoint set"),
+            Step("r"),
+            Step("set o.inner.count 7"),
+            Step("p o.inner.count", "int count = 7"),
+            Step("set p.inner.ratio 2.5"),
+            Step("set p.cinner.tag 120"),
+            Step("p o", "Outer o = { id = 1, inner = { tag = 97, count = 7, ratio = 2.5 }, cinner = { tag = 120, count = 3, ratio = 1.5 } }"),
+            Step("set o.inner = p.cinner"),
+            Step("p o.inner", "Inner inner = { tag = 120, count = 3, ratio = 1.5 }"),
+            Step("set o.inner 5", "invalid path"),
+            Step("set o.inner = o", "invalid value"),
+            Step("q"),
+        ]
+    )
+
+
 def test_operators(debugger):
     debugger(
         code="""#include <stdio.h>
@@ -140,6 +246,43 @@ int main()
     )
 
 
+def test_sparse_enum(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+enum Sparse
+{
+    A = -1,
+    B = 1000000,
+};
+
+int main()
+{
+    enum Sparse s = A;
+    printf("%d\\n", s);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 12", "breakpoint set"),
+            Step("r"),
+            Step("p s", "enum Sparse s = A"),
+            Step("p/x s", "enum Sparse s = 0xffffffff"),
+            Step("set s = B"),
+            Step("p s", "enum Sparse s = B"),
+            Step("set s = A"),
+            Step("p s", "enum Sparse s = A"),
+            # values without a variant are kept as integers
+            Step("set s = -5"),
+            Step("p s", "enum Sparse s = -5"),
+            Step("set s = 1000000"),
+            Step("p s", "enum Sparse s = B"),
+            Step("set s = 4294967296", "invalid value"),
+            Step("q"),
+        ]
+    )
+
+
 def test_union(debugger):
     debugger(
         code="""#include <stdio.h>
@@ -204,6 +347,8 @@ int main()
             Step("b 22", "breakpoint set"),
             Step("r"),
             Step("p a[1][1][1]", "int a[1][1][1] = 14"),
+            Step("p a[0x1][0x1][0x1]", "int a[1][1][1] = 14"),
+            Step("p a[-1]", "negative number"),
             Step("p a[0]", "int[3][3] a[0] = [[1, 2, 3], [4, 5, 6], [7, 8, 9]]"),
             Step("set a[1][1][1] = 100"),
             Step("p a[1][1]", "int[3] a[1][1] = [13, 100, 15]"),
@@ -265,8 +410,143 @@ int main()
             Step("p op", "Operation op = add"),
             Step("set op = sub"),
             Step("p op", "Operation op = sub"),
+            Step("set op = &add"),
+            Step("p op", "Operation op = add"),
+            Step("set op = &sub"),
+            Step("p op", "Operation op = sub"),
             Step("set op = mul", "invalid value"),
+            Step("set op = &mul", "invalid value"),
             Step("c", "2"),
             Step("q"),
         ]
     )
+
+
+def test_set_from_var(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+typedef struct {
+    int x;
+    int y;
+} Point;
+
+int main()
+{
+    int a = 1;
+    int b = 2;
+    int *p = NULL;
+    double d = 1.5;
+    Point p1 = {1, 2};
+    Point p2 = {3, 4};
+    printf("%d %d %p %f %d %d\\n", a, b, p, d, p1.x, p2.y);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 17", "breakpoint set"),
+            Step("r"),
+            Step("set a = b"),
+            Step("p a", "int a = 2"),
+            Step("set p = &b"),
+            Step("p *p", "int *p = 2"),
+            Step("set a = p2.x"),
+            Step("p a", "int a = 3"),
+            Step("set p1 = p2"),
+            Step("p p1", "Point p1 = { x = 3, y = 4 }"),
+            Step("set a = p1", "invalid value"),
+            Step("set a = d", "invalid value"),
+            Step("set a = z", "invalid value"),
+            Step("set d = nan"),
+            Step("p d", "double d = NaN"),
+            Step("q"),
+        ]
+    )
+
+
+def test_func_typedef_field(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+typedef int (*handler_t)(int);
+
+struct Ops {
+    handler_t cb;
+    const handler_t ccb;
+    handler_t table[2];
+};
+
+int inc(int x)
+{
+    return x + 1;
+}
+
+int dec(int x)
+{
+    return x - 1;
+}
+
+int main()
+{
+    struct Ops ops = {inc, inc, {inc, inc}};
+    volatile handler_t vh = inc;
+    printf("%d %d %d %d\\n", ops.cb(1), ops.ccb(1), ops.table[1](1), vh(1));
+    return 0;
+}
+""",
+        steps=[
+            Step("b 25", "breakpoint set"),
+            Step("r"),
+            Step("p ops.cb", "handler_t cb = inc"),
+            Step("set ops.cb = dec"),
+            Step("set ops.ccb = dec"),
+            Step("set ops.table[1] = dec"),
+            Step("set vh = dec"),
+            Step("p ops", "Ops ops = { cb = dec, ccb = dec, table = [inc, dec] }"),
+            Step("p vh", "volatile handler_t vh = dec"),
+            Step("set ops.cb = mul", "invalid value"),
+            Step("c", "0 0 0 0"),
+            Step("q"),
+        ]
+    )
+
+
+def test_set_radix(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 10;
+    unsigned short c = 255;
+    int a[2] = {8, -1};
+    printf("%d %d\\n", x, c);
+    return 0;
+}
+""",
+        steps=[
+            Step("set radix 2", "invalid value"),
+            Step("set output-radix 16"),
+            Step("b 8", "breakpoint set"),
+            Step("r"),
+            Step("p x", "int x = 0xa"),
+            Step("p/d x", "int x = 10"),
+            Step("p a", "int[2] a = [0x8, 0xffffffff]"),
+            Step("set output-radix 8"),
+            Step("p c", "c = 0377"),
+            Step("set input-radix 16"),
+            Step("set x = 10"),
+            Step("p/d x", "int x = 16"),
+            Step("set x = ff"),
+            Step("p/d x", "int x = 255"),
+            Step("set x = -0x10"),
+            Step("p/d x", "int x = -16"),
+            Step("set c = 10000", "invalid value"),
+            Step("set radix 10"),
+            Step("set x = 12"),
+            Step("p x", "int x = 12"),
+            Step("c", "12 255"),
+            Step("q"),
+        ],
+        filename="t"
+    )
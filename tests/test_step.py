@@ -34,6 +34,73 @@ int main()
     )
 
 
+def test_step_loop_back_edge(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int sum = 0;
+    for (int i = 0; i < 3; i++) {
+        sum += i;
+    }
+    printf("%d\\n", sum);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("step"),
+            Step("loc", "t.c:6"),  # increment is placed after the body
+            Step("step"),
+            Step("loc", "t.c:7"),
+            Step("p sum", "int sum = 0"),
+            Step("step"),
+            Step("step"),
+            Step("loc", "t.c:7"),
+            Step("p sum", "int sum = 1"),
+            Step("rm t.c:7", "breakpoint removed"),
+            Step("step"),
+            Step("loc", "t.c:6"),
+            Step("step"),
+            Step("loc", "t.c:9"),
+            Step("c", "3"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_step_from_adjacent_breakpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    x++;
+    x++;
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("step"),
+            Step("loc", "t.c:7"),
+            Step("p x", "int x = 2"),
+            Step("step"),
+            Step("loc", "t.c:8"),
+            Step("c", "3"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
 def test_step_in(debugger):
     debugger(
         code="""#include <stdio.h>
@@ -92,7 +159,188 @@ int main()
             Step("step-out"),
             Step("loc", "t.c:11"),
             Step("step-out", "10"),
-            Step("stop", "invalid command"),  # assert program completed
+            Step("stop", "program not running"),  # assert program completed
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_step_out_without_frame_pointer(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int foo(int x)
+{
+    int y = x * 2;
+    return y + 1;
+}
+
+int bar(int x)
+{
+    return foo(x) + 1;
+}
+
+int main()
+{
+    int y = bar(5);
+    printf("%d\\n", y);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 6", "breakpoint set"),
+            Step("r"),
+            Step("step-out"),
+            Step("loc", "t.c:11"),
+            Step("step-out"),
+            Step("loc", "t.c:17"),
+            Step("c", "12"),
+            Step("q"),
+        ],
+        filename="t",
+        cflags=["-fomit-frame-pointer"]
+    )
+
+
+def test_step_in_without_cet(debugger):
+    # without endbr64 the body of a small function starts right after push %rbp; mov %rsp,%rbp
+    debugger(
+        code="""#include <stdio.h>
+
+int bar(void)
+{
+    return 42;
+}
+
+int main()
+{
+    int y = bar();
+    printf("%d\\n", y);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 10", "breakpoint set"),
+            Step("r"),
+            Step("step-in"),
+            Step("loc", "t.c:5"),
+            Step("info line-table bar", ["t.c:4 (prologue)", "t.c:5", "t.c:6 (epilogue)"]),
+            Step("c", "42"),
+            Step("q"),
+        ],
+        filename="t",
+        cflags=["-fcf-protection=none"]
+    )
+
+
+def test_next_over_recursive_call(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int fact(int n)
+{
+    if (n <= 1)
+        return 1;
+    int rest = fact(n - 1);
+    return n * rest;
+}
+
+int main()
+{
+    int x = fact(4);
+    printf("%d\\n", x);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 13", "breakpoint set"),
+            Step("r"),
+            Step("step-in"),
+            Step("loc", "t.c:5"),
+            Step("next"),
+            Step("loc", "t.c:7"),
+            # recursive calls hit traps of the next lines in deeper frames
+            Step("n"),
+            Step("loc", "t.c:8"),
+            Step("p n", "int n = 4"),
+            Step("p rest", "int rest = 6"),
+            Step("n"),
+            Step("loc", "t.c:14"),
+            Step("p x", "int x = 24"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_step_count(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int main()
+{
+    int x = 1;
+    x++;
+    x++;
+    x++;
+    printf("%d\\n", x);
+    x++;
+    return 0;
+}
+""",
+        steps=[
+            Step("b 5", "breakpoint set"),
+            Step("b 9", "breakpoint set"),
+            Step("r"),
+            Step("n 2"),
+            Step("loc", "t.c:7"),
+            Step("p x", "int x = 2"),
+            Step("step 0", "invalid value"),
+            # breakpoint stops stepping earlier
+            Step("n 5"),
+            Step("loc", "t.c:9"),
+            Step("p x", "int x = 4"),
+            Step("next 10", "4"),
+            Step("loc", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_step_count_stops_at_signal(debugger):
+    debugger(
+        code="""#include <signal.h>
+
+int x = 0;
+
+void handler(int sig)
+{
+    x = 10;
+}
+
+int main()
+{
+    signal(SIGUSR1, handler);
+    x++;
+    raise(SIGUSR1);
+    x++;
+    x++;
+    x++;
+    return 0;
+}
+""",
+        steps=[
+            Step("b 13", "breakpoint set"),
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("n 5", "program received signal SIGUSR1"),
+            # signal is delivered on continue and the handler runs
+            Step("c"),
+            Step("p x", "int x = 1"),
+            Step("c"),
+            Step("stop", "program not running"),
             Step("q"),
         ],
         filename="t"
@@ -0,0 +1,167 @@
+import os
+import signal
+import subprocess
+import time
+
+from tests import Step
+
+
+def test_watch_condition(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int total = 0;
+
+void add(int x)
+{
+    total += x;
+}
+
+int main()
+{
+    for (int i = 1; i <= 5; i++) {
+        add(i);
+    }
+    printf("total = %d\\n", total);
+    return 0;
+}
+""",
+        steps=[
+            Step("watch total > 5", "program not running"),
+            Step("b 12", "breakpoint set"),
+            Step("r"),
+            Step("watch nope > 1", "nope not found"),
+            Step("watch total > 5", ["watchpoint total > 5 triggered", "int total = 6"]),
+            Step("loc", "t.c:8"),
+            Step("p x", "int x = 3"),
+            Step("watch total == 0xf", ["watchpoint total == 0xf triggered", "int total = 15"]),
+            Step("p x", "int x = 5"),
+            # already true, never flips again
+            Step("watch total >= 1.5", "total = 15"),
+            Step("c", "program not running"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_watch_hardware(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+struct point {
+    int x, y, z;
+};
+
+int counter = 0;
+int a, b, c, d;
+struct point p;
+
+void bump(int n)
+{
+    counter += n;
+}
+
+int main()
+{
+    long total = 0;
+    bump(0);
+    bump(2);
+    for (int i = 0; i < 3; i++) {
+        total += i;
+    }
+    bump(3);
+    printf("%d %ld\\n", counter, total);
+    return 0;
+}
+""",
+        steps=[
+            Step("watch counter", "program not running"),
+            Step("b 19", "breakpoint set"),
+            Step("r"),
+            Step("watch nope", "nope not found"),
+            Step("watch p", "watched variable must be aligned 1, 2, 4 or 8 bytes"),
+            Step("watch counter", "watchpoint 1 set"),
+            Step("watch total", "watchpoint 2 set"),
+            Step("watch a", "watchpoint 3 set"),
+            Step("watch b", "watchpoint 4 set"),
+            Step("watch c", "all 4 hardware watchpoints are used"),
+            Step("unwatch 4", "watchpoint removed"),
+            Step("unwatch 4", "watchpoint not found"),
+            Step("unwatch 3", "watchpoint removed"),
+            # bump(0) writes the same value
+            Step("c", ["watchpoint 1: counter", "old int counter = 0", "new int counter = 2"]),
+            Step("bt", ["#0 bump at t.c:14", "#1 main at t.c:20"]),
+            Step("c", ["watchpoint 2: total", "old long int total = 0", "new long int total = 1"]),
+            Step("unwatch 2", "watchpoint removed"),
+            Step("c", ["watchpoint 1: counter", "old int counter = 2", "new int counter = 5"]),
+            Step("p n", "int n = 3"),
+            Step("c", "5 3"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_watch_write_under_breakpoint(debugger):
+    debugger(
+        code="""#include <stdio.h>
+
+int counter = 0;
+
+int main()
+{
+    counter = 1;
+    counter = 2;
+    printf("%d\\n", counter);
+    return 0;
+}
+""",
+        steps=[
+            Step("b 7", "breakpoint set"),
+            Step("r"),
+            Step("watch counter", "watchpoint 1 set"),
+            # write is done while stepping over the breakpoint
+            Step("c", ["watchpoint 1: counter", "old int counter = 0", "new int counter = 1"]),
+            Step("c", ["watchpoint 1: counter", "old int counter = 1", "new int counter = 2"]),
+            Step("c", "2"),
+            Step("q"),
+        ],
+        filename="t"
+    )
+
+
+def test_watch_interrupted(tmp_path):
+    (tmp_path / "t.c").write_text("""int main()
+{
+    volatile int i = 0;
+    while (1) {
+        i++;
+    }
+    return 0;
+}
+""")
+    exec_path = tmp_path / "t"
+    subprocess.run(["gcc", "-g", "-O0", "-Wall", str(tmp_path / "t.c"), "-o", str(exec_path)], check=True)
+
+    # condition never becomes true, ctrl-c (sent to the whole process group) stops stepping instead of killing the debugger
+    with subprocess.Popen(
+        ["target/debug/dbg", str(exec_path)],
+        stdin=subprocess.PIPE,
+        stdout=subprocess.PIPE,
+        stderr=subprocess.STDOUT,
+        text=True,
+        start_new_session=True,
+        env={**os.environ, "HOME": str(tmp_path)},
+    ) as child:
+        child.stdin.write("b 3\nr\nwatch i < 0\n")
+        child.stdin.flush()
+        time.sleep(1)
+        os.killpg(child.pid, signal.SIGINT)
+        output, _ = child.communicate("p i\nq\n", timeout=10)
+
+    assert child.returncode == 0
+    # program is stopped somewhere in the loop
+    lines = output.splitlines()
+    assert lines[0] == "breakpoint set"
+    assert lines[1].startswith("volatile int i = ")